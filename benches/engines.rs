@@ -0,0 +1,67 @@
+//! Compares the bytecode engine with the syntax tree walker on the examples,
+//! run with `cargo bench --bench engines`
+
+use std::{fs, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+use speckylang::{run::{BufferIo, Engine, Limits}, Interpreter};
+
+const INPUT: [&str; 5] = ["5", "3", "+++[->++<]>.", "n", "80"];
+const MAX_STEPS: u64 = 1_000_000;
+const TIME_PER_EXAMPLE: Duration = Duration::from_secs(1);
+
+fn examples(directory: &Path, found: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(directory).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            examples(&path, found);
+        } else if path.extension().is_some_and(|extension| extension == "specky") {
+            found.push(path);
+        }
+    }
+}
+
+/// Average time of a run, programs are stopped after `MAX_STEPS` statements
+fn measure(program: &speckylang::ast::Statements, engine: Engine) -> Duration {
+    let mut runs = 0;
+    let start = Instant::now();
+
+    while runs < 3 || start.elapsed() < TIME_PER_EXAMPLE / 2 {
+        let mut interpreter = Interpreter::with_io(program.clone(), BufferIo::with_input(INPUT));
+        interpreter.set_engine(engine);
+        interpreter.set_limits(Limits { max_steps: Some(MAX_STEPS), ..Default::default() });
+        let _ = interpreter.run_to_end();
+        runs += 1;
+    }
+
+    start.elapsed() / runs
+}
+
+fn main() {
+    let mut paths = vec![];
+    examples(Path::new("examples"), &mut paths);
+    paths.sort();
+
+    let (mut ast_total, mut bytecode_total) = (Duration::ZERO, Duration::ZERO);
+
+    println!("{:<40} {:>12} {:>12} {:>8}", "example", "ast", "bytecode", "speedup");
+    for path in paths {
+        let code = fs::read_to_string(&path).unwrap();
+        let program = speckylang::parse(&code).unwrap();
+
+        let ast = measure(&program, Engine::Ast);
+        let bytecode = measure(&program, Engine::Bytecode);
+        ast_total += ast;
+        bytecode_total += bytecode;
+
+        let name = path.strip_prefix("examples").unwrap().display().to_string();
+        println!("{name:<40} {:>12} {:>12} {:>7.2}x", format!("{ast:.2?}"), format!("{bytecode:.2?}"), ast.as_secs_f64() / bytecode.as_secs_f64());
+    }
+
+    println!(
+        "{:<40} {:>12} {:>12} {:>7.2}x",
+        "total",
+        format!("{ast_total:.2?}"),
+        format!("{bytecode_total:.2?}"),
+        ast_total.as_secs_f64() / bytecode_total.as_secs_f64(),
+    );
+}
@@ -1,22 +1,31 @@
-use std::{time::Instant, hash::Hash};
+use std::{time::{Duration, Instant}, hash::Hash};
 
 use num_bigint::BigInt;
 use num_bigfloat::BigFloat;
 
+use crate::parser::error::CodeArea;
+
+pub use crate::symbol::Symbol;
+pub use crate::map::Map;
+
 pub type Text = String;
 pub type Integer = BigInt;
 pub type Float = BigFloat;
 pub type SmallInt = i128;
 
-#[derive(Debug, Hash, PartialEq, Eq, Clone, PartialOrd)]
+#[derive(Debug, Hash, PartialEq, Eq, Clone, PartialOrd, Default)]
 pub enum Value {
-    Symbol(String),
+    Symbol(Symbol),
     Boolean(bool),
     Integer(Integer),
     SmallInt(SmallInt),
     Float(Float),
     Text(Text),
     Time(Option<Instant>),
+    Duration(Duration),
+    List(Vec<Value>),
+    Map(Map),
+    #[default]
     Null,
 }
 
@@ -24,21 +33,47 @@ pub enum Value {
 pub struct Expression {
     pub reader: usize,
     pub value: Value,
-} 
+    /// `@`, starts from the pointer saved by the last `|>` instead of the value (which is null)
+    pub saved_pointer: bool,
+    pub area: CodeArea,
+}
+
+#[derive(Debug, Clone)]
+pub struct Statement {
+    pub kind: StatementKind,
+    pub area: CodeArea,
+}
 
 #[derive(Debug, Clone)]
-pub enum Statement {
+pub enum StatementKind {
     Load(Expression),
+    Push(Expression),    // saves the pointer, then loads
+    Pop,                 // back to the pointer saved by the last push
+    Exchange,            // swaps the pointer with the one saved by the last push
     Define(Expression),  // address definition
     Jump(Expression),    // jump to address
+    Call {               // jump to address, coming back with a return
+        expression: Expression,
+        /// The memory changes of the call are undone when it returns (`[~>:]`)
+        scoped: bool,
+    },
+    Return,              // back to the statement after the last call
     Assign(Expression),
     Overwrite(Expression),
     Swap(Expression),
     Index(Expression),
+    SetIndex(Expression),
+    Property(Expression),
 
     And(Expression),
     Or(Expression),
     Xor(Expression),
+    BitAnd(Expression),
+    BitOr(Expression),
+    BitXor(Expression),
+    BitNot(Expression),  // the complement of the expression, like an assign
+    ShiftLeft(Expression),
+    ShiftRight(Expression),
 
     Plus(Expression),
     Minus(Expression),
@@ -72,6 +107,37 @@ pub enum Statement {
     Input,
 }
 
+impl Expression {
+    /// The value, if the expression doesn't read memory or the saved pointer (like the label of `[<] label`)
+    pub fn literal(&self) -> Option<&Value> {
+        (self.reader == 0 && !self.saved_pointer).then_some(&self.value)
+    }
+}
+
+impl StatementKind {
+    pub fn expression(&self) -> Option<&Expression> {
+        use StatementKind::*;
+        match self {
+            Load(expression)|Push(expression)|Define(expression)|Jump(expression)|Assign(expression)|Overwrite(expression)|
+            Swap(expression)|Index(expression)|SetIndex(expression)|Property(expression)|And(expression)|Or(expression)|Xor(expression)|
+            BitAnd(expression)|BitOr(expression)|BitXor(expression)|BitNot(expression)|ShiftLeft(expression)|ShiftRight(expression)|
+            Plus(expression)|Minus(expression)|Times(expression)|Divide(expression)|Modulo(expression)|
+            PPercent(expression)|Exponential(expression)|Unequal(expression)|Equal(expression)|
+            LessThan(expression)|LessThanOrEqual(expression)|GreaterThan(expression)|
+            GreaterThanOrEqual(expression)|Call { expression, .. } => Some(expression),
+            Pop|Exchange|Return|Truthy(_)|Falsy(_)|Exists(_)|Empty(_)|Log { .. }|Input => None,
+        }
+    }
+
+    /// How many of the following statements a condition skips when it fails
+    pub fn condition_length(&self) -> usize {
+        match self {
+            StatementKind::Truthy(n)|StatementKind::Falsy(n)|StatementKind::Exists(n)|StatementKind::Empty(n) => *n,
+            _ => 0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum LogKind {
     Value(LogValue),
@@ -0,0 +1,258 @@
+use std::fmt::Write;
+
+use crate::{ast::{Expression, StatementKind, Statements}, lint, run};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Block(usize),
+    /// The statement index went past the end of the program
+    End,
+    /// A jump that depends on memory, like `[>] §x` or a label that also gets changed with math
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// The next statement
+    Next,
+    /// A condition that holds, with its character
+    Holds(char),
+    /// A condition that fails and skips statements
+    Skips(usize),
+    /// `[>] label`, with the label as written
+    Jump(String),
+    /// `[>:] label`, with the label as written
+    Call(String),
+    /// `[<:]` going back to after a call
+    Return,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Edge {
+    pub kind: EdgeKind,
+    pub target: Target,
+}
+
+/// Statements that always run one after the other
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub start: usize,
+    pub end: usize,
+    pub edges: Vec<Edge>,
+}
+
+/// Control flow of a program.
+/// Jumps go to the block of the `[<] label`, which only stores the address again.
+/// Calls also go to the statement after them (where their return comes back to),
+/// and returns go to the statements after every call.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Graph {
+    pub blocks: Vec<Block>,
+}
+
+fn condition_char(kind: &StatementKind) -> char {
+    match kind {
+        StatementKind::Truthy(_) => '?',
+        StatementKind::Falsy(_) => '!',
+        StatementKind::Exists(_) => '$',
+        _ => '°',
+    }
+}
+
+impl Graph {
+    pub fn build(statements: &Statements) -> Self {
+        let (defines, written) = lint::labels(statements);
+
+        let mut leaders = vec![false; statements.len() + 1];
+        leaders[0] = true;
+        for (index, statement) in statements.iter().enumerate() {
+            match &statement.kind {
+                StatementKind::Define(_) => leaders[index] = true,
+                StatementKind::Jump(_)|StatementKind::Call { .. }|StatementKind::Return => leaders[index + 1] = true,
+                kind if kind.condition_length() > 0 => {
+                    leaders[index + 1] = true;
+                    if let Some(leader) = leaders.get_mut(index + kind.condition_length() + 1) {
+                        *leader = true;
+                    }
+                },
+                _ => {},
+            }
+        }
+
+        let starts = (0..statements.len()).filter(|&index| leaders[index]).collect::<Vec<_>>();
+        let end_index = |block: usize| starts.get(block + 1).copied().unwrap_or(statements.len()) - 1;
+        let block_of = |index: usize| match starts.binary_search(&index) {
+            Ok(block) => Target::Block(block),
+            Err(_) => Target::End,
+        };
+
+        let blocks = starts.iter().enumerate()
+            .map(|(block, &start)| {
+                let jump_index = end_index(block);
+                let end = jump_index + 1;
+                let last = &statements[end - 1].kind;
+                let next = Edge { kind: EdgeKind::Next, target: block_of(end) };
+
+                // the blocks of the `[<] label`s, or `next` if the label isn't stored yet
+                let jump_edges = |expression: &Expression, kind: fn(String) -> EdgeKind| {
+                    let name = run::value_to_string(&expression.value, false);
+                    let jump = |target| Edge { kind: kind(format!("{}{name}", "§".repeat(expression.reader))), target };
+                    match expression.literal() {
+                        Some(label) => {
+                            let mut edges = defines.iter()
+                                .filter(|(defined, _)| *defined == label)
+                                .map(|&(_, index)| jump(block_of(index)))
+                                .collect::<Vec<_>>();
+                            if written.contains(&label) {
+                                edges.push(jump(Target::Unknown));
+                            }
+                            // jumps to addresses that aren't stored yet do nothing, like function returns before the first call
+                            if !defines.iter().any(|&(defined, index)| defined == label && index < jump_index) {
+                                edges.push(next.clone());
+                            }
+                            edges
+                        },
+                        None => vec![jump(Target::Unknown), next.clone()],
+                    }
+                };
+
+                let edges = match last {
+                    StatementKind::Jump(expression) => jump_edges(expression, EdgeKind::Jump),
+                    StatementKind::Call { expression, .. } => {
+                        let mut edges = jump_edges(expression, EdgeKind::Call);
+                        if !edges.contains(&next) {
+                            edges.push(next);
+                        }
+                        edges
+                    },
+                    // without a call to return from, the return does nothing
+                    StatementKind::Return => statements.iter().enumerate()
+                        .filter(|(_, statement)| matches!(statement.kind, StatementKind::Call { .. }))
+                        .map(|(call, _)| Edge { kind: EdgeKind::Return, target: block_of(call + 1) })
+                        .chain([next])
+                        .collect(),
+                    kind if kind.condition_length() > 0 => vec![
+                        Edge { kind: EdgeKind::Holds(condition_char(kind)), target: block_of(end) },
+                        Edge { kind: EdgeKind::Skips(kind.condition_length()), target: block_of(end + kind.condition_length()) },
+                    ],
+                    _ => vec![next],
+                };
+
+                Block { start, end, edges }
+            })
+            .collect();
+
+        Self { blocks }
+    }
+
+    /// The source of the statements of a block, grouped by line
+    fn block_lines(&self, code: &str, statements: &Statements, block: &Block) -> Vec<String> {
+        let mut lines: Vec<(usize, String)> = vec![];
+        for statement in &statements[block.start..block.end] {
+            let line = statement.area.line(code);
+            let text = &code[statement.area.0..statement.area.1];
+            match lines.last_mut() {
+                Some((last, source)) if *last == line => {
+                    source.push(' ');
+                    source.push_str(text);
+                },
+                _ => lines.push((line, text.to_string())),
+            }
+        }
+        lines.into_iter().map(|(_, source)| source).collect()
+    }
+
+    fn edge_label(kind: &EdgeKind) -> String {
+        match kind {
+            EdgeKind::Next => String::new(),
+            EdgeKind::Holds(condition) => condition.to_string(),
+            EdgeKind::Skips(n) => format!("skip {n}"),
+            EdgeKind::Jump(label) => format!("[>] {label}"),
+            EdgeKind::Call(label) => format!("[>:] {label}"),
+            EdgeKind::Return => "[<:]".to_string(),
+        }
+    }
+
+    pub fn to_dot(&self, code: &str, statements: &Statements) -> String {
+        let escape = |text: &str| text.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\l");
+        let node = |target: Target| match target {
+            Target::Block(block) => format!("b{block}"),
+            Target::End => "end".to_string(),
+            Target::Unknown => "unknown".to_string(),
+        };
+
+        let mut dot = String::from("digraph program {\n    node [shape=box, fontname=\"monospace\"];\n");
+        let mut end = false;
+        let mut unknown = false;
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            let line = statements[block.start].area.line(code);
+            let text = self.block_lines(code, statements, block).iter()
+                .map(|line| format!("{}\\l", escape(line)))
+                .collect::<String>();
+            let _ = writeln!(dot, "    b{index} [label=\"line {line}\\l{text}\"];");
+
+            for edge in &block.edges {
+                end |= edge.target == Target::End;
+                unknown |= edge.target == Target::Unknown;
+                let mut attributes = vec![];
+                let label = Self::edge_label(&edge.kind);
+                if !label.is_empty() {
+                    attributes.push(format!("label=\"{}\"", escape(&label)));
+                }
+                if edge.target == Target::Unknown {
+                    attributes.push("style=dashed".to_string());
+                }
+                let attributes = match attributes.is_empty() {
+                    true => String::new(),
+                    false => format!(" [{}]", attributes.join(", ")),
+                };
+                let _ = writeln!(dot, "    b{index} -> {}{attributes};", node(edge.target));
+            }
+        }
+
+        if end || self.blocks.is_empty() {
+            dot.push_str("    end [shape=doublecircle];\n");
+        }
+        if unknown {
+            dot.push_str("    unknown [shape=diamond, label=\"?\"];\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn to_mermaid(&self, code: &str, statements: &Statements) -> String {
+        let escape = |text: &str| text
+            .replace('&', "#amp;")
+            .replace('"', "#quot;")
+            .replace('<', "#lt;")
+            .replace('>', "#gt;")
+            .replace('|', "#124;")
+            .replace('\n', "<br/>");
+        let node = |target: Target| match target {
+            Target::Block(block) => format!("b{block}"),
+            Target::End => "finish((end))".to_string(),
+            Target::Unknown => "unknown{?}".to_string(),
+        };
+
+        let mut mermaid = String::from("flowchart TD\n");
+
+        for (index, block) in self.blocks.iter().enumerate() {
+            let line = statements[block.start].area.line(code);
+            let text = self.block_lines(code, statements, block).iter()
+                .map(|line| escape(line))
+                .collect::<Vec<_>>()
+                .join("<br/>");
+            let _ = writeln!(mermaid, "    b{index}[\"line {line}<br/>{text}\"]");
+
+            for edge in &block.edges {
+                let arrow = if edge.target == Target::Unknown { "-.->" } else { "-->" };
+                let label = Self::edge_label(&edge.kind);
+                let label = if label.is_empty() { String::new() } else { format!("|\"{}\"|", escape(&label)) };
+                let _ = writeln!(mermaid, "    b{index} {arrow}{label} {}", node(edge.target));
+            }
+        }
+
+        mermaid
+    }
+}
@@ -0,0 +1,191 @@
+use std::fmt::Write;
+
+use crate::{ast::{Expression, LogKind, LogMemory, LogValue, StatementKind, Statements, Value}, run::Operator};
+
+use super::{float_literals, Constants, Unsupported};
+
+const RUNTIME: &str = include_str!("runtime.c");
+
+/// Escapes `bytes` for a C string literal, octal escapes can't swallow the characters after them
+fn string(bytes: &[u8]) -> String {
+    let mut literal = String::from("\"");
+    for &byte in bytes {
+        match byte {
+            b'"' | b'\\' | b'?' => { literal.push('\\'); literal.push(byte as char) },
+            b' '..=b'~' => literal.push(byte as char),
+            _ => { let _ = write!(literal, "\\{byte:03o}"); },
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// A 128-bit integer, C has no literals for them
+fn integer(integer: i128) -> String {
+    match i64::try_from(integer) {
+        Ok(small) if small != i64::MIN => format!("(sp_int){small}LL"),
+        _ => {
+            let bits = integer as u128;
+            format!("(sp_int)((sp_uint)0x{:x}ULL << 64 | 0x{:x}ULL)", bits >> 64, bits as u64)
+        },
+    }
+}
+
+/// A C expression that creates the value of `expression`
+fn value(code: &str, expression: &Expression, fixed_width: bool) -> Result<String, Unsupported> {
+    let needs_fixed_width = |kind| Unsupported {
+        message: format!("{kind} need `--fixed-width` in C"),
+        area: expression.area,
+    };
+
+    Ok(match &expression.value {
+        Value::Symbol(symbol) => format!("sp_symbol_value({}, {})", string(symbol.as_bytes()), symbol.len()),
+        Value::Boolean(boolean) => format!("sp_boolean({boolean})"),
+        Value::Integer(big) => {
+            if !fixed_width {
+                return Err(needs_fixed_width("Integers bigger than 128 bits"))
+            }
+            let (sign, digits) = big.to_u64_digits();
+            let bits = digits.first().copied().unwrap_or(0) as u128 | (digits.get(1).copied().unwrap_or(0) as u128) << 64;
+            let bits = if sign == num_bigint::Sign::Minus { bits.wrapping_neg() } else { bits };
+            format!("sp_small({})", integer(bits as i128))
+        },
+        Value::SmallInt(small) => format!("sp_small({})", integer(*small)),
+        Value::Float(_) => {
+            if !fixed_width {
+                return Err(needs_fixed_width("Floats"))
+            }
+            let (negative, digits) = float_literals(code, expression)[0];
+            format!("sp_float({}{digits})", if negative { "-" } else { "" })
+        },
+        Value::Text(text) => format!("sp_text({}, {})", string(text.as_bytes()), text.len()),
+        Value::Time(_) => "sp_time()".to_string(),
+        Value::Duration(_) => unreachable!("durations have no literal"),
+        Value::List(_) => return Err(Unsupported {
+            message: "Lists aren't supported in C".to_string(),
+            area: expression.area,
+        }),
+        Value::Map(_) => return Err(Unsupported {
+            message: "Maps aren't supported in C".to_string(),
+            area: expression.area,
+        }),
+        Value::Null => "SP_NULL_VALUE".to_string(),
+    })
+}
+
+fn log(kind: &StatementKind) -> String {
+    let StatementKind::Log { kind, reverse, newline, space, vertical, assign } = kind else {
+        unreachable!("not a log statement")
+    };
+    let (kind, reader, pretty, sort) = match kind {
+        Some(LogKind::Value(LogValue { reader, pretty })) => ("SP_LOG_VALUE", *reader, *pretty, false),
+        Some(LogKind::Type) => ("SP_LOG_TYPE", 0, false, false),
+        Some(LogKind::Memory(LogMemory { sort })) => ("SP_LOG_MEMORY", 0, false, *sort),
+        None => ("SP_LOG_NONE", 0, false, false),
+    };
+    format!("&(sp_log_args){{ {kind}, {reader}, {pretty}, {sort}, {reverse}, {newline}, {space}, {vertical}, {assign} }}")
+}
+
+fn operator(operator: Operator) -> &'static str {
+    match operator {
+        Operator::Index => "SP_INDEX",
+        Operator::SetIndex => "SP_SET_INDEX",
+        Operator::Property => "SP_PROPERTY",
+        Operator::And => "SP_AND",
+        Operator::Or => "SP_OR",
+        Operator::Xor => "SP_XOR",
+        Operator::BitAnd => "SP_BIT_AND",
+        Operator::BitOr => "SP_BIT_OR",
+        Operator::BitXor => "SP_BIT_XOR",
+        Operator::BitNot => "SP_BIT_NOT",
+        Operator::ShiftLeft => "SP_SHIFT_LEFT",
+        Operator::ShiftRight => "SP_SHIFT_RIGHT",
+        Operator::Plus => "SP_PLUS",
+        Operator::Minus => "SP_MINUS",
+        Operator::Times => "SP_TIMES",
+        Operator::Divide => "SP_DIVIDE",
+        Operator::Modulo => "SP_MODULO",
+        Operator::PPercent => "SP_PPERCENT",
+        Operator::Exponential => "SP_EXPONENTIAL",
+        Operator::Unequal => "SP_UNEQUAL",
+        Operator::Equal => "SP_EQUAL",
+        Operator::LessThan => "SP_LESS_THAN",
+        Operator::LessThanOrEqual => "SP_LESS_THAN_OR_EQUAL",
+        Operator::GreaterThan => "SP_GREATER_THAN",
+        Operator::GreaterThanOrEqual => "SP_GREATER_THAN_OR_EQUAL",
+    }
+}
+
+/// Generates a single C file with its runtime, which builds with `cc -O2 out.c -o out -lm`
+/// (integers are `__int128`, so it needs gcc or clang).
+/// Integers bigger than 128 bits and floats can't be represented exactly, `fixed_width`
+/// makes integers wrap and floats doubles instead of stopping with an error.
+pub fn transpile(code: &str, statements: &Statements, name: &str, fixed_width: bool) -> Result<String, Unsupported> {
+    let constants = Constants::collect(statements);
+    let mut c = String::new();
+
+    let _ = writeln!(c, "/* Generated by `speckylang compile --target c` from {} */", name.replace("*/", "* /"));
+    c.push_str("/* Build with `cc -O2 <this file> -lm` */\n\n");
+    let _ = writeln!(c, "#define SP_FIXED_WIDTH {}\n", fixed_width as u8);
+    c.push_str(RUNTIME);
+
+    // for error reports, with a zero at the end so that it's never empty
+    let lines = statements.iter()
+        .map(|statement| statement.area.line(code).to_string())
+        .chain(["0".to_string()])
+        .collect::<Vec<_>>();
+    let _ = writeln!(c, "\nstatic const unsigned LINES[] = {{ {} }};\n", lines.join(", "));
+
+    c.push_str("int main(void) {\n");
+    let _ = writeln!(c, "    sp_value constants[{}];", constants.expressions.len().max(1));
+    for (index, expression) in constants.expressions.iter().enumerate() {
+        let _ = writeln!(c, "    constants[{index}] = {};", value(code, expression, fixed_width)?);
+    }
+    c.push_str("    sp_runtime rt;\n");
+    c.push_str("    sp_init(&rt);\n");
+    c.push_str("    size_t index = 0;\n\n");
+    c.push_str("    for (;;) {\n");
+    c.push_str("        size_t current = index;\n");
+    c.push_str("        sp_error error = SP_OK;\n");
+    c.push_str("        switch (index) {\n");
+
+    for (index, statement) in statements.iter().enumerate() {
+        let next = index + 1;
+        let operand = |expression: &Expression| match expression.saved_pointer {
+            true => format!("sp_saved_pointer(&rt), {}", expression.reader),
+            false => format!("&constants[{}], {}", constants.index(&expression.value), expression.reader),
+        };
+        let case = match &statement.kind {
+            StatementKind::Load(expression) => format!("sp_load(&rt, {}); index = {next};", operand(expression)),
+            StatementKind::Push(expression) => format!("sp_push_pointer(&rt, {}); index = {next};", operand(expression)),
+            StatementKind::Pop => format!("sp_pop_pointer(&rt); index = {next};"),
+            StatementKind::Exchange => format!("sp_exchange_pointer(&rt); index = {next};"),
+            StatementKind::Define(expression) => format!("sp_define(&rt, {}, {index}); index = {next};", operand(expression)),
+            StatementKind::Jump(expression) => format!("index = sp_jump(&rt, {}, {next});", operand(expression)),
+            StatementKind::Call { expression, scoped } => format!("index = sp_call(&rt, {}, {index}, {scoped}, {next});", operand(expression)),
+            StatementKind::Return => format!("index = sp_return(&rt, {next});"),
+            StatementKind::Assign(expression) => format!("sp_assign(&rt, {}); index = {next};", operand(expression)),
+            StatementKind::Overwrite(expression) => format!("sp_overwrite(&rt, {}); index = {next};", operand(expression)),
+            StatementKind::Swap(expression) => format!("sp_swap(&rt, {}); index = {next};", operand(expression)),
+            StatementKind::Truthy(skip) => format!("index = sp_truthy(&rt) ? {next} : {};", next + skip),
+            StatementKind::Falsy(skip) => format!("index = !sp_truthy(&rt) ? {next} : {};", next + skip),
+            StatementKind::Exists(skip) => format!("index = sp_exists(&rt) ? {next} : {};", next + skip),
+            StatementKind::Empty(skip) => format!("index = !sp_exists(&rt) ? {next} : {};", next + skip),
+            kind @ StatementKind::Log { .. } => format!("error = sp_log(&rt, {}); index = {next};", log(kind)),
+            StatementKind::Input => format!("error = sp_input(&rt); index = {next};"),
+            kind => {
+                let (op, expression) = Operator::of(kind).expect("every other statement is an operation");
+                format!("error = sp_operate(&rt, {}, {}); index = {next};", operator(op), operand(expression))
+            },
+        };
+        let _ = writeln!(c, "            case {index}: {case} break;");
+    }
+
+    c.push_str("            default: sp_flush(&rt); return 0;\n");
+    c.push_str("        }\n");
+    c.push_str("        if (error) sp_fail(&rt, error, current, LINES);\n");
+    c.push_str("    }\n");
+    c.push_str("}\n");
+
+    Ok(c)
+}
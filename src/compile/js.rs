@@ -0,0 +1,155 @@
+use std::fmt::Write;
+
+use crate::{ast::{Expression, LogKind, LogMemory, LogValue, StatementKind, Statements, Value}, run::Operator};
+
+use super::{float_literals, take_float_literal, Constants};
+
+pub(crate) const RUNTIME: &str = include_str!("runtime.js");
+
+/// A JS string literal, everything outside of printable ASCII is escaped
+fn string(text: &str) -> String {
+    let mut literal = String::from("\"");
+    for char in text.chars() {
+        match char {
+            '"' | '\\' => { literal.push('\\'); literal.push(char) },
+            ' '..='~' => literal.push(char),
+            _ => { let _ = write!(literal, "\\u{{{:x}}}", char as u32); },
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// A JS expression that creates the value of `expression`, `d` is the decimal hook
+fn value(code: &str, expression: &Expression) -> String {
+    literal(&expression.value, &mut float_literals(code, expression))
+}
+
+/// A JS expression that creates `value`, its floats are taken from `floats`
+fn literal(value: &Value, floats: &mut Vec<(bool, &str)>) -> String {
+    match value {
+        Value::Symbol(symbol) => format!("symbol({})", string(symbol.as_str())),
+        Value::Boolean(boolean) => format!("boolean({boolean})"),
+        Value::Integer(integer) => format!("integer({integer}n)"),
+        Value::SmallInt(integer) => format!("integer({integer}n)"),
+        Value::Float(float) => {
+            let (negative, digits) = take_float_literal(floats, float);
+            let float = format!("d.parse(\"{digits}\")");
+            if negative { format!("float(d.neg({float}))") } else { format!("float({float})") }
+        },
+        Value::Text(text) => format!("text({})", string(text)),
+        Value::Time(_) => "time()".to_string(),
+        Value::Duration(_) => unreachable!("durations have no literal"),
+        Value::List(list) => {
+            let values = list.iter().map(|value| literal(value, floats)).collect::<Vec<_>>();
+            format!("list([{}])", values.join(", "))
+        },
+        Value::Map(map) => {
+            let entries = map.iter()
+                .map(|(key, value)| format!("[{}, {}]", literal(key, floats), literal(value, floats)))
+                .collect::<Vec<_>>();
+            format!("rt.map([{}])", entries.join(", "))
+        },
+        Value::Null => "NULL_VALUE".to_string(),
+    }
+}
+
+fn log(kind: &StatementKind) -> String {
+    let StatementKind::Log { kind, reverse, newline, space, vertical, assign } = kind else {
+        unreachable!("not a log statement")
+    };
+    let (kind, reader, pretty, sort) = match kind {
+        Some(LogKind::Value(LogValue { reader, pretty })) => ("value", *reader, *pretty, false),
+        Some(LogKind::Type) => ("type", 0, false, false),
+        Some(LogKind::Memory(LogMemory { sort })) => ("memory", 0, false, *sort),
+        None => ("none", 0, false, false),
+    };
+    format!(
+        "{{ kind: \"{kind}\", reader: {reader}, pretty: {pretty}, sort: {sort}, reverse: {reverse}, \
+        newline: {newline}, space: {space}, vertical: {vertical}, assign: {assign} }}"
+    )
+}
+
+/// Generates an ES module with its runtime that exports `run(host)`, which resolves when the program halts.
+/// `host.write(text)` gets the output, `host.input()` returns the next line (or a promise of it)
+/// and `host.decimal` replaces the floats, see `numberDecimal` in the runtime for its methods.
+/// Running the module itself with node runs the program on stdin and stdout.
+pub fn transpile(code: &str, statements: &Statements, name: &str) -> String {
+    let constants = Constants::collect(statements);
+    let mut js = String::new();
+
+    let _ = writeln!(js, "// Generated by `speckylang compile --target js` from {}", name.replace('\n', " "));
+    js.push_str("// Run with `node <this file>.mjs`, or import it and `await run({ write, input, decimal })`\n\n");
+    js.push_str(RUNTIME);
+
+    let lines = statements.iter()
+        .map(|statement| statement.area.line(code).to_string())
+        .collect::<Vec<_>>();
+    let _ = writeln!(js, "\nconst LINES = [{}];\n", lines.join(", "));
+
+    js.push_str("export async function run(host = {}) {\n");
+    js.push_str("    const rt = new Runtime(host);\n");
+    js.push_str("    const d = rt.decimal;\n");
+    js.push_str("    const constants = [\n");
+    for expression in &constants.expressions {
+        let _ = writeln!(js, "        {},", value(code, expression));
+    }
+    js.push_str("    ];\n");
+    js.push_str("    let index = 0;\n\n");
+    js.push_str("    for (;;) {\n");
+    js.push_str("        const current = index;\n");
+    js.push_str("        try {\n");
+    js.push_str("            switch (index) {\n");
+
+    for (index, statement) in statements.iter().enumerate() {
+        let next = index + 1;
+        let operand = |expression: &Expression| match expression.saved_pointer {
+            true => format!("rt.savedPointer(), {}", expression.reader),
+            false => format!("constants[{}], {}", constants.index(&expression.value), expression.reader),
+        };
+        let case = match &statement.kind {
+            StatementKind::Load(expression) => format!("rt.load({}); index = {next};", operand(expression)),
+            StatementKind::Push(expression) => format!("rt.push({}); index = {next};", operand(expression)),
+            StatementKind::Pop => format!("rt.pop(); index = {next};"),
+            StatementKind::Exchange => format!("rt.exchange(); index = {next};"),
+            StatementKind::Define(expression) => format!("rt.define({}, {index}); index = {next};", operand(expression)),
+            StatementKind::Jump(expression) => format!("index = rt.jump({}, {next});", operand(expression)),
+            StatementKind::Call { expression, scoped } => format!("index = rt.call({}, {index}, {scoped}, {next});", operand(expression)),
+            StatementKind::Return => format!("index = rt.returnFromCall({next});"),
+            StatementKind::Assign(expression) => format!("rt.assign({}); index = {next};", operand(expression)),
+            StatementKind::Overwrite(expression) => format!("rt.overwrite({}); index = {next};", operand(expression)),
+            StatementKind::Swap(expression) => format!("rt.swap({}); index = {next};", operand(expression)),
+            StatementKind::Truthy(skip) => format!("index = rt.truthy() ? {next} : {};", next + skip),
+            StatementKind::Falsy(skip) => format!("index = !rt.truthy() ? {next} : {};", next + skip),
+            StatementKind::Exists(skip) => format!("index = rt.exists() ? {next} : {};", next + skip),
+            StatementKind::Empty(skip) => format!("index = !rt.exists() ? {next} : {};", next + skip),
+            kind @ StatementKind::Log { .. } => format!("rt.log({}); index = {next};", log(kind)),
+            StatementKind::Input => format!("await rt.input(); index = {next};"),
+            kind => {
+                let (op, expression) = Operator::of(kind).expect("every other statement is an operation");
+                format!("rt.operate(Operator.{op:?}, {}); index = {next};", operand(expression))
+            },
+        };
+        let _ = writeln!(js, "                case {index}: {case} break;");
+    }
+
+    js.push_str("                default: return;\n");
+    js.push_str("            }\n");
+    js.push_str("        } catch (error) {\n");
+    js.push_str("            if (error instanceof SpeckyError) {\n");
+    js.push_str("                error.statement = current;\n");
+    js.push_str("                error.line = LINES[current];\n");
+    js.push_str("                error.calls = rt.calls.map(({ call }) => ({ statement: call, line: LINES[call] }));\n");
+    js.push_str("            }\n");
+    js.push_str("            throw error;\n");
+    js.push_str("        }\n");
+    js.push_str("    }\n");
+    js.push_str("}\n\n");
+
+    js.push_str("// `node <this file>.mjs` runs the program with stdin and stdout\n");
+    js.push_str("if (globalThis.process?.argv?.[1] && import.meta.url === (await import(\"node:url\")).pathToFileURL(process.argv[1]).href) {\n");
+    js.push_str("    await runMain(run);\n");
+    js.push_str("}\n");
+
+    js
+}
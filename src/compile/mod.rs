@@ -0,0 +1,90 @@
+//! Transpilers from SpeckyLang to other languages, the generated programs
+//! print the same as the interpreter
+
+pub mod rust;
+pub mod c;
+pub mod js;
+
+use ahash::AHashMap;
+use ariadne::{Color, Report, ReportKind};
+use logos::Logos;
+
+use crate::{ast::{Expression, Float, Statements, Value}, parser::error::{report_config, CodeArea}, token::Token};
+
+/// A part of a program that the target language can't express
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    pub message: String,
+    pub area: CodeArea,
+}
+
+pub fn print_unsupported(code: &str, unsupported: &Unsupported) {
+    Report::build(ReportKind::Error, unsupported.area)
+        .with_config(report_config())
+        .with_message(&unsupported.message)
+        .with_label(
+            ariadne::Label::new(unsupported.area)
+                .with_message(&unsupported.message)
+                .with_color(Color::Red),
+        )
+        .finish()
+        .print(ariadne::sources([("code", code)]))
+        .expect("Failed to print error report");
+}
+
+/// The literals of a program, once each, with the first expression that has them (`@` has none)
+pub(crate) struct Constants<'a> {
+    pub expressions: Vec<&'a Expression>,
+    indexes: AHashMap<&'a Value, usize>,
+}
+
+impl<'a> Constants<'a> {
+    pub fn collect(statements: &'a Statements) -> Self {
+        let mut constants = Constants { expressions: vec![], indexes: AHashMap::new() };
+        for expression in statements.iter().filter_map(|statement| statement.kind.expression()).filter(|expression| !expression.saved_pointer) {
+            if !constants.indexes.contains_key(&expression.value) {
+                constants.indexes.insert(&expression.value, constants.expressions.len());
+                constants.expressions.push(expression);
+            }
+        }
+        constants
+    }
+
+    pub fn index(&self, value: &Value) -> usize {
+        self.indexes[value]
+    }
+}
+
+/// Whether each float literal of an expression is negative and its digits as written, in order
+/// (more than one in lists and maps), so that the generated code can parse them exactly like the parser did
+pub(crate) fn float_literals<'a>(code: &'a str, expression: &Expression) -> Vec<(bool, &'a str)> {
+    let source = &code[expression.area.0..expression.area.1];
+    let mut lexer = Token::lexer(source);
+    let mut negative = false;
+    let mut floats = vec![];
+
+    while let Some(token) = lexer.next() {
+        match token {
+            Ok(Token::Minus) => negative = !negative,
+            Ok(Token::Plus) => {},
+            Ok(Token::FloatLiteral) => {
+                floats.push((negative, lexer.slice()));
+                negative = false;
+            },
+            _ => negative = false,
+        }
+    }
+
+    floats
+}
+
+/// Takes the literal of `float` out of `floats`, maps sort their entries so they aren't in order
+pub(crate) fn take_float_literal<'a>(floats: &mut Vec<(bool, &'a str)>, float: &Float) -> (bool, &'a str) {
+    let index = floats.iter()
+        .position(|&(negative, digits)| {
+            let parsed = digits.parse::<Float>().expect("a float literal");
+            &(if negative { -parsed } else { parsed }) == float
+        })
+        .expect("every float has a literal");
+    floats.remove(index)
+}
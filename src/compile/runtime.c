@@ -0,0 +1,1367 @@
+/* SpeckyLang runtime, the same value semantics as the interpreter with 128-bit integers.
+   Programs that need bigger integers stop with an error, unless SP_FIXED_WIDTH makes them wrap,
+   floats are doubles and only allowed with SP_FIXED_WIDTH. */
+
+#include <math.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <time.h>
+
+/* programs only use some of the runtime */
+#pragma GCC diagnostic ignored "-Wunused-function"
+
+typedef __int128 sp_int;
+typedef unsigned __int128 sp_uint;
+
+#define SP_INT_MAX ((sp_int)(~(sp_uint)0 >> 1))
+#define SP_INT_MIN (-SP_INT_MAX - 1)
+/* the longest Rust `Duration`, in nanoseconds */
+#define SP_DURATION_MAX ((sp_uint)UINT64_MAX * 1000000000 + 999999999)
+
+/* in the order of `ast::Value`, which is how memory dumps are sorted */
+typedef enum { SP_SYMBOL, SP_BOOLEAN, SP_SMALLINT, SP_FLOAT, SP_TEXT, SP_TIME, SP_DURATION, SP_NULL } sp_tag;
+
+typedef struct { const char *bytes; size_t len; uint64_t hash; } sp_symbol;
+
+typedef struct {
+    sp_tag tag;
+    union {
+        const sp_symbol *symbol;
+        bool boolean;
+        sp_int integer;
+        double number;
+        struct { char *bytes; size_t len; } text;
+        /* tv_nsec < 0 is a time that hasn't been assigned yet */
+        struct timespec time;
+        /* durations, in nanoseconds */
+        sp_uint nanos;
+    } as;
+} sp_value;
+
+typedef enum {
+    SP_OK,
+    SP_DIVISION_BY_ZERO,
+    SP_NEGATIVE_EXPONENT,
+    SP_EXPONENT_TOO_LARGE,
+    SP_SHIFT_TOO_LARGE,
+    SP_OVERFLOW,
+    SP_FLOATS,
+    SP_TOO_LARGE,
+} sp_error;
+
+typedef enum {
+    SP_INDEX, SP_SET_INDEX, SP_PROPERTY, SP_AND, SP_OR, SP_XOR, SP_BIT_AND, SP_BIT_OR, SP_BIT_XOR, SP_BIT_NOT, SP_SHIFT_LEFT, SP_SHIFT_RIGHT,
+    SP_PLUS, SP_MINUS, SP_TIMES, SP_DIVIDE, SP_MODULO, SP_PPERCENT, SP_EXPONENTIAL,
+    SP_UNEQUAL, SP_EQUAL, SP_LESS_THAN, SP_LESS_THAN_OR_EQUAL, SP_GREATER_THAN, SP_GREATER_THAN_OR_EQUAL,
+} sp_operator;
+
+typedef enum { SP_LOG_NONE, SP_LOG_VALUE, SP_LOG_TYPE, SP_LOG_MEMORY } sp_log_kind;
+
+typedef struct {
+    sp_log_kind kind;
+    size_t reader;
+    bool pretty, sort, reverse, newline;
+    size_t space;
+    bool vertical, assign;
+} sp_log_args;
+
+static const sp_value SP_NULL_VALUE = { .tag = SP_NULL };
+
+static void sp_out_of_memory(void) {
+    fputs("Out of memory\n", stderr);
+    exit(1);
+}
+
+static void *sp_alloc(void *pointer, size_t size) {
+    pointer = realloc(pointer, size ? size : 1);
+    if (!pointer) sp_out_of_memory();
+    return pointer;
+}
+
+/* Growable byte strings */
+
+typedef struct { char *bytes; size_t len, capacity; } sp_buf;
+
+static void sp_reserve(sp_buf *buf, size_t more) {
+    if (buf->len + more <= buf->capacity) return;
+    while (buf->len + more > buf->capacity) buf->capacity = buf->capacity ? buf->capacity * 2 : 32;
+    buf->bytes = sp_alloc(buf->bytes, buf->capacity);
+}
+
+static void sp_push(sp_buf *buf, const char *bytes, size_t len) {
+    if (!len) return;
+    sp_reserve(buf, len);
+    memcpy(buf->bytes + buf->len, bytes, len);
+    buf->len += len;
+}
+
+static void sp_push_str(sp_buf *buf, const char *string) {
+    sp_push(buf, string, strlen(string));
+}
+
+static void sp_push_int(sp_buf *buf, sp_int integer) {
+    char digits[48];
+    size_t at = sizeof digits;
+    sp_uint magnitude = integer < 0 ? -(sp_uint)integer : (sp_uint)integer;
+    do {
+        digits[--at] = '0' + (char)(magnitude % 10);
+        magnitude /= 10;
+    } while (magnitude);
+    if (integer < 0) digits[--at] = '-';
+    sp_push(buf, digits + at, sizeof digits - at);
+}
+
+/* Like Rust prints an f64: the shortest digits that read back the same, never with an exponent */
+static void sp_push_double(sp_buf *buf, double number) {
+    if (isnan(number)) { sp_push_str(buf, "NaN"); return; }
+    if (isinf(number)) { sp_push_str(buf, number < 0 ? "-inf" : "inf"); return; }
+
+    char scientific[40];
+    for (int precision = 1; precision <= 17; precision++) {
+        snprintf(scientific, sizeof scientific, "%.*e", precision - 1, number);
+        if (strtod(scientific, NULL) == number) break;
+    }
+
+    const char *at = scientific;
+    if (*at == '-') { sp_push_str(buf, "-"); at++; }
+    char digits[20];
+    size_t count = 0;
+    for (; *at != 'e'; at++) if (*at != '.') digits[count++] = *at;
+    int exponent = atoi(at + 1);
+    while (count > 1 && digits[count - 1] == '0') count--;
+
+    if (exponent < 0) {
+        sp_push_str(buf, "0.");
+        for (int zero = -1; zero > exponent; zero--) sp_push_str(buf, "0");
+        sp_push(buf, digits, count);
+    } else if ((size_t)exponent + 1 >= count) {
+        sp_push(buf, digits, count);
+        for (size_t zero = count; zero < (size_t)exponent + 1; zero++) sp_push_str(buf, "0");
+    } else {
+        sp_push(buf, digits, (size_t)exponent + 1);
+        sp_push_str(buf, ".");
+        sp_push(buf, digits + exponent + 1, count - (size_t)exponent - 1);
+    }
+}
+
+/* The length of the UTF-8 character starting with `byte` */
+static size_t sp_char_len(unsigned char byte) {
+    if (byte < 0x80) return 1;
+    if (byte >= 0xF0) return 4;
+    if (byte >= 0xE0) return 3;
+    if (byte >= 0xC0) return 2;
+    return 1;
+}
+
+static void sp_push_char(sp_buf *buf, uint32_t c) {
+    char bytes[4];
+    if (c < 0x80) { bytes[0] = (char)c; sp_push(buf, bytes, 1); }
+    else if (c < 0x800) { bytes[0] = (char)(0xC0 | c >> 6); bytes[1] = (char)(0x80 | (c & 0x3F)); sp_push(buf, bytes, 2); }
+    else if (c < 0x10000) { bytes[0] = (char)(0xE0 | c >> 12); bytes[1] = (char)(0x80 | (c >> 6 & 0x3F)); bytes[2] = (char)(0x80 | (c & 0x3F)); sp_push(buf, bytes, 3); }
+    else { bytes[0] = (char)(0xF0 | c >> 18); bytes[1] = (char)(0x80 | (c >> 12 & 0x3F)); bytes[2] = (char)(0x80 | (c >> 6 & 0x3F)); bytes[3] = (char)(0x80 | (c & 0x3F)); sp_push(buf, bytes, 4); }
+}
+
+/* The characters of `bytes` in reverse */
+static void sp_push_reversed(sp_buf *buf, const char *bytes, size_t len) {
+    sp_reserve(buf, len);
+    size_t end = buf->len + len;
+    for (size_t at = 0; at < len;) {
+        size_t char_len = sp_char_len((unsigned char)bytes[at]);
+        if (at + char_len > len) char_len = len - at;
+        memcpy(buf->bytes + end - at - char_len, bytes + at, char_len);
+        at += char_len;
+    }
+    buf->len = end;
+}
+
+/* Like Rust's `{:?}` of a str */
+static void sp_push_debug(sp_buf *buf, const char *bytes, size_t len) {
+    sp_push_str(buf, "\"");
+    for (size_t at = 0; at < len; at++) {
+        unsigned char byte = (unsigned char)bytes[at];
+        char escape[16];
+        switch (byte) {
+            case '"': sp_push_str(buf, "\\\""); break;
+            case '\\': sp_push_str(buf, "\\\\"); break;
+            case '\n': sp_push_str(buf, "\\n"); break;
+            case '\r': sp_push_str(buf, "\\r"); break;
+            case '\t': sp_push_str(buf, "\\t"); break;
+            case '\0': sp_push_str(buf, "\\0"); break;
+            default:
+                if (byte < 0x20 || byte == 0x7F) {
+                    snprintf(escape, sizeof escape, "\\u{%x}", byte);
+                    sp_push_str(buf, escape);
+                } else {
+                    sp_push(buf, (const char *)&byte, 1);
+                }
+        }
+    }
+    sp_push_str(buf, "\"");
+}
+
+/* Symbols, interned so that they can be compared by address */
+
+static uint64_t sp_hash_bytes(uint64_t hash, const void *bytes, size_t len) {
+    for (size_t at = 0; at < len; at++) {
+        hash ^= ((const unsigned char *)bytes)[at];
+        hash *= 0x100000001B3;
+    }
+    return hash;
+}
+
+static struct { sp_symbol **slots; size_t len, capacity; } sp_symbols;
+
+static const sp_symbol *sp_intern(const char *bytes, size_t len) {
+    uint64_t hash = sp_hash_bytes(0xCBF29CE484222325, bytes, len);
+
+    if (sp_symbols.len * 2 >= sp_symbols.capacity) {
+        size_t capacity = sp_symbols.capacity ? sp_symbols.capacity * 2 : 64;
+        sp_symbol **slots = calloc(capacity, sizeof *slots);
+        if (!slots) sp_out_of_memory();
+        for (size_t at = 0; at < sp_symbols.capacity; at++) {
+            sp_symbol *symbol = sp_symbols.slots[at];
+            if (!symbol) continue;
+            size_t slot = symbol->hash & (capacity - 1);
+            while (slots[slot]) slot = (slot + 1) & (capacity - 1);
+            slots[slot] = symbol;
+        }
+        free(sp_symbols.slots);
+        sp_symbols.slots = slots;
+        sp_symbols.capacity = capacity;
+    }
+
+    size_t slot = hash & (sp_symbols.capacity - 1);
+    for (sp_symbol *symbol; (symbol = sp_symbols.slots[slot]); slot = (slot + 1) & (sp_symbols.capacity - 1)) {
+        if (symbol->hash == hash && symbol->len == len && memcmp(symbol->bytes, bytes, len) == 0) return symbol;
+    }
+
+    sp_symbol *symbol = sp_alloc(NULL, sizeof *symbol);
+    char *copy = sp_alloc(NULL, len);
+    memcpy(copy, bytes, len);
+    *symbol = (sp_symbol){ copy, len, hash };
+    sp_symbols.slots[slot] = symbol;
+    sp_symbols.len++;
+    return symbol;
+}
+
+/* Values */
+
+static sp_value sp_symbol_value(const char *bytes, size_t len) {
+    return (sp_value){ .tag = SP_SYMBOL, .as.symbol = sp_intern(bytes, len) };
+}
+
+static sp_value sp_boolean(bool boolean) {
+    return (sp_value){ .tag = SP_BOOLEAN, .as.boolean = boolean };
+}
+
+static sp_value sp_small(sp_int integer) {
+    return (sp_value){ .tag = SP_SMALLINT, .as.integer = integer };
+}
+
+static sp_value sp_float(double number) {
+    return (sp_value){ .tag = SP_FLOAT, .as.number = number };
+}
+
+static sp_value sp_text(const char *bytes, size_t len) {
+    sp_value value = { .tag = SP_TEXT };
+    value.as.text.bytes = sp_alloc(NULL, len);
+    memcpy(value.as.text.bytes, bytes, len);
+    value.as.text.len = len;
+    return value;
+}
+
+/* Takes the bytes of `buf` */
+static sp_value sp_text_from(sp_buf *buf) {
+    sp_value value = { .tag = SP_TEXT };
+    value.as.text.bytes = buf->bytes ? buf->bytes : sp_alloc(NULL, 0);
+    value.as.text.len = buf->len;
+    *buf = (sp_buf){ 0 };
+    return value;
+}
+
+static sp_value sp_time(void) {
+    return (sp_value){ .tag = SP_TIME, .as.time = { .tv_nsec = -1 } };
+}
+
+static struct timespec sp_now(void) {
+    struct timespec now;
+    clock_gettime(CLOCK_MONOTONIC, &now);
+    return now;
+}
+
+static sp_int sp_time_nanos(struct timespec time) {
+    return (sp_int)time.tv_sec * 1000000000 + time.tv_nsec;
+}
+
+/* Null if it's further away than a timespec can hold */
+static sp_value sp_time_at(sp_int nanos) {
+    sp_int seconds = nanos / 1000000000, fraction = nanos % 1000000000;
+    if (fraction < 0) { seconds--; fraction += 1000000000; }
+    if (seconds < INT64_MIN || seconds > INT64_MAX) return SP_NULL_VALUE;
+    return (sp_value){ .tag = SP_TIME, .as.time = { .tv_sec = (time_t)seconds, .tv_nsec = (long)fraction } };
+}
+
+/* Null if it's longer than a Rust `Duration` */
+static sp_value sp_duration(sp_uint nanos) {
+    if (nanos > SP_DURATION_MAX) return SP_NULL_VALUE;
+    return (sp_value){ .tag = SP_DURATION, .as.nanos = nanos };
+}
+
+/* Like `Duration::try_from_secs_f64`, null if the seconds are negative or too many */
+static sp_value sp_duration_of(double seconds) {
+    if (!(seconds >= 0) || seconds >= 18446744073709551616.0) return SP_NULL_VALUE;
+    return sp_duration((sp_uint)round(seconds * 1e9));
+}
+
+static double sp_seconds(sp_uint nanos) {
+    return (double)(nanos / 1000000000) + (double)(nanos % 1000000000) / 1e9;
+}
+
+/* Nanoseconds since the Unix epoch of a time read with `sp_now` */
+static sp_int sp_wall_nanos(struct timespec time) {
+    struct timespec wall;
+    clock_gettime(CLOCK_REALTIME, &wall);
+    return sp_time_nanos(wall) - (sp_time_nanos(sp_now()) - sp_time_nanos(time));
+}
+
+static sp_value sp_clone(const sp_value *value) {
+    if (value->tag == SP_TEXT) return sp_text(value->as.text.bytes, value->as.text.len);
+    return *value;
+}
+
+static void sp_drop(sp_value *value) {
+    if (value->tag == SP_TEXT) free(value->as.text.bytes);
+    *value = SP_NULL_VALUE;
+}
+
+/* The text of texts and symbols */
+static bool sp_text_of(const sp_value *value, const char **bytes, size_t *len) {
+    switch (value->tag) {
+        case SP_TEXT: *bytes = value->as.text.bytes; *len = value->as.text.len; return true;
+        case SP_SYMBOL: *bytes = value->as.symbol->bytes; *len = value->as.symbol->len; return true;
+        default: return false;
+    }
+}
+
+static bool sp_equal(const sp_value *left, const sp_value *right) {
+    if (left->tag != right->tag) return false;
+    switch (left->tag) {
+        case SP_SYMBOL: return left->as.symbol == right->as.symbol;
+        case SP_BOOLEAN: return left->as.boolean == right->as.boolean;
+        case SP_SMALLINT: return left->as.integer == right->as.integer;
+        case SP_FLOAT: return left->as.number == right->as.number;
+        case SP_TEXT: return left->as.text.len == right->as.text.len && memcmp(left->as.text.bytes, right->as.text.bytes, left->as.text.len) == 0;
+        case SP_TIME: return left->as.time.tv_sec == right->as.time.tv_sec && left->as.time.tv_nsec == right->as.time.tv_nsec;
+        case SP_DURATION: return left->as.nanos == right->as.nanos;
+        case SP_NULL: return true;
+    }
+    return false;
+}
+
+static uint64_t sp_hash(const sp_value *value) {
+    uint64_t hash = sp_hash_bytes(0xCBF29CE484222325, &value->tag, sizeof value->tag);
+    switch (value->tag) {
+        case SP_SYMBOL: return hash ^ value->as.symbol->hash;
+        case SP_BOOLEAN: return sp_hash_bytes(hash, &value->as.boolean, sizeof value->as.boolean);
+        case SP_SMALLINT: return sp_hash_bytes(hash, &value->as.integer, sizeof value->as.integer);
+        case SP_FLOAT: {
+            double number = value->as.number == 0 ? 0 : value->as.number;
+            return sp_hash_bytes(hash, &number, sizeof number);
+        }
+        case SP_TEXT: return sp_hash_bytes(hash, value->as.text.bytes, value->as.text.len);
+        case SP_TIME: return sp_hash_bytes(hash, &value->as.time.tv_sec, sizeof value->as.time.tv_sec);
+        case SP_DURATION: return sp_hash_bytes(hash, &value->as.nanos, sizeof value->as.nanos);
+        case SP_NULL: return hash;
+    }
+    return hash;
+}
+
+static int sp_compare_bytes(const char *left, size_t left_len, const char *right, size_t right_len) {
+    int order = memcmp(left, right, left_len < right_len ? left_len : right_len);
+    if (order) return order;
+    return (left_len > right_len) - (left_len < right_len);
+}
+
+/* Like `PartialOrd` of `ast::Value`, incomparable floats are equal */
+static int sp_compare(const sp_value *left, const sp_value *right) {
+    if (left->tag != right->tag) return left->tag < right->tag ? -1 : 1;
+    switch (left->tag) {
+        case SP_SYMBOL: return sp_compare_bytes(left->as.symbol->bytes, left->as.symbol->len, right->as.symbol->bytes, right->as.symbol->len);
+        case SP_BOOLEAN: return left->as.boolean - right->as.boolean;
+        case SP_SMALLINT: return (left->as.integer > right->as.integer) - (left->as.integer < right->as.integer);
+        case SP_FLOAT: return (left->as.number > right->as.number) - (left->as.number < right->as.number);
+        case SP_TEXT: return sp_compare_bytes(left->as.text.bytes, left->as.text.len, right->as.text.bytes, right->as.text.len);
+        case SP_TIME:
+            if (left->as.time.tv_sec != right->as.time.tv_sec) return left->as.time.tv_sec < right->as.time.tv_sec ? -1 : 1;
+            return (left->as.time.tv_nsec > right->as.time.tv_nsec) - (left->as.time.tv_nsec < right->as.time.tv_nsec);
+        case SP_DURATION: return (left->as.nanos > right->as.nanos) - (left->as.nanos < right->as.nanos);
+        case SP_NULL: return 0;
+    }
+    return 0;
+}
+
+static bool sp_is_truthy(const sp_value *value) {
+    switch (value->tag) {
+        case SP_SYMBOL: return true;
+        case SP_BOOLEAN: return value->as.boolean;
+        case SP_SMALLINT: return value->as.integer != 0;
+        case SP_FLOAT: return !isnan(value->as.number) && !isinf(value->as.number) && value->as.number != 0;
+        case SP_TEXT: return value->as.text.len > 0;
+        case SP_TIME: return true;
+        case SP_DURATION: return value->as.nanos != 0;
+        case SP_NULL: return false;
+    }
+    return false;
+}
+
+static void sp_push_nanos(sp_buf *buf, sp_uint nanos, bool seconds) {
+    if (seconds) {
+        sp_push_double(buf, sp_seconds(nanos));
+        return;
+    }
+
+    /* like Rust's `{:?}` of a Duration */
+    sp_uint whole, fraction;
+    int digits;
+    const char *unit;
+    if (nanos >= 1000000000) { whole = nanos / 1000000000; fraction = nanos % 1000000000; digits = 9; unit = "s"; }
+    else if (nanos >= 1000000) { whole = nanos / 1000000; fraction = nanos % 1000000; digits = 6; unit = "ms"; }
+    else if (nanos >= 1000) { whole = nanos / 1000; fraction = nanos % 1000; digits = 3; unit = "\xC2\xB5s"; }
+    else { whole = nanos; fraction = 0; digits = 0; unit = "ns"; }
+
+    sp_push_int(buf, (sp_int)whole);
+    if (fraction) {
+        char decimals[16];
+        snprintf(decimals, sizeof decimals, ".%0*lld", digits, (long long)fraction);
+        size_t len = strlen(decimals);
+        while (decimals[len - 1] == '0') len--;
+        sp_push(buf, decimals, len);
+    }
+    sp_push_str(buf, unit);
+}
+
+/* The time passed since `since` */
+static void sp_push_duration(sp_buf *buf, struct timespec since, bool seconds) {
+    struct timespec now = sp_now();
+    if (since.tv_nsec < 0) since = now;
+    sp_int nanos = sp_time_nanos(now) - sp_time_nanos(since);
+    sp_push_nanos(buf, nanos < 0 ? 0 : (sp_uint)nanos, seconds);
+}
+
+/* The decimal digits of the big-endian number in `bytes` */
+static void sp_push_bytes_number(sp_buf *buf, const char *bytes, size_t len) {
+    unsigned char *number = sp_alloc(NULL, len);
+    memcpy(number, bytes, len);
+    sp_buf digits = { 0 };
+
+    size_t start = 0;
+    do {
+        unsigned remainder = 0;
+        for (size_t at = start; at < len; at++) {
+            unsigned current = remainder * 256 + number[at];
+            number[at] = (unsigned char)(current / 10);
+            remainder = current % 10;
+        }
+        char digit = (char)('0' + remainder);
+        sp_push(&digits, &digit, 1);
+        while (start < len && number[start] == 0) start++;
+    } while (start < len);
+
+    for (size_t at = digits.len; at > 0; at--) sp_push(buf, &digits.bytes[at - 1], 1);
+    free(digits.bytes);
+    free(number);
+}
+
+static void sp_push_code_point(sp_buf *buf, sp_int integer) {
+    sp_uint code = (sp_uint)integer;
+    if (code <= 0x10FFFF && (code < 0xD800 || code > 0xDFFF)) sp_push_char(buf, (uint32_t)code);
+    else sp_push_char(buf, 0xFFFD);
+}
+
+/* `value_to_string`, `pretty` is the `$` form */
+static void sp_push_value(sp_buf *buf, const sp_value *value, bool pretty) {
+    switch (value->tag) {
+        case SP_SYMBOL:
+            if (pretty) sp_push_bytes_number(buf, value->as.symbol->bytes, value->as.symbol->len);
+            else sp_push(buf, value->as.symbol->bytes, value->as.symbol->len);
+            break;
+        case SP_BOOLEAN:
+            sp_push_str(buf, pretty ? (value->as.boolean ? "1" : "0") : (value->as.boolean ? "true" : "false"));
+            break;
+        case SP_SMALLINT:
+            if (pretty) sp_push_code_point(buf, value->as.integer);
+            else sp_push_int(buf, value->as.integer);
+            break;
+        case SP_FLOAT:
+            sp_push_double(buf, value->as.number);
+            break;
+        case SP_TEXT:
+            if (pretty) {
+                sp_push(buf, value->as.text.bytes, value->as.text.len);
+                break;
+            }
+            sp_push_str(buf, "/");
+            for (size_t at = 0; at < value->as.text.len; at++) {
+                if (value->as.text.bytes[at] == '/') sp_push_str(buf, "\\");
+                sp_push(buf, &value->as.text.bytes[at], 1);
+            }
+            sp_push_str(buf, "/");
+            break;
+        case SP_TIME:
+            sp_push_duration(buf, value->as.time, pretty);
+            break;
+        case SP_DURATION:
+            sp_push_nanos(buf, value->as.nanos, pretty);
+            break;
+        case SP_NULL:
+            if (pretty) sp_push(buf, "", 1);
+            else sp_push_str(buf, "null");
+            break;
+    }
+}
+
+/* Like Rust's `{:?}` of a Value, for memory dumps */
+static void sp_push_value_debug(sp_buf *buf, const sp_value *value) {
+    char time[64];
+    switch (value->tag) {
+        case SP_SYMBOL:
+            sp_push_str(buf, "Symbol(");
+            sp_push_debug(buf, value->as.symbol->bytes, value->as.symbol->len);
+            sp_push_str(buf, ")");
+            break;
+        case SP_BOOLEAN: sp_push_str(buf, value->as.boolean ? "Boolean(true)" : "Boolean(false)"); break;
+        case SP_SMALLINT:
+            sp_push_str(buf, "SmallInt(");
+            sp_push_int(buf, value->as.integer);
+            sp_push_str(buf, ")");
+            break;
+        case SP_FLOAT:
+            sp_push_str(buf, "Float(");
+            sp_push_double(buf, value->as.number);
+            sp_push_str(buf, ")");
+            break;
+        case SP_TEXT:
+            sp_push_str(buf, "Text(");
+            sp_push_debug(buf, value->as.text.bytes, value->as.text.len);
+            sp_push_str(buf, ")");
+            break;
+        case SP_TIME:
+            if (value->as.time.tv_nsec < 0) {
+                sp_push_str(buf, "Time(None)");
+                break;
+            }
+            snprintf(time, sizeof time, "Time(Some(Instant { tv_sec: %lld, tv_nsec: %ld }))", (long long)value->as.time.tv_sec, value->as.time.tv_nsec);
+            sp_push_str(buf, time);
+            break;
+        case SP_DURATION:
+            sp_push_str(buf, "Duration(");
+            sp_push_nanos(buf, value->as.nanos, false);
+            sp_push_str(buf, ")");
+            break;
+        case SP_NULL: sp_push_str(buf, "Null"); break;
+    }
+}
+
+/* Memory, a hash table that remembers the order of insertion */
+
+typedef struct { sp_value key, value; } sp_entry;
+
+typedef struct {
+    sp_entry *entries;
+    size_t len, capacity;
+    size_t *slots; /* index of the entry + 1, 0 when empty */
+    size_t slot_count;
+} sp_memory;
+
+static size_t sp_find(const sp_memory *memory, const sp_value *key, uint64_t hash) {
+    size_t slot = hash & (memory->slot_count - 1);
+    while (memory->slots[slot] && !sp_equal(&memory->entries[memory->slots[slot] - 1].key, key)) {
+        slot = (slot + 1) & (memory->slot_count - 1);
+    }
+    return slot;
+}
+
+static const sp_value *sp_get(const sp_memory *memory, const sp_value *key) {
+    if (!memory->len) return &SP_NULL_VALUE;
+    size_t entry = memory->slots[sp_find(memory, key, sp_hash(key))];
+    return entry ? &memory->entries[entry - 1].value : &SP_NULL_VALUE;
+}
+
+/* Takes `key` and `value` */
+static void sp_store(sp_memory *memory, sp_value key, sp_value value) {
+    if ((memory->len + 1) * 2 > memory->slot_count) {
+        memory->slot_count = memory->slot_count ? memory->slot_count * 2 : 64;
+        free(memory->slots);
+        memory->slots = calloc(memory->slot_count, sizeof *memory->slots);
+        if (!memory->slots) sp_out_of_memory();
+        for (size_t entry = 0; entry < memory->len; entry++) {
+            const sp_value *existing = &memory->entries[entry].key;
+            memory->slots[sp_find(memory, existing, sp_hash(existing))] = entry + 1;
+        }
+    }
+
+    size_t slot = sp_find(memory, &key, sp_hash(&key));
+    if (memory->slots[slot]) {
+        sp_entry *entry = &memory->entries[memory->slots[slot] - 1];
+        sp_drop(&entry->value);
+        entry->value = value;
+        sp_drop(&key);
+        return;
+    }
+
+    if (memory->len == memory->capacity) {
+        memory->capacity = memory->capacity ? memory->capacity * 2 : 32;
+        memory->entries = sp_alloc(memory->entries, memory->capacity * sizeof *memory->entries);
+    }
+    memory->entries[memory->len] = (sp_entry){ key, value };
+    memory->slots[slot] = ++memory->len;
+}
+
+/* A copy of every entry, for scoped calls */
+static sp_memory sp_copy_memory(const sp_memory *memory) {
+    sp_memory copy = { 0 };
+    for (size_t entry = 0; entry < memory->len; entry++) {
+        sp_store(&copy, sp_clone(&memory->entries[entry].key), sp_clone(&memory->entries[entry].value));
+    }
+    return copy;
+}
+
+static void sp_free_memory(sp_memory *memory) {
+    for (size_t entry = 0; entry < memory->len; entry++) {
+        sp_drop(&memory->entries[entry].key);
+        sp_drop(&memory->entries[entry].value);
+    }
+    free(memory->entries);
+    free(memory->slots);
+    *memory = (sp_memory){ 0 };
+}
+
+static int sp_compare_entries(const void *left, const void *right) {
+    return sp_compare(&((const sp_entry *)left)->key, &((const sp_entry *)right)->key);
+}
+
+static void sp_push_memory(sp_buf *buf, const sp_memory *memory, bool sort) {
+    sp_entry *entries = sp_alloc(NULL, memory->len * sizeof *entries);
+    if (memory->len) memcpy(entries, memory->entries, memory->len * sizeof *entries);
+    if (sort) qsort(entries, memory->len, sizeof *entries, sp_compare_entries);
+
+    sp_push_str(buf, "{\n");
+    for (size_t at = 0; at < memory->len; at++) {
+        sp_push_str(buf, "\t");
+        sp_push_value_debug(buf, &entries[at].key);
+        sp_push_str(buf, " => ");
+        sp_push_value_debug(buf, &entries[at].value);
+        sp_push_str(buf, "\n");
+    }
+    sp_push_str(buf, "}");
+    free(entries);
+}
+
+/* `§` expressions, following the values in memory `reader` times */
+static const sp_value *sp_read_value(const sp_memory *memory, const sp_value *value, size_t reader) {
+    if (reader == 0) return value;
+
+    const sp_value **chain = sp_alloc(NULL, 8 * sizeof *chain);
+    size_t len = 0, capacity = 8;
+    chain[len++] = value;
+    const sp_value *current = value;
+
+    for (size_t i = 0; i < reader; i++) {
+        current = sp_get(memory, current);
+
+        /* a loop, where it ends is known without going around it */
+        for (size_t index = 0; index < len; index++) {
+            if (!sp_equal(chain[index], current)) continue;
+            const sp_value *result = chain[(reader + i + index + 1) % (len - index) + index];
+            free(chain);
+            return result;
+        }
+
+        if (len == capacity) chain = sp_alloc(chain, (capacity *= 2) * sizeof *chain);
+        chain[len++] = current;
+    }
+
+    free(chain);
+    return current;
+}
+
+/* Integers that don't fit in 128 bits */
+static sp_error sp_overflowed(sp_int *result, sp_int wrapped) {
+    if (!SP_FIXED_WIDTH) return SP_OVERFLOW;
+    *result = wrapped;
+    return SP_OK;
+}
+
+/* `string_to_value`, for inputs and `{=...}` */
+static sp_error sp_parse(const char *bytes, size_t len, sp_value *result) {
+    while (len && (*bytes == ' ' || (*bytes >= '\t' && *bytes <= '\r'))) { bytes++; len--; }
+    while (len && (bytes[len - 1] == ' ' || (bytes[len - 1] >= '\t' && bytes[len - 1] <= '\r'))) len--;
+
+    if (!len) {
+        *result = SP_NULL_VALUE;
+        return SP_OK;
+    }
+
+    size_t dots = 0;
+    bool numeric = true;
+    for (size_t at = 0; at < len; at++) {
+        if (bytes[at] == '.') dots++;
+        else if (bytes[at] < '0' || bytes[at] > '9') numeric = false;
+    }
+
+    if (numeric && dots == 0) {
+        sp_int integer = 0;
+        bool overflow = false;
+        for (size_t at = 0; at < len; at++) {
+            overflow |= __builtin_mul_overflow(integer, 10, &integer);
+            overflow |= __builtin_add_overflow(integer, bytes[at] - '0', &integer);
+        }
+        *result = sp_small(0);
+        if (overflow) {
+            sp_uint wrapped = 0;
+            for (size_t at = 0; at < len; at++) wrapped = wrapped * 10 + (sp_uint)(bytes[at] - '0');
+            return sp_overflowed(&result->as.integer, (sp_int)wrapped);
+        }
+        result->as.integer = integer;
+        return SP_OK;
+    }
+
+    if (numeric && dots == 1 && len > 1) {
+        if (!SP_FIXED_WIDTH) return SP_FLOATS;
+        char *copy = sp_alloc(NULL, len + 1);
+        memcpy(copy, bytes, len);
+        copy[len] = 0;
+        *result = sp_float(strtod(copy, NULL));
+        free(copy);
+        return SP_OK;
+    }
+
+#define SP_IS(word) (len == strlen(word) && memcmp(bytes, word, len) == 0)
+    if (SP_IS("true") || SP_IS("on") || SP_IS("yes")) *result = sp_boolean(true);
+    else if (SP_IS("false") || SP_IS("off") || SP_IS("no")) *result = sp_boolean(false);
+    else if (SP_IS("null")) *result = SP_NULL_VALUE;
+    else if (SP_IS("\xC2\xB5")) { *result = sp_time(); result->as.time = sp_now(); }
+    else {
+        bool symbol = true;
+        for (size_t at = 0; at < len; at++) {
+            char c = bytes[at];
+            symbol &= (c >= 'a' && c <= 'z') || (c >= 'A' && c <= 'Z') || (c >= '0' && c <= '9') || c == '_';
+        }
+        *result = symbol ? sp_symbol_value(bytes, len) : sp_text(bytes, len);
+    }
+#undef SP_IS
+    return SP_OK;
+}
+
+/* `bytes` as a big-endian number, like texts are subtracted */
+static sp_error sp_bytes_number(const char *bytes, size_t len, sp_int *result) {
+    sp_uint number = 0;
+    bool overflow = false;
+    for (size_t at = 0; at < len; at++) {
+        overflow |= number >> 120 != 0;
+        number = number << 8 | (unsigned char)bytes[at];
+    }
+    overflow |= number > (sp_uint)SP_INT_MAX;
+    if (overflow) return sp_overflowed(result, (sp_int)number);
+    *result = (sp_int)number;
+    return SP_OK;
+}
+
+static sp_error sp_repeat(sp_buf *buf, const char *bytes, size_t len, sp_uint count, bool reversed) {
+    if (len && count > (sp_uint)(PTRDIFF_MAX / len)) return SP_TOO_LARGE;
+    sp_reserve(buf, len * (size_t)count);
+    for (sp_uint time = 0; time < count; time++) {
+        if (reversed) sp_push_reversed(buf, bytes, len);
+        else sp_push(buf, bytes, len);
+    }
+    return SP_OK;
+}
+
+static sp_error sp_pow(sp_int base, sp_int exponent, sp_int *result) {
+    if (exponent < 0) return SP_NEGATIVE_EXPONENT;
+    if (exponent > UINT32_MAX) return SP_EXPONENT_TOO_LARGE;
+
+    sp_int power = 1;
+    sp_uint wrapped = 1, wrapped_base = (sp_uint)base;
+    bool overflow = false;
+    for (uint64_t bits = (uint64_t)exponent; bits; bits >>= 1) {
+        if (bits & 1) {
+            overflow |= __builtin_mul_overflow(power, base, &power);
+            wrapped *= wrapped_base;
+        }
+        if (bits > 1) {
+            overflow |= __builtin_mul_overflow(base, base, &base);
+            wrapped_base *= wrapped_base;
+        }
+    }
+    if (overflow) return sp_overflowed(result, (sp_int)wrapped);
+    *result = power;
+    return SP_OK;
+}
+
+/* `.<<`, a negative count shifts right (rounding down) */
+static sp_error sp_shift(sp_int value, sp_int count, sp_int *result) {
+    if (count > UINT32_MAX) return SP_SHIFT_TOO_LARGE;
+    if (count < 0) {
+        *result = count <= -128 ? (value < 0 ? -1 : 0) : value >> -count;
+        return SP_OK;
+    }
+    sp_int wrapped = count >= 128 ? 0 : (sp_int)((sp_uint)value << count);
+    if (count >= 128 ? value != 0 : wrapped >> count != value) return sp_overflowed(result, wrapped);
+    *result = wrapped;
+    return SP_OK;
+}
+
+/* `~` with a symbol on durations (`ms`...), numbers (which become durations) and times (`unix`, `date`...) */
+static sp_error sp_index_key(const sp_value *left, const sp_symbol *key, sp_value *result) {
+    static const struct { const char *name; sp_uint nanos; } units[] = { { "s", 1000000000 }, { "ms", 1000000 }, { "us", 1000 }, { "ns", 1 } };
+#define SP_KEY(word) (key->len == strlen(word) && memcmp(key->bytes, word, key->len) == 0)
+    sp_uint unit = 0;
+    for (size_t at = 0; at < sizeof units / sizeof *units; at++) {
+        if (SP_KEY(units[at].name)) unit = units[at].nanos;
+    }
+
+    switch (left->tag) {
+        case SP_DURATION:
+            if (unit == 1000000000) {
+                if (!SP_FIXED_WIDTH) return SP_FLOATS;
+                *result = sp_float(sp_seconds(left->as.nanos));
+            } else if (unit) {
+                *result = sp_small((sp_int)(left->as.nanos / unit));
+            }
+            break;
+        case SP_SMALLINT:
+            if (unit && left->as.integer >= 0 && (sp_uint)left->as.integer <= SP_DURATION_MAX / unit) {
+                *result = sp_duration((sp_uint)left->as.integer * unit);
+            }
+            break;
+        case SP_FLOAT:
+            if (unit) *result = sp_duration_of(left->as.number * (double)unit / 1e9);
+            break;
+        case SP_TIME: {
+            sp_int nanos = sp_wall_nanos(left->as.time);
+            sp_int seconds = nanos / 1000000000 - (nanos % 1000000000 < 0);
+            if (SP_KEY("unix")) *result = sp_small(seconds);
+            else if (SP_KEY("unix_ms")) *result = sp_small(nanos / 1000000 - (nanos % 1000000 < 0));
+            else if (SP_KEY("date") || SP_KEY("datetime")) {
+                time_t time = (time_t)seconds;
+                struct tm date;
+                char text[64];
+                if (!gmtime_r(&time, &date)) break;
+                size_t len = strftime(text, sizeof text, SP_KEY("date") ? "%Y-%m-%d" : "%Y-%m-%dT%H:%M:%SZ", &date);
+                *result = sp_text(text, len);
+            }
+            break;
+        }
+        default: break;
+    }
+#undef SP_KEY
+    return SP_OK;
+}
+
+/* `operate`, with the value at the pointer on the left */
+static sp_error sp_apply(sp_operator operator, sp_value left, sp_value right, sp_value *result) {
+    *result = SP_NULL_VALUE;
+
+    /* the complement only uses its value, which doesn't get converted to the kind of the value at the pointer */
+    if (operator == SP_BIT_NOT) {
+        if (right.tag == SP_SMALLINT) *result = sp_small(~right.as.integer);
+        return SP_OK;
+    }
+
+    /* numbers of different kinds get converted to the bigger kind */
+    if (left.tag == SP_SMALLINT && right.tag == SP_FLOAT) left = sp_float((double)left.as.integer);
+    if (left.tag == SP_FLOAT && right.tag == SP_SMALLINT) right = sp_float((double)right.as.integer);
+
+    const char *left_text = NULL, *right_text = NULL;
+    size_t left_len = 0, right_len = 0;
+    if (sp_text_of(&left, &left_text, &left_len) && sp_text_of(&right, &right_text, &right_len)) {
+        int order = sp_compare_bytes(left_text, left_len, right_text, right_len);
+        sp_int left_number, right_number, difference;
+        sp_error error;
+        switch (operator) {
+            case SP_MINUS:
+                if ((error = sp_bytes_number(left_text, left_len, &left_number))) return error;
+                if ((error = sp_bytes_number(right_text, right_len, &right_number))) return error;
+                if (__builtin_sub_overflow(left_number, right_number, &difference)
+                    && (error = sp_overflowed(&difference, (sp_int)((sp_uint)left_number - (sp_uint)right_number)))) return error;
+                *result = sp_small(difference);
+                return SP_OK;
+            case SP_UNEQUAL: *result = sp_boolean(order != 0); return SP_OK;
+            case SP_EQUAL: *result = sp_boolean(order == 0); return SP_OK;
+            case SP_LESS_THAN: *result = sp_boolean(order < 0); return SP_OK;
+            case SP_GREATER_THAN: *result = sp_boolean(order > 0); return SP_OK;
+            case SP_LESS_THAN_OR_EQUAL: *result = sp_boolean(order <= 0); return SP_OK;
+            case SP_GREATER_THAN_OR_EQUAL: *result = sp_boolean(order >= 0); return SP_OK;
+            default: break;
+        }
+    }
+
+    bool integers = left.tag == SP_SMALLINT && right.tag == SP_SMALLINT;
+    bool floats = left.tag == SP_FLOAT && right.tag == SP_FLOAT;
+    bool times = left.tag == SP_TIME && right.tag == SP_TIME;
+    bool durations = left.tag == SP_DURATION && right.tag == SP_DURATION;
+    sp_int l = left.as.integer, r = right.as.integer, integer = 0;
+    double x = left.as.number, y = right.as.number;
+    sp_uint a = left.as.nanos, b = right.as.nanos;
+    bool left_is_text = sp_text_of(&left, &left_text, &left_len);
+    sp_buf buf = { 0 };
+    sp_error error = SP_OK;
+
+    switch (operator) {
+        case SP_INDEX: {
+            if (!left_is_text && right.tag == SP_SYMBOL) {
+                error = sp_index_key(&left, right.as.symbol, result);
+                break;
+            }
+            if (!left_is_text) break;
+            sp_uint index;
+            if (right.tag == SP_SMALLINT && r >= 0) index = (sp_uint)r;
+            else if (right.tag == SP_FLOAT && floor(y) >= 0 && floor(y) < 3.4e38) index = (sp_uint)floor(y);
+            else break;
+            for (size_t at = 0; at < left_len; index--) {
+                size_t char_len = sp_char_len((unsigned char)left_text[at]);
+                if (at + char_len > left_len) char_len = left_len - at;
+                if (index == 0) {
+                    *result = sp_text(left_text + at, char_len);
+                    break;
+                }
+                at += char_len;
+            }
+            break;
+        }
+        // only lists and maps have indexes to assign and properties, and they aren't supported
+        case SP_SET_INDEX: case SP_PROPERTY: break;
+        case SP_AND: *result = sp_boolean(sp_is_truthy(&left) && sp_is_truthy(&right)); break;
+        case SP_OR: *result = sp_boolean(sp_is_truthy(&left) || sp_is_truthy(&right)); break;
+        case SP_XOR: *result = sp_boolean(sp_is_truthy(&left) != sp_is_truthy(&right)); break;
+        case SP_BIT_AND: if (integers) *result = sp_small(l & r); break;
+        case SP_BIT_OR: if (integers) *result = sp_small(l | r); break;
+        case SP_BIT_XOR: if (integers) *result = sp_small(l ^ r); break;
+        case SP_BIT_NOT: break;
+        case SP_SHIFT_LEFT:
+        case SP_SHIFT_RIGHT:
+            if (integers) {
+                if (operator == SP_SHIFT_RIGHT) r = r == SP_INT_MIN ? SP_INT_MAX : -r;
+                error = sp_shift(l, r, &integer);
+                *result = sp_small(integer);
+            }
+            break;
+        case SP_PLUS:
+            if (integers) {
+                if (__builtin_add_overflow(l, r, &integer)) error = sp_overflowed(&integer, (sp_int)((sp_uint)l + (sp_uint)r));
+                *result = sp_small(integer);
+            } else if (floats) {
+                *result = sp_float(x + y);
+            } else if (left.tag == SP_SYMBOL && right.tag == SP_SYMBOL) {
+                sp_push(&buf, left_text, left_len);
+                sp_push(&buf, right.as.symbol->bytes, right.as.symbol->len);
+                *result = sp_symbol_value(buf.bytes, buf.len);
+            } else if (left_is_text && (right.tag == SP_TEXT || right.tag == SP_SMALLINT)) {
+                sp_push(&buf, left_text, left_len);
+                if (right.tag == SP_TEXT) sp_push(&buf, right.as.text.bytes, right.as.text.len);
+                else sp_push_int(&buf, r);
+                *result = sp_text_from(&buf);
+            } else if (left.tag == SP_TIME && right.tag == SP_DURATION) {
+                *result = sp_time_at(sp_time_nanos(left.as.time) + (sp_int)b);
+            } else if (left.tag == SP_DURATION && right.tag == SP_TIME) {
+                *result = sp_time_at(sp_time_nanos(right.as.time) + (sp_int)a);
+            } else if (durations) {
+                *result = sp_duration(a + b);
+            }
+            break;
+        case SP_MINUS:
+            if (integers) {
+                if (__builtin_sub_overflow(l, r, &integer)) error = sp_overflowed(&integer, (sp_int)((sp_uint)l - (sp_uint)r));
+                *result = sp_small(integer);
+            } else if (floats) {
+                *result = sp_float(x - y);
+            } else if (times) {
+                sp_int difference = sp_time_nanos(left.as.time) - sp_time_nanos(right.as.time);
+                if (difference >= 0) *result = sp_duration((sp_uint)difference);
+            } else if (left.tag == SP_TIME && right.tag == SP_DURATION) {
+                *result = sp_time_at(sp_time_nanos(left.as.time) - (sp_int)b);
+            } else if (durations && a >= b) {
+                *result = sp_duration(a - b);
+            }
+            break;
+        case SP_TIMES:
+            if (integers) {
+                if (__builtin_mul_overflow(l, r, &integer)) error = sp_overflowed(&integer, (sp_int)((sp_uint)l * (sp_uint)r));
+                *result = sp_small(integer);
+            } else if (floats) {
+                *result = sp_float(x * y);
+            } else if (left_is_text && right.tag == SP_SMALLINT) {
+                sp_uint count = r < 0 ? -(sp_uint)r : (sp_uint)r;
+                if (!(error = sp_repeat(&buf, left_text, left_len, count, r < 0))) *result = sp_text_from(&buf);
+            } else if (left_is_text && right.tag == SP_FLOAT) {
+                double whole = fabs(trunc(y));
+                sp_uint count = isnan(y) || whole >= 3.4e38 ? ~(sp_uint)0 : (sp_uint)whole;
+                size_t fraction = (size_t)round(fabs(y - trunc(y)) * (double)left_len);
+                if (fraction > left_len) fraction = left_len;
+                if (y < 0) {
+                    sp_push_reversed(&buf, left_text, fraction);
+                    error = sp_repeat(&buf, left_text, left_len, count, true);
+                } else {
+                    error = sp_repeat(&buf, left_text, left_len, count, false);
+                    sp_push(&buf, left_text, fraction);
+                }
+                if (!error) *result = sp_text_from(&buf);
+            } else if (left.tag == SP_DURATION && right.tag == SP_SMALLINT) {
+                if (r == 0 || (r > 0 && a <= SP_DURATION_MAX / (sp_uint)r)) *result = sp_duration(a * (sp_uint)r);
+            } else if (left.tag == SP_DURATION && right.tag == SP_FLOAT) {
+                *result = sp_duration_of(sp_seconds(a) * y);
+            }
+            break;
+        case SP_DIVIDE:
+            if (integers) {
+                if (r == 0) return SP_DIVISION_BY_ZERO;
+                if (l == SP_INT_MIN && r == -1) error = sp_overflowed(&integer, SP_INT_MIN);
+                else integer = l / r;
+                *result = sp_small(integer);
+            } else if (floats) {
+                *result = sp_float(x / y);
+            } else if (durations) {
+                if (b == 0) return SP_DIVISION_BY_ZERO;
+                if (!SP_FIXED_WIDTH) return SP_FLOATS;
+                *result = sp_float(sp_seconds(a) / sp_seconds(b));
+            } else if (left.tag == SP_DURATION && right.tag == SP_SMALLINT) {
+                if (r == 0) return SP_DIVISION_BY_ZERO;
+                if (r > 0) *result = sp_duration(a / (sp_uint)r);
+            } else if (left.tag == SP_DURATION && right.tag == SP_FLOAT) {
+                *result = sp_duration_of(sp_seconds(a) / y);
+            }
+            break;
+        case SP_MODULO:
+            if (integers) {
+                if (r == 0) return SP_DIVISION_BY_ZERO;
+                *result = sp_small(r == -1 ? 0 : l % r);
+            } else if (floats) {
+                *result = sp_float(fmod(x, y));
+            } else if (durations) {
+                if (b == 0) return SP_DIVISION_BY_ZERO;
+                *result = sp_duration(a % b);
+            }
+            break;
+        case SP_PPERCENT:
+            if (integers) {
+                if (r == 0) return SP_DIVISION_BY_ZERO;
+                integer = r == -1 ? 0 : l % r;
+                if (integer != 0 && (integer < 0) != (r < 0)) integer += r;
+                *result = sp_small(integer);
+            } else if (floats) {
+                *result = sp_float(fmod(fmod(x, y) + y, y));
+            }
+            break;
+        case SP_EXPONENTIAL:
+            if (integers) {
+                if ((error = sp_pow(l, r, &integer))) return error;
+                *result = sp_small(integer);
+            } else if (floats) {
+                *result = sp_float(pow(x, y));
+            }
+            break;
+        case SP_UNEQUAL:
+        case SP_EQUAL: {
+            bool equal = left.tag == right.tag && (left.tag == SP_BOOLEAN || integers || floats || times || durations) && sp_equal(&left, &right);
+            *result = sp_boolean(operator == SP_EQUAL ? equal : !equal);
+            break;
+        }
+        case SP_LESS_THAN:
+            if (integers) *result = sp_boolean(l < r);
+            else if (floats) *result = sp_boolean(x < y);
+            else if (times || durations) *result = sp_boolean(sp_compare(&left, &right) < 0);
+            break;
+        case SP_GREATER_THAN:
+            if (integers) *result = sp_boolean(l > r);
+            else if (floats) *result = sp_boolean(x > y);
+            else if (times || durations) *result = sp_boolean(sp_compare(&left, &right) > 0);
+            break;
+        case SP_LESS_THAN_OR_EQUAL:
+            if (integers) *result = sp_boolean(l <= r);
+            else if (floats) *result = sp_boolean(x <= y);
+            else if (times || durations) *result = sp_boolean(sp_compare(&left, &right) <= 0);
+            break;
+        case SP_GREATER_THAN_OR_EQUAL:
+            if (integers) *result = sp_boolean(l >= r);
+            else if (floats) *result = sp_boolean(x >= y);
+            else if (times || durations) *result = sp_boolean(sp_compare(&left, &right) >= 0);
+            break;
+    }
+
+    free(buf.bytes);
+    return error;
+}
+
+/* What the generated statements call, like `run::Runtime` */
+
+/* A call that hasn't returned yet, `saved` is the memory before a scoped call */
+typedef struct {
+    size_t call;
+    bool scoped;
+    sp_memory saved;
+} sp_frame;
+
+typedef struct {
+    sp_memory memory;
+    sp_value pointer;
+    /* the pointers saved by `|>`, the last one is `@` */
+    sp_value *saved;
+    size_t saved_count, saved_capacity;
+    struct timespec last_flush;
+    sp_frame *calls;
+    size_t call_count, call_capacity;
+} sp_runtime;
+
+static void sp_init(sp_runtime *rt) {
+    *rt = (sp_runtime){ .pointer = SP_NULL_VALUE, .last_flush = sp_now() };
+    setvbuf(stdout, NULL, _IOFBF, 1 << 16);
+}
+
+static void sp_flush(sp_runtime *rt) {
+    fflush(stdout);
+    rt->last_flush = sp_now();
+}
+
+static const sp_value *sp_pointed(sp_runtime *rt) {
+    return sp_get(&rt->memory, &rt->pointer);
+}
+
+/* Times that haven't been assigned yet become the current time */
+static sp_value sp_started(const sp_value *value) {
+    sp_value started = sp_clone(value);
+    if (started.tag == SP_TIME && started.as.time.tv_nsec < 0) started.as.time = sp_now();
+    return started;
+}
+
+static void sp_load(sp_runtime *rt, const sp_value *value, size_t reader) {
+    sp_value pointer = sp_clone(sp_read_value(&rt->memory, value, reader));
+    sp_drop(&rt->pointer);
+    rt->pointer = pointer;
+}
+
+static void sp_push_pointer(sp_runtime *rt, const sp_value *value, size_t reader) {
+    /* cloned first, `value` can be the last saved pointer */
+    sp_value pointer = sp_clone(sp_read_value(&rt->memory, value, reader));
+    if (rt->saved_count == rt->saved_capacity) {
+        rt->saved_capacity = rt->saved_capacity ? rt->saved_capacity * 2 : 16;
+        rt->saved = sp_alloc(rt->saved, rt->saved_capacity * sizeof *rt->saved);
+    }
+    rt->saved[rt->saved_count++] = rt->pointer;
+    rt->pointer = pointer;
+}
+
+static void sp_pop_pointer(sp_runtime *rt) {
+    if (!rt->saved_count) return;
+    sp_drop(&rt->pointer);
+    rt->pointer = rt->saved[--rt->saved_count];
+}
+
+static void sp_exchange_pointer(sp_runtime *rt) {
+    if (!rt->saved_count) return;
+    sp_value saved = rt->saved[rt->saved_count - 1];
+    rt->saved[rt->saved_count - 1] = rt->pointer;
+    rt->pointer = saved;
+}
+
+static const sp_value *sp_saved_pointer(const sp_runtime *rt) {
+    return rt->saved_count ? &rt->saved[rt->saved_count - 1] : &SP_NULL_VALUE;
+}
+
+static void sp_define(sp_runtime *rt, const sp_value *label, size_t reader, size_t index) {
+    sp_store(&rt->memory, sp_clone(sp_read_value(&rt->memory, label, reader)), sp_small((sp_int)index + 1));
+}
+
+/* Whether the label has a statement to jump to, written to `address` */
+static bool sp_address(sp_runtime *rt, const sp_value *label, size_t reader, size_t *address) {
+    const sp_value *value = sp_get(&rt->memory, sp_read_value(&rt->memory, label, reader));
+    if (value->tag == SP_SMALLINT && value->as.integer >= 0 && (sp_uint)value->as.integer <= SIZE_MAX) {
+        *address = (size_t)value->as.integer;
+        return true;
+    }
+    return false;
+}
+
+/* The statement to continue from, `next` if the jump does nothing */
+static size_t sp_jump(sp_runtime *rt, const sp_value *label, size_t reader, size_t next) {
+    size_t address;
+    return sp_address(rt, label, reader, &address) ? address : next;
+}
+
+/* `[>:] label` at `index`, like `sp_jump` */
+static size_t sp_call(sp_runtime *rt, const sp_value *label, size_t reader, size_t index, bool scoped, size_t next) {
+    size_t address;
+    if (!sp_address(rt, label, reader, &address)) return next;
+    if (rt->call_count == rt->call_capacity) {
+        rt->call_capacity = rt->call_capacity ? rt->call_capacity * 2 : 16;
+        rt->calls = sp_alloc(rt->calls, rt->call_capacity * sizeof *rt->calls);
+    }
+    rt->calls[rt->call_count++] = (sp_frame){
+        .call = index,
+        .scoped = scoped,
+        .saved = scoped ? sp_copy_memory(&rt->memory) : (sp_memory){ 0 },
+    };
+    return address;
+}
+
+/* `[<:]`, the statement after the last call, `next` if there's no call to return from */
+static size_t sp_return(sp_runtime *rt, size_t next) {
+    if (!rt->call_count) return next;
+    sp_frame frame = rt->calls[--rt->call_count];
+    if (frame.scoped) {
+        /* everything but the value at the pointer goes back to how it was before the call */
+        const sp_value *result = sp_get(&rt->memory, &rt->pointer);
+        if (result != &SP_NULL_VALUE) sp_store(&frame.saved, sp_clone(&rt->pointer), sp_clone(result));
+        sp_free_memory(&rt->memory);
+        rt->memory = frame.saved;
+    }
+    return frame.call + 1;
+}
+
+static void sp_assign(sp_runtime *rt, const sp_value *value, size_t reader) {
+    sp_store(&rt->memory, sp_clone(&rt->pointer), sp_started(sp_read_value(&rt->memory, value, reader)));
+}
+
+static void sp_overwrite(sp_runtime *rt, const sp_value *value, size_t reader) {
+    sp_store(&rt->memory, sp_started(sp_read_value(&rt->memory, value, reader)), sp_clone(&rt->pointer));
+}
+
+static void sp_swap(sp_runtime *rt, const sp_value *value, size_t reader) {
+    sp_value key = sp_clone(sp_read_value(&rt->memory, value, reader));
+    sp_value pointed = sp_clone(sp_pointed(rt));
+    sp_value swapped = sp_clone(sp_get(&rt->memory, &key));
+    sp_store(&rt->memory, sp_clone(&rt->pointer), swapped);
+    sp_store(&rt->memory, key, pointed);
+}
+
+static sp_error sp_operate(sp_runtime *rt, sp_operator operator, const sp_value *value, size_t reader) {
+    sp_value result;
+    sp_value right = *sp_read_value(&rt->memory, value, reader);
+    if (right.tag == SP_TIME && right.as.time.tv_nsec < 0) right.as.time = sp_now();
+    sp_error error = sp_apply(operator, *sp_pointed(rt), right, &result);
+    if (error) return error;
+    sp_store(&rt->memory, sp_clone(&rt->pointer), result);
+    return SP_OK;
+}
+
+static bool sp_truthy(sp_runtime *rt) {
+    return sp_is_truthy(sp_pointed(rt));
+}
+
+static bool sp_exists(sp_runtime *rt) {
+    return sp_pointed(rt)->tag != SP_NULL;
+}
+
+/* The lines of `bytes` written top to bottom, like `{|...}` */
+static void sp_push_vertical(sp_buf *buf, const char *bytes, size_t len) {
+    size_t count = 0, capacity = 8, max = 0;
+    struct { const char *bytes; size_t len; } *lines = sp_alloc(NULL, capacity * sizeof *lines);
+
+    for (size_t start = 0; start < len;) {
+        const char *end = memchr(bytes + start, '\n', len - start);
+        size_t line_len = end ? (size_t)(end - bytes) - start : len - start;
+        if (count == capacity) lines = sp_alloc(lines, (capacity *= 2) * sizeof *lines);
+        lines[count].bytes = bytes + start;
+        lines[count].len = line_len && bytes[start + line_len - 1] == '\r' ? line_len - 1 : line_len;
+        if (lines[count].len > max) max = lines[count].len;
+        count++;
+        start += line_len + 1;
+    }
+
+    for (size_t column = 0; column < max; column++) {
+        if (column) sp_push_str(buf, "\n");
+        for (size_t line = 0; line < count; line++) {
+            size_t at = 0, index = 0;
+            while (at < lines[line].len && index < column) {
+                at += sp_char_len((unsigned char)lines[line].bytes[at]);
+                index++;
+            }
+            if (at < lines[line].len) {
+                size_t char_len = sp_char_len((unsigned char)lines[line].bytes[at]);
+                sp_push(buf, lines[line].bytes + at, at + char_len > lines[line].len ? lines[line].len - at : char_len);
+            } else {
+                sp_push_str(buf, " ");
+            }
+        }
+    }
+    free(lines);
+}
+
+static sp_error sp_log(sp_runtime *rt, const sp_log_args *log) {
+    static const char *const types[] = { "Symbol", "Boolean", "SmallInt", "Float", "Text", "Time", "Duration", "Null" };
+    sp_buf text = { 0 };
+
+    switch (log->kind) {
+        case SP_LOG_VALUE: sp_push_value(&text, sp_read_value(&rt->memory, &rt->pointer, log->reader), log->pretty); break;
+        case SP_LOG_TYPE: sp_push_str(&text, types[sp_pointed(rt)->tag]); break;
+        case SP_LOG_MEMORY: sp_push_memory(&text, &rt->memory, log->sort); break;
+        case SP_LOG_NONE: break;
+    }
+
+    if (log->reverse) {
+        sp_buf reversed = { 0 };
+        sp_push_reversed(&reversed, text.bytes, text.len);
+        free(text.bytes);
+        text = reversed;
+    }
+    for (size_t space = 0; space < log->space; space++) sp_push_str(&text, " ");
+    if (log->newline) sp_push_str(&text, "\n");
+    if (log->vertical) {
+        sp_buf vertical = { 0 };
+        sp_push_vertical(&vertical, text.bytes, text.len);
+        free(text.bytes);
+        text = vertical;
+    }
+
+    sp_error error = SP_OK;
+    if (log->assign) {
+        sp_value value;
+        if (!(error = sp_parse(text.bytes, text.len, &value))) sp_store(&rt->memory, sp_clone(&rt->pointer), value);
+    } else {
+        fwrite(text.bytes, 1, text.len, stdout);
+        struct timespec now = sp_now();
+        if ((now.tv_sec - rt->last_flush.tv_sec) * 1000 + (now.tv_nsec - rt->last_flush.tv_nsec) / 1000000 > 50) sp_flush(rt);
+    }
+    free(text.bytes);
+    return error;
+}
+
+/* `'`, the end of the input reads as an empty line */
+static sp_error sp_input(sp_runtime *rt) {
+    sp_flush(rt);
+    sp_buf line = { 0 };
+    for (int c; (c = getchar()) != EOF;) {
+        char byte = (char)c;
+        sp_push(&line, &byte, 1);
+        if (byte == '\n') break;
+    }
+    sp_value value;
+    sp_error error = sp_parse(line.bytes, line.len, &value);
+    free(line.bytes);
+    if (!error) sp_store(&rt->memory, sp_clone(&rt->pointer), value);
+    return error;
+}
+
+/* Prints the error and the calls that haven't returned, with the line of each statement from `lines` */
+static void sp_fail(sp_runtime *rt, sp_error error, size_t index, const unsigned *lines) {
+    static const char *const messages[] = {
+        [SP_DIVISION_BY_ZERO] = "Division by zero",
+        [SP_NEGATIVE_EXPONENT] = "Integer raised to a negative exponent",
+        [SP_EXPONENT_TOO_LARGE] = "Exponent is too large",
+        [SP_SHIFT_TOO_LARGE] = "Shift is too large",
+        [SP_OVERFLOW] = "Integer overflow, integers are 128 bits wide (`--fixed-width` makes them wrap)",
+        [SP_FLOATS] = "Floats need `--fixed-width`",
+        [SP_TOO_LARGE] = "Text is too large",
+    };
+    sp_flush(rt);
+    fprintf(stderr, "Error: %s (statement %zu, line %u)\n", messages[error], index, lines[index]);
+    for (size_t call = rt->call_count; call > 0 && call + 10 > rt->call_count; call--) {
+        size_t statement = rt->calls[call - 1].call;
+        fprintf(stderr, "    called from statement %zu (line %u)\n", statement, lines[statement]);
+    }
+    if (rt->call_count > 10) fprintf(stderr, "    ... and %zu more calls\n", rt->call_count - 10);
+    exit(1);
+}
@@ -0,0 +1,713 @@
+// SpeckyLang runtime, the same value semantics as the interpreter.
+// Integers are BigInts, floats go through `host.decimal` (JS numbers by default),
+// texts are handled by their characters and UTF-8 bytes like Rust strings.
+
+const SYMBOL = 0, BOOLEAN = 1, INTEGER = 2, FLOAT = 3, TEXT = 4, TIME = 5, DURATION = 6, LIST = 7, MAP = 8, NULL = 9;
+const NULL_VALUE = { type: NULL };
+const SMALL_MIN = -(1n << 127n), SMALL_MAX = (1n << 127n) - 1n;
+/** The longest Rust `Duration`, in nanoseconds */
+const DURATION_MAX = 18446744073709551615999999999n;
+const UNITS = { s: 1000000000n, ms: 1000000n, us: 1000n, ns: 1n };
+
+/** Thrown when a statement fails, `statement` and `line` tell which one */
+export class SpeckyError extends Error {
+    constructor(message) {
+        super(message);
+        this.name = "SpeckyError";
+        this.statement = undefined;
+        this.line = undefined;
+        /** The calls that hadn't returned yet, `{ statement, line }` outermost first */
+        this.calls = [];
+    }
+}
+
+/**
+ * Floats as JS numbers, a decimal library can be used instead by passing an object
+ * with the same methods as `host.decimal`
+ */
+export const numberDecimal = {
+    parse: text => { const number = Number(text); return Number.isNaN(number) ? null : number; },
+    fromInteger: integer => Number(integer),
+    toNumber: float => float,
+    toString: float => formatNumber(float),
+    neg: float => -float,
+    add: (left, right) => left + right,
+    sub: (left, right) => left - right,
+    mul: (left, right) => left * right,
+    div: (left, right) => left / right,
+    rem: (left, right) => left % right,
+    pow: (left, right) => left ** right,
+    /** -1, 0, 1 or NaN if they can't be compared */
+    compare: (left, right) => left < right ? -1 : left > right ? 1 : left === right ? 0 : NaN,
+};
+
+const encoder = new TextEncoder();
+const decoder = new TextDecoder();
+
+/** Like Rust prints an f64: never with an exponent */
+function formatNumber(number) {
+    if (Number.isNaN(number)) return "NaN";
+    if (!Number.isFinite(number)) return number < 0 ? "-inf" : "inf";
+    if (Object.is(number, -0)) return "-0";
+    const string = String(number);
+    if (!string.includes("e")) return string;
+
+    const [mantissa, exponentText] = string.split("e");
+    const negative = mantissa.startsWith("-");
+    const [whole, fraction = ""] = mantissa.replace("-", "").split(".");
+    const digits = whole + fraction;
+    const point = whole.length + Number(exponentText);
+    const plain = point <= 0
+        ? "0." + "0".repeat(-point) + digits
+        : point >= digits.length
+            ? digits + "0".repeat(point - digits.length)
+            : digits.slice(0, point) + "." + digits.slice(point);
+    return (negative ? "-" : "") + plain;
+}
+
+/** Compares like Rust strings, by code points */
+function compareText(left, right) {
+    const a = [...left], b = [...right];
+    for (let i = 0; i < Math.min(a.length, b.length); i++) {
+        const difference = a[i].codePointAt(0) - b[i].codePointAt(0);
+        if (difference) return Math.sign(difference);
+    }
+    return Math.sign(a.length - b.length);
+}
+
+function bytesNumber(text) {
+    let number = 0n;
+    for (const byte of encoder.encode(text)) number = number << 8n | BigInt(byte);
+    return number;
+}
+
+const reverse = text => [...text].reverse().join("");
+
+/** Like Rust's `{:?}` of a str */
+function debugText(text) {
+    const escapes = { '"': '\\"', "\\": "\\\\", "\n": "\\n", "\r": "\\r", "\t": "\\t", "\0": "\\0" };
+    let debug = '"';
+    for (const char of text) {
+        const code = char.codePointAt(0);
+        if (char in escapes) debug += escapes[char];
+        else if (code < 0x20 || code === 0x7f) debug += `\\u{${code.toString(16)}}`;
+        else debug += char;
+    }
+    return debug + '"';
+}
+
+/** Like Rust's `{:?}` of a Duration */
+function debugDuration(nanos) {
+    nanos = BigInt(nanos);
+    const [scale, digits, unit] = nanos >= 1000000000n ? [1000000000n, 9, "s"]
+        : nanos >= 1000000n ? [1000000n, 6, "ms"]
+        : nanos >= 1000n ? [1000n, 3, "µs"]
+        : [1n, 0, "ns"];
+    const fraction = nanos % scale;
+    const decimals = fraction ? "." + String(fraction).padStart(digits, "0").replace(/0+$/, "") : "";
+    return `${nanos / scale}${decimals}${unit}`;
+}
+
+/** Like Rust's `Duration::as_secs_f64` */
+const seconds = nanos => Number(nanos / 1000000000n) + Number(nanos % 1000000000n) / 1e9;
+
+const now = () => Math.round((globalThis.performance ?? Date).now() * 1e6);
+
+/** Nanoseconds since the Unix epoch of a time read with `now()` */
+const wallClock = started => BigInt(Date.now()) * 1000000n + BigInt(started - now());
+
+/** Year, month and day (like `/2024-02-29/`) and the full UTC time of nanoseconds since the Unix epoch */
+function wallDate(nanos) {
+    const millis = nanos / 1000000n - (nanos % 1000000n < 0n ? 1n : 0n);
+    const iso = new Date(Number(millis)).toISOString();
+    return [iso.slice(0, 10), iso.slice(0, 19) + "Z"];
+}
+
+/** `Duration::try_from_secs_f64`, null if the seconds are negative or too many */
+function durationOf(seconds) {
+    if (!(seconds >= 0) || seconds >= 18446744073709551616) return NULL_VALUE;
+    const nanos = BigInt(Math.round(seconds * 1e9));
+    return nanos > DURATION_MAX ? NULL_VALUE : duration(nanos);
+}
+
+function isTruthy(value, decimal) {
+    switch (value.type) {
+        case SYMBOL: case TIME: return true;
+        case DURATION: return value.nanos !== 0n;
+        case LIST: return value.items.length > 0;
+        case MAP: return value.entries.length > 0;
+        case BOOLEAN: return value.value;
+        case INTEGER: return value.value !== 0n;
+        case FLOAT: { const number = decimal.toNumber(value.value); return Number.isFinite(number) && number !== 0; }
+        case TEXT: return value.value.length > 0;
+        default: return false;
+    }
+}
+
+function isText(value) {
+    return value.type === TEXT || value.type === SYMBOL;
+}
+
+export const symbol = value => ({ type: SYMBOL, value });
+export const boolean = value => ({ type: BOOLEAN, value });
+export const integer = value => ({ type: INTEGER, value });
+export const float = value => ({ type: FLOAT, value });
+export const text = value => ({ type: TEXT, value });
+/** `µ`, `started` is undefined until the time is assigned */
+export const time = started => ({ type: TIME, started });
+/** The time between two times, in nanoseconds */
+export const duration = nanos => nanos > DURATION_MAX ? NULL_VALUE : { type: DURATION, nanos };
+export const list = items => ({ type: LIST, items });
+export { NULL_VALUE };
+
+const repeat = (string, count) => {
+    if (count > 0x7fffffff || string.length * count > 0x1fffffe8) throw new SpeckyError("Text is too large");
+    return string.repeat(count);
+};
+
+const repeatItems = (items, count) => {
+    if (items.length === 0) return [];
+    if (count > 0xffffffff || items.length * count > 0xffffffff) throw new SpeckyError("List is too large");
+    return Array.from({ length: items.length * count }, (_, index) => items[index % items.length]);
+};
+
+/** `list_index`: non-negative integers and floats rounded down, `undefined` for anything else */
+function listIndex(value, decimal) {
+    if (value.type === INTEGER) return value.value >= 0n ? value.value : undefined;
+    if (value.type !== FLOAT) return undefined;
+    const index = Math.floor(decimal.toNumber(value.value));
+    return index >= 0 && Number.isFinite(index) ? BigInt(index) : undefined;
+}
+
+export const Operator = {
+    Index: 0, SetIndex: 1, And: 2, Or: 3, Xor: 4, BitAnd: 5, BitOr: 6, BitXor: 7, BitNot: 8, ShiftLeft: 9, ShiftRight: 10,
+    Plus: 11, Minus: 12, Times: 13, Divide: 14, Modulo: 15, PPercent: 16, Exponential: 17, Unequal: 18, Equal: 19,
+    LessThan: 20, LessThanOrEqual: 21, GreaterThan: 22, GreaterThanOrEqual: 23, Property: 24,
+};
+
+/** Memory, pointer and host of a program, each method does what the statement of the same name does */
+export class Runtime {
+    constructor(host = {}) {
+        this.decimal = host.decimal ?? numberDecimal;
+        this.write = host.write ?? (globalThis.process?.stdout ? text => process.stdout.write(text) : text => console.log(text));
+        /** Returns the next line (or a promise of it), `null` or `undefined` reads as an empty line */
+        this.readLine = host.input ?? (() => "");
+        this.memory = new Map();
+        this.pointer = NULL_VALUE;
+        /** The pointers saved by `|>`, the last one is `@` */
+        this.savedPointers = [];
+        /** The calls that haven't returned yet, `saved` is the memory before a scoped call */
+        this.calls = [];
+    }
+
+    key(value) {
+        switch (value.type) {
+            case SYMBOL: return "y" + value.value;
+            case BOOLEAN: return "b" + value.value;
+            case INTEGER: return "i" + value.value;
+            case FLOAT: return "f" + this.decimal.toString(value.value);
+            case TEXT: return "t" + value.value;
+            case TIME: return "m" + value.started;
+            case DURATION: return "d" + value.nanos;
+            case LIST: return "l" + JSON.stringify(value.items.map(item => this.key(item)));
+            case MAP: return "p" + JSON.stringify(value.entries.map(([key, item]) => [this.key(key), this.key(item)]));
+            default: return "n";
+        }
+    }
+
+    get(key) {
+        return this.memory.get(this.key(key))?.[1] ?? NULL_VALUE;
+    }
+
+    store(key, value) {
+        const id = this.key(key);
+        const existing = this.memory.get(id);
+        if (existing) existing[1] = value;
+        else this.memory.set(id, [key, value]);
+    }
+
+    /** `§` expressions, following the values in memory `reader` times */
+    read(value, reader) {
+        if (reader === 0) return value;
+        const chain = [value];
+        let current = value;
+        for (let i = 0; i < reader; i++) {
+            current = this.get(current);
+            // a loop, where it ends is known without going around it
+            const key = this.key(current);
+            const index = chain.findIndex(value => this.key(value) === key && !(value.type === FLOAT && Number.isNaN(this.decimal.toNumber(value.value))));
+            if (index >= 0) return chain[(reader + i + index + 1) % (chain.length - index) + index];
+            chain.push(current);
+        }
+        return current;
+    }
+
+    typeName(value) {
+        if (value.type === INTEGER) return value.value < SMALL_MIN || value.value > SMALL_MAX ? "Integer" : "SmallInt";
+        return ["Symbol", "Boolean", "", "Float", "Text", "Time", "Duration", "List", "Map", "Null"][value.type];
+    }
+
+    /** `value_to_string`, `pretty` is the `$` form */
+    toString(value, pretty) {
+        switch (value.type) {
+            case SYMBOL: return pretty ? String(bytesNumber(value.value)) : value.value;
+            case BOOLEAN: return pretty ? (value.value ? "1" : "0") : String(value.value);
+            case INTEGER: {
+                if (!pretty) return String(value.value);
+                const code = value.value;
+                const valid = code >= 0n && code <= 0x10ffffn && (code < 0xd800n || code > 0xdfffn);
+                return String.fromCodePoint(valid ? Number(code) : 0xfffd);
+            }
+            case FLOAT: return pretty ? formatNumber(this.decimal.toNumber(value.value)) : this.decimal.toString(value.value);
+            case TEXT: return pretty ? value.value : `/${value.value.replaceAll("/", "\\/")}/`;
+            case TIME: {
+                const nanos = value.started === undefined ? 0 : Math.max(0, now() - value.started);
+                return pretty ? formatNumber(nanos / 1e9) : debugDuration(nanos);
+            }
+            case DURATION: return pretty ? formatNumber(seconds(value.nanos)) : debugDuration(value.nanos);
+            case LIST: {
+                const items = value.items.map(item => this.toString(item, pretty));
+                return pretty ? items.join("") : `[${items.join(" ")}]`;
+            }
+            case MAP: {
+                if (pretty) return value.entries.map(([, item]) => this.toString(item, true)).join("");
+                return `{${value.entries.map(([key, item]) => `${this.toString(key, false)} => ${this.toString(item, false)}`).join(" ")}}`;
+            }
+            default: return pretty ? "\0" : "null";
+        }
+    }
+
+    debug(value) {
+        switch (value.type) {
+            case SYMBOL: return `Symbol(${debugText(value.value)})`;
+            case BOOLEAN: return `Boolean(${value.value})`;
+            case INTEGER: return `${this.typeName(value)}(${value.value})`;
+            case FLOAT: return `Float(${this.decimal.toString(value.value)})`;
+            case TEXT: return `Text(${debugText(value.value)})`;
+            case TIME: return value.started === undefined ? "Time(None)"
+                : `Time(Some(Instant { tv_sec: ${Math.floor(value.started / 1e9)}, tv_nsec: ${value.started % 1e9} }))`;
+            case DURATION: return `Duration(${debugDuration(value.nanos)})`;
+            case LIST: return `List([${value.items.map(item => this.debug(item)).join(", ")}])`;
+            case MAP: return `Map({${value.entries.map(([key, item]) => `${this.debug(key)}: ${this.debug(item)}`).join(", ")}})`;
+            default: return "Null";
+        }
+    }
+
+    /** Like `PartialOrd` of `ast::Value`, incomparable floats are equal */
+    compare(left, right) {
+        const order = value => value.type === INTEGER ? (this.typeName(value) === "Integer" ? 2 : 2.5) : value.type;
+        if (order(left) !== order(right)) return order(left) < order(right) ? -1 : 1;
+        switch (left.type) {
+            case SYMBOL: case TEXT: return compareText(left.value, right.value);
+            case BOOLEAN: return Number(left.value) - Number(right.value);
+            case INTEGER: return left.value < right.value ? -1 : left.value > right.value ? 1 : 0;
+            case FLOAT: return this.decimal.compare(left.value, right.value) || 0;
+            case TIME: return (left.started ?? -1) - (right.started ?? -1);
+            case DURATION: return left.nanos < right.nanos ? -1 : left.nanos > right.nanos ? 1 : 0;
+            case LIST: {
+                for (let i = 0; i < left.items.length && i < right.items.length; i++) {
+                    const order = this.compare(left.items[i], right.items[i]);
+                    if (order !== 0) return order;
+                }
+                return left.items.length - right.items.length;
+            }
+            case MAP: {
+                for (let i = 0; i < left.entries.length && i < right.entries.length; i++) {
+                    const order = this.compare(left.entries[i][0], right.entries[i][0]) || this.compare(left.entries[i][1], right.entries[i][1]);
+                    if (order !== 0) return order;
+                }
+                return left.entries.length - right.entries.length;
+            }
+            default: return 0;
+        }
+    }
+
+    /** `ast::Map`, entries are sorted by key and later ones replace earlier ones with the same key */
+    map(entries) {
+        let result = { type: MAP, entries: [] };
+        for (const [key, item] of entries) result = this.insert(result, key, item);
+        return result;
+    }
+
+    /** A copy of `map` with `key` set to `item`, or removed if it's null */
+    insert(map, key, item) {
+        const id = this.key(key);
+        const entries = map.entries.filter(([existing]) => this.key(existing) !== id);
+        if (item.type !== NULL) {
+            const index = entries.findIndex(([existing]) => this.compare(existing, key) > 0);
+            entries.splice(index < 0 ? entries.length : index, 0, [key, item]);
+        }
+        return { type: MAP, entries };
+    }
+
+    /** `compare_lists`: by the first different element, then by length, `undefined` if two elements can't be compared */
+    compareLists(left, right) {
+        const d = this.decimal;
+        for (let i = 0; i < left.length && i < right.length; i++) {
+            let [l, r] = [left[i], right[i]];
+            if (l.type === INTEGER && r.type === FLOAT) l = float(d.fromInteger(l.value));
+            if (l.type === FLOAT && r.type === INTEGER) r = float(d.fromInteger(r.value));
+            let order;
+            if (l.type === LIST && r.type === LIST) order = this.compareLists(l.items, r.items);
+            else if (isText(l) && isText(r)) order = compareText(l.value, r.value);
+            else if (l.type === FLOAT && r.type === FLOAT) order = d.compare(l.value, r.value);
+            else if (l.type === TIME && r.type === TIME && l.started !== undefined && r.started !== undefined) order = this.compare(l, r);
+            else if (l.type === r.type && (l.type === INTEGER || l.type === DURATION)) order = this.compare(l, r);
+            if (order === undefined || Number.isNaN(order)) return undefined;
+            if (order !== 0) return order;
+        }
+        return left.length - right.length;
+    }
+
+    memoryString(sort) {
+        const entries = [...this.memory.values()];
+        if (sort) entries.sort(([a], [b]) => this.compare(a, b));
+        return "{\n" + entries.map(([key, value]) => `\t${this.debug(key)} => ${this.debug(value)}\n`).join("") + "}";
+    }
+
+    /** `string_to_value`, for inputs and `{<...}` */
+    parse(string) {
+        string = string.trim();
+        if (!string) return NULL_VALUE;
+
+        if (/^[0-9.]+$/.test(string)) {
+            const dots = string.split(".").length - 1;
+            if (dots === 0) return integer(BigInt(string));
+            if (dots === 1) {
+                const parsed = this.decimal.parse(string);
+                if (parsed !== null) return float(parsed);
+            }
+        }
+
+        switch (string) {
+            case "true": case "on": case "yes": return boolean(true);
+            case "false": case "off": case "no": return boolean(false);
+            case "null": return NULL_VALUE;
+            case "µ": return time(now());
+        }
+        return /^[A-Za-z0-9_]+$/.test(string) ? symbol(string) : text(string);
+    }
+
+    /** `operate`, with the value at the pointer on the left */
+    apply(operator, left, right) {
+        const d = this.decimal;
+
+        // the complement only uses its value, which doesn't get converted to the kind of the value at the pointer
+        if (operator === Operator.BitNot) return right.type === INTEGER ? integer(~right.value) : NULL_VALUE;
+
+        // numbers of different kinds get converted to the bigger kind
+        if (left.type === INTEGER && right.type === FLOAT) left = float(d.fromInteger(left.value));
+        if (left.type === FLOAT && right.type === INTEGER) right = float(d.fromInteger(right.value));
+
+        if (isText(left) && isText(right)) {
+            const order = compareText(left.value, right.value);
+            switch (operator) {
+                case Operator.Minus: return integer(bytesNumber(left.value) - bytesNumber(right.value));
+                case Operator.Unequal: return boolean(order !== 0);
+                case Operator.Equal: return boolean(order === 0);
+                case Operator.LessThan: return boolean(order < 0);
+                case Operator.GreaterThan: return boolean(order > 0);
+                case Operator.LessThanOrEqual: return boolean(order <= 0);
+                case Operator.GreaterThanOrEqual: return boolean(order >= 0);
+            }
+        }
+
+        const integers = left.type === INTEGER && right.type === INTEGER;
+        const floats = left.type === FLOAT && right.type === FLOAT;
+        const times = left.type === TIME && right.type === TIME;
+        const durations = left.type === DURATION && right.type === DURATION;
+        const lists = left.type === LIST && right.type === LIST;
+        const maps = left.type === MAP && right.type === MAP;
+        const l = left.value, r = right.value;
+        const compared = integers ? (l < r ? -1 : l > r ? 1 : 0) : floats ? d.compare(l, r)
+            : times || durations ? this.compare(left, right) : lists ? this.compareLists(left.items, right.items) : undefined;
+
+        switch (operator) {
+            case Operator.Index: {
+                if (left.type === LIST) {
+                    const index = listIndex(right, d);
+                    return index !== undefined && index < left.items.length ? left.items[Number(index)] : NULL_VALUE;
+                }
+                if (left.type === MAP) {
+                    const id = this.key(right);
+                    return left.entries.find(([key]) => this.key(key) === id)?.[1] ?? NULL_VALUE;
+                }
+                if (left.type === DURATION && right.type === SYMBOL && r in UNITS) {
+                    const nanos = left.nanos;
+                    if (r === "s") return float(d.parse(`${nanos / 1000000000n}.${String(nanos % 1000000000n).padStart(9, "0")}`));
+                    return integer(nanos / UNITS[r]);
+                }
+                if (left.type === TIME && right.type === SYMBOL) {
+                    const nanos = wallClock(left.started);
+                    const unix = scale => nanos / scale - (nanos % scale < 0n ? 1n : 0n);
+                    switch (r) {
+                        case "unix": return integer(unix(1000000000n));
+                        case "unix_ms": return integer(unix(1000000n));
+                        case "date": return text(wallDate(nanos)[0]);
+                        case "datetime": return text(wallDate(nanos)[1]);
+                        default: return NULL_VALUE;
+                    }
+                }
+                if ((left.type === INTEGER || left.type === FLOAT) && right.type === SYMBOL && r in UNITS) {
+                    if (left.type === FLOAT) return durationOf(d.toNumber(l) * Number(UNITS[r]) / 1e9);
+                    return l < 0n ? NULL_VALUE : duration(l * UNITS[r]);
+                }
+                if (!isText(left)) return NULL_VALUE;
+                let index;
+                if (right.type === INTEGER) index = r;
+                else if (right.type === FLOAT) index = Math.floor(d.toNumber(r));
+                else return NULL_VALUE;
+                if (!(index >= 0)) return NULL_VALUE;
+                const char = [...l][Number(index)];
+                return char === undefined ? NULL_VALUE : text(char);
+            }
+            case Operator.Property: {
+                if (right.type !== SYMBOL) return NULL_VALUE;
+                if (left.type === LIST && r === "len") return integer(BigInt(left.items.length));
+                if (left.type === MAP && r === "len") return integer(BigInt(left.entries.length));
+                if (left.type === MAP && r === "keys") return list(left.entries.map(([key]) => key));
+                return NULL_VALUE;
+            }
+            case Operator.SetIndex: {
+                if (left.type === MAP && right.type === LIST && right.items.length === 2) return this.insert(left, ...right.items);
+                if (left.type !== LIST || right.type !== LIST || right.items.length !== 2) return NULL_VALUE;
+                const index = listIndex(right.items[0], d);
+                if (index === undefined || index >= left.items.length) return NULL_VALUE;
+                const items = [...left.items];
+                items[Number(index)] = right.items[1];
+                return list(items);
+            }
+            case Operator.And: return boolean(isTruthy(left, d) && isTruthy(right, d));
+            case Operator.Or: return boolean(isTruthy(left, d) || isTruthy(right, d));
+            case Operator.Xor: return boolean(isTruthy(left, d) !== isTruthy(right, d));
+            case Operator.BitAnd: return integers ? integer(l & r) : NULL_VALUE;
+            case Operator.BitOr: return integers ? integer(l | r) : NULL_VALUE;
+            case Operator.BitXor: return integers ? integer(l ^ r) : NULL_VALUE;
+            case Operator.ShiftLeft:
+            case Operator.ShiftRight: {
+                if (!integers) return NULL_VALUE;
+                const count = operator === Operator.ShiftLeft ? r : -r;
+                if (count > 0xffffffffn) throw new SpeckyError("Shift is too large");
+                return integer(l << count);
+            }
+            case Operator.Plus:
+                if (lists) return list([...left.items, ...right.items]);
+                if (left.type === LIST) return list([...left.items, right]);
+                if (left.type === MAP && right.type === MAP) return right.entries.reduce((map, [key, item]) => this.insert(map, key, item), left);
+                if (integers) return integer(l + r);
+                if (floats) return float(d.add(l, r));
+                if (left.type === SYMBOL && right.type === SYMBOL) return symbol(l + r);
+                if (isText(left) && (right.type === TEXT || right.type === INTEGER)) return text(l + String(r));
+                if (left.type === TIME && right.type === DURATION) return time(left.started + Number(right.nanos));
+                if (left.type === DURATION && right.type === TIME) return time(right.started + Number(left.nanos));
+                if (durations) return duration(left.nanos + right.nanos);
+                return NULL_VALUE;
+            case Operator.Minus:
+                if (integers) return integer(l - r);
+                if (floats) return float(d.sub(l, r));
+                if (times) return left.started >= right.started ? duration(BigInt(left.started - right.started)) : NULL_VALUE;
+                if (left.type === TIME && right.type === DURATION) return time(left.started - Number(right.nanos));
+                if (durations) return left.nanos >= right.nanos ? duration(left.nanos - right.nanos) : NULL_VALUE;
+                return NULL_VALUE;
+            case Operator.Times:
+                if (integers) return integer(l * r);
+                if (floats) return float(d.mul(l, r));
+                if (isText(left) && right.type === INTEGER) {
+                    return text(repeat(r < 0n ? reverse(l) : l, Number(r < 0n ? -r : r)));
+                }
+                if (isText(left) && right.type === FLOAT) {
+                    const number = d.toNumber(r);
+                    const count = Number.isNaN(number) ? Infinity : Math.abs(Math.trunc(number));
+                    const bytes = encoder.encode(l);
+                    const fraction = decoder.decode(bytes.slice(0, Math.round(Math.abs(number - Math.trunc(number)) * bytes.length)));
+                    return text(number < 0 ? reverse(fraction) + repeat(reverse(l), count) : repeat(l, count) + fraction);
+                }
+                if (left.type === DURATION && right.type === INTEGER) return r < 0n ? NULL_VALUE : duration(left.nanos * r);
+                if (left.type === DURATION && right.type === FLOAT) return durationOf(seconds(left.nanos) * d.toNumber(r));
+                if (left.type === LIST && right.type === INTEGER) {
+                    const items = r < 0n ? [...left.items].reverse() : left.items;
+                    return list(repeatItems(items, Number(r < 0n ? -r : r)));
+                }
+                return NULL_VALUE;
+            case Operator.Divide:
+                if (integers && r === 0n) throw new SpeckyError("Division by zero");
+                if (integers) return integer(l / r);
+                if (floats) return float(d.div(l, r));
+                if (left.type === DURATION && (durations ? right.nanos : r) === 0n) throw new SpeckyError("Division by zero");
+                if (durations) return float(d.div(d.fromInteger(left.nanos), d.fromInteger(right.nanos)));
+                if (left.type === DURATION && right.type === INTEGER) return r < 0n ? NULL_VALUE : duration(left.nanos / r);
+                if (left.type === DURATION && right.type === FLOAT) return durationOf(seconds(left.nanos) / d.toNumber(r));
+                return NULL_VALUE;
+            case Operator.Modulo:
+                if (integers && r === 0n) throw new SpeckyError("Division by zero");
+                if (integers) return integer(l % r);
+                if (floats) return float(d.rem(l, r));
+                if (durations && right.nanos === 0n) throw new SpeckyError("Division by zero");
+                if (durations) return duration(left.nanos % right.nanos);
+                return NULL_VALUE;
+            case Operator.PPercent:
+                if (integers && r === 0n) throw new SpeckyError("Division by zero");
+                if (integers) return integer((l % r + r) % r);
+                if (floats) return float(d.rem(d.add(d.rem(l, r), r), r));
+                return NULL_VALUE;
+            case Operator.Exponential:
+                if (integers && r < 0n) throw new SpeckyError("Integer raised to a negative exponent");
+                if (integers && r > 0xffffffffn) throw new SpeckyError("Exponent is too large");
+                if (integers) return integer(l ** r);
+                if (floats) return float(d.pow(l, r));
+                return NULL_VALUE;
+            case Operator.Unequal:
+            case Operator.Equal: {
+                const equal = left.type === BOOLEAN && right.type === BOOLEAN ? l === r
+                    : lists || maps ? this.key(left) === this.key(right) : compared === 0;
+                return boolean(operator === Operator.Equal ? equal : !equal);
+            }
+            case Operator.LessThan: return compared === undefined ? NULL_VALUE : boolean(compared < 0);
+            case Operator.GreaterThan: return compared === undefined ? NULL_VALUE : boolean(compared > 0);
+            case Operator.LessThanOrEqual: return compared === undefined ? NULL_VALUE : boolean(compared <= 0);
+            case Operator.GreaterThanOrEqual: return compared === undefined ? NULL_VALUE : boolean(compared >= 0);
+        }
+    }
+
+    pointed() {
+        return this.get(this.pointer);
+    }
+
+    load(value, reader) {
+        this.pointer = this.read(value, reader);
+    }
+
+    push(value, reader) {
+        const pointer = this.read(value, reader);
+        this.savedPointers.push(this.pointer);
+        this.pointer = pointer;
+    }
+
+    pop() {
+        if (this.savedPointers.length) this.pointer = this.savedPointers.pop();
+    }
+
+    exchange() {
+        const last = this.savedPointers.length - 1;
+        if (last < 0) return;
+        [this.pointer, this.savedPointers[last]] = [this.savedPointers[last], this.pointer];
+    }
+
+    savedPointer() {
+        return this.savedPointers.at(-1) ?? NULL_VALUE;
+    }
+
+    define(label, reader, index) {
+        this.store(this.read(label, reader), integer(BigInt(index + 1)));
+    }
+
+    /** The statement to continue from, `next` if the jump does nothing */
+    jump(label, reader, next) {
+        const address = this.get(this.read(label, reader));
+        if (address.type === INTEGER && address.value >= 0n && address.value < 1n << 64n) return Number(address.value);
+        return next;
+    }
+
+    /** `[>:] label` at `index`, the statement to continue from, `next` if the call does nothing */
+    call(label, reader, index, scoped, next) {
+        const address = this.jump(label, reader, undefined);
+        if (address === undefined) return next;
+        const saved = scoped ? new Map([...this.memory].map(([id, [key, value]]) => [id, [key, value]])) : undefined;
+        this.calls.push({ call: index, saved });
+        return address;
+    }
+
+    /** `[<:]`, the statement after the last call, `next` if there's no call to return from */
+    returnFromCall(next) {
+        const frame = this.calls.pop();
+        if (!frame) return next;
+        if (frame.saved) {
+            // everything but the value at the pointer goes back to how it was before the call
+            const id = this.key(this.pointer);
+            const result = this.memory.get(id);
+            this.memory = frame.saved;
+            if (result) this.memory.set(id, result);
+        }
+        return frame.call + 1;
+    }
+
+    assign(value, reader) {
+        value = this.read(value, reader);
+        this.store(this.pointer, value.type === TIME ? time(value.started ?? now()) : value);
+    }
+
+    overwrite(value, reader) {
+        const key = this.read(value, reader);
+        this.store(key.type === TIME ? time(key.started ?? now()) : key, this.pointer);
+    }
+
+    swap(value, reader) {
+        const key = this.read(value, reader);
+        const pointed = this.pointed();
+        this.store(this.pointer, this.get(key));
+        this.store(key, pointed);
+    }
+
+    operate(operator, value, reader) {
+        value = this.read(value, reader);
+        this.store(this.pointer, this.apply(operator, this.pointed(), value.type === TIME ? time(value.started ?? now()) : value));
+    }
+
+    truthy() {
+        return isTruthy(this.pointed(), this.decimal);
+    }
+
+    exists() {
+        return this.pointed().type !== NULL;
+    }
+
+    log({ kind, reader, pretty, sort, reverse: reversed, newline, space, vertical, assign }) {
+        let string = "";
+        switch (kind) {
+            case "value": string = this.toString(this.read(this.pointer, reader), pretty); break;
+            case "type": string = this.typeName(this.pointed()); break;
+            case "memory": string = this.memoryString(sort); break;
+        }
+        if (reversed) string = reverse(string);
+        string += " ".repeat(space) + (newline ? "\n" : "");
+        if (vertical) {
+            const lines = string.split("\n").map(line => line.replace(/\r$/, ""));
+            if (string.endsWith("\n") || string === "") lines.pop();
+            const max = Math.max(0, ...lines.map(line => encoder.encode(line).length));
+            const chars = lines.map(line => [...line]);
+            string = Array.from({ length: max }, (_, i) => chars.map(line => line[i] ?? " ").join("")).join("\n");
+        }
+
+        if (assign) this.store(this.pointer, this.parse(string));
+        else this.write(string);
+    }
+
+    /** `'`, waits for the host */
+    async input() {
+        const line = await this.readLine();
+        this.store(this.pointer, this.parse(line ?? ""));
+    }
+}
+
+/** Runs `run` with node's stdin and stdout, errors are printed like the interpreter's and exit with 1 */
+async function runMain(run) {
+    let lines;
+    const input = async () => {
+        // created on the first `'`, so that programs without input don't wait for stdin
+        lines ??= (await import("node:readline")).createInterface({ input: process.stdin })[Symbol.asyncIterator]();
+        return (await lines.next()).value;
+    };
+    try {
+        await run({ input });
+    } catch (error) {
+        if (!(error instanceof SpeckyError)) throw error;
+        process.stderr.write(`Error: ${error.message} (statement ${error.statement}, line ${error.line})\n`);
+        for (const { statement, line } of error.calls.slice(-10).reverse()) {
+            process.stderr.write(`    called from statement ${statement} (line ${line})\n`);
+        }
+        if (error.calls.length > 10) process.stderr.write(`    ... and ${error.calls.length - 10} more calls\n`);
+        process.exitCode = 1;
+    } finally {
+        await lines?.return();
+    }
+}
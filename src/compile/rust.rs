@@ -0,0 +1,137 @@
+use std::fmt::Write;
+
+use crate::{ast::{LogKind, LogMemory, LogValue, StatementKind, Statements, Value}, run::Operator};
+
+use super::{float_literals, take_float_literal, Constants};
+
+/// A Rust expression that creates the value of `expression`
+fn value(code: &str, expression: &crate::ast::Expression) -> String {
+    literal(&expression.value, &mut float_literals(code, expression))
+}
+
+/// A Rust expression that creates `value`, its floats are taken from `floats`
+fn literal(value: &Value, floats: &mut Vec<(bool, &str)>) -> String {
+    match value {
+        Value::Symbol(symbol) => format!("Value::Symbol(Symbol::intern({:?}))", symbol.as_str()),
+        Value::Boolean(boolean) => format!("Value::Boolean({boolean})"),
+        Value::Integer(integer) => format!("Value::Integer({:?}.parse().unwrap())", integer.to_string()),
+        Value::SmallInt(integer) => format!("Value::SmallInt({integer})"),
+        Value::Float(float) => {
+            let (negative, digits) = take_float_literal(floats, float);
+            format!("Value::Float({}{digits:?}.parse::<Float>().unwrap())", if negative { "-" } else { "" })
+        },
+        Value::Text(text) => format!("Value::Text({text:?}.to_string())"),
+        Value::Time(_) => "Value::Time(None)".to_string(),
+        Value::Duration(_) => unreachable!("durations have no literal"),
+        Value::List(list) => {
+            let values = list.iter().map(|value| literal(value, floats)).collect::<Vec<_>>();
+            format!("Value::List(vec![{}])", values.join(", "))
+        },
+        Value::Map(map) => {
+            let entries = map.iter()
+                .map(|(key, value)| format!("({}, {})", literal(key, floats), literal(value, floats)))
+                .collect::<Vec<_>>();
+            format!("Value::Map(Map::from_iter([{}]))", entries.join(", "))
+        },
+        Value::Null => "Value::Null".to_string(),
+    }
+}
+
+fn log(kind: &StatementKind) -> String {
+    let StatementKind::Log { kind, reverse, newline, space, vertical, assign } = kind else {
+        unreachable!("not a log statement")
+    };
+    let kind = match kind {
+        Some(LogKind::Value(LogValue { reader, pretty })) => format!("Some(LogKind::Value(LogValue {{ reader: {reader}, pretty: {pretty} }}))"),
+        Some(LogKind::Type) => "Some(LogKind::Type)".to_string(),
+        Some(LogKind::Memory(LogMemory { sort })) => format!("Some(LogKind::Memory(LogMemory {{ sort: {sort} }}))"),
+        None => "None".to_string(),
+    };
+    format!("StatementKind::Log {{ kind: {kind}, reverse: {reverse}, newline: {newline}, space: {space}, vertical: {vertical}, assign: {assign} }}")
+}
+
+/// Generates a Rust program that runs `statements` with [`Runtime`](crate::run::Runtime),
+/// so it has the same values and operations as the interpreter.
+/// It needs the `speckylang` library to build, e.g.
+/// `rustc -O out.rs --extern speckylang=target/release/libspeckylang.rlib -L target/release/deps`.
+/// The program takes `--clock` and `--max-steps` like the interpreter.
+pub fn transpile(code: &str, statements: &Statements, name: &str) -> String {
+    let constants = Constants::collect(statements);
+    let mut rust = String::new();
+
+    let _ = writeln!(rust, "// Generated by `speckylang compile --target rust` from {name}");
+    rust.push_str("// Build with `rustc -O <this file> --extern speckylang=target/release/libspeckylang.rlib -L target/release/deps`\n");
+    rust.push_str("#![allow(unused)]\n\n");
+    rust.push_str("use speckylang::{ast::{Float, LogKind, LogMemory, LogValue, Map, StatementKind, Symbol, Value}, run::{error::{print_error, CallSite, RuntimeError, RuntimeErrorKind}, Operator, Runtime}, CodeArea};\n\n");
+
+    // for error reports
+    let _ = writeln!(rust, "const SOURCE: &str = {code:?};");
+    let areas = statements.iter()
+        .map(|statement| format!("({}, {})", statement.area.0, statement.area.1))
+        .collect::<Vec<_>>();
+    let _ = writeln!(rust, "const AREAS: [(usize, usize); {}] = [{}];\n", areas.len(), areas.join(", "));
+
+    rust.push_str("fn main() {\n");
+    let _ = writeln!(rust, "    let constants: [Value; {}] = [", constants.expressions.len());
+    for expression in &constants.expressions {
+        let _ = writeln!(rust, "        {},", value(code, expression));
+    }
+    rust.push_str("    ];\n");
+    rust.push_str("    let mut rt = Runtime::from_args(std::env::args().skip(1)).unwrap_or_else(|error| {\n");
+    rust.push_str("        eprintln!(\"{error}\");\n");
+    rust.push_str("        std::process::exit(2)\n");
+    rust.push_str("    });\n");
+    rust.push_str("    let mut index = 0;\n\n");
+    let _ = writeln!(rust, "    while index < {} {{", statements.len());
+    rust.push_str("        let next: Result<usize, RuntimeErrorKind> = rt.step().and_then(|()| match index {\n");
+
+    for (index, statement) in statements.iter().enumerate() {
+        let next = index + 1;
+        let operand = |expression: &crate::ast::Expression| match expression.saved_pointer {
+            true => format!("&rt.saved_pointer(), {}", expression.reader),
+            false => format!("&constants[{}], {}", constants.index(&expression.value), expression.reader),
+        };
+        let arm = match &statement.kind {
+            StatementKind::Load(expression) => format!("{{ rt.load({}); Ok({next}) }}", operand(expression)),
+            StatementKind::Push(expression) => format!("{{ rt.push({}); Ok({next}) }}", operand(expression)),
+            StatementKind::Pop => format!("{{ rt.pop(); Ok({next}) }}"),
+            StatementKind::Exchange => format!("{{ rt.exchange(); Ok({next}) }}"),
+            StatementKind::Define(expression) => format!("{{ rt.define({}, {index}); Ok({next}) }}", operand(expression)),
+            StatementKind::Jump(expression) => format!("Ok(rt.jump({}).unwrap_or({next}))", operand(expression)),
+            StatementKind::Call { expression, scoped } => format!("Ok(rt.call({}, {index}, {scoped}).unwrap_or({next}))", operand(expression)),
+            StatementKind::Return => format!("Ok(rt.return_from_call().unwrap_or({next}))"),
+            StatementKind::Assign(expression) => format!("{{ rt.assign({}); Ok({next}) }}", operand(expression)),
+            StatementKind::Overwrite(expression) => format!("{{ rt.overwrite({}); Ok({next}) }}", operand(expression)),
+            StatementKind::Swap(expression) => format!("{{ rt.swap({}); Ok({next}) }}", operand(expression)),
+            StatementKind::Truthy(skip) => format!("Ok(if rt.truthy() {{ {next} }} else {{ {} }})", next + skip),
+            StatementKind::Falsy(skip) => format!("Ok(if rt.falsy() {{ {next} }} else {{ {} }})", next + skip),
+            StatementKind::Exists(skip) => format!("Ok(if rt.exists() {{ {next} }} else {{ {} }})", next + skip),
+            StatementKind::Empty(skip) => format!("Ok(if rt.empty() {{ {next} }} else {{ {} }})", next + skip),
+            kind @ StatementKind::Log { .. } => format!("rt.log(&{}).map(|()| {next})", log(kind)),
+            StatementKind::Input => format!("rt.input().map(|()| {next})"),
+            kind => {
+                let (operator, expression) = Operator::of(kind).expect("every other statement is an operation");
+                format!("rt.operate(Operator::{operator:?}, {}).map(|()| {next})", operand(expression))
+            },
+        };
+        let _ = writeln!(rust, "            {index} => {arm},");
+    }
+
+    rust.push_str("            _ => unreachable!(),\n");
+    rust.push_str("        });\n\n");
+    rust.push_str("        match next {\n");
+    rust.push_str("            Ok(next) => index = next,\n");
+    rust.push_str("            Err(kind) => {\n");
+    rust.push_str("                rt.flush();\n");
+    rust.push_str("                let area = |index: usize| CodeArea(AREAS[index].0, AREAS[index].1);\n");
+    rust.push_str("                let stack = rt.call_stack().map(|index| CallSite { statement_index: index, area: area(index) }).collect();\n");
+    rust.push_str("                print_error(SOURCE, &RuntimeError { kind, statement_index: index, area: area(index), stack });\n");
+    rust.push_str("                std::process::exit(1)\n");
+    rust.push_str("            },\n");
+    rust.push_str("        }\n");
+    rust.push_str("    }\n\n");
+    rust.push_str("    rt.flush();\n");
+    rust.push_str("}\n");
+
+    rust
+}
@@ -0,0 +1,222 @@
+use std::io::{self, Write};
+
+use colored::Colorize;
+use speckylang::{ast::{self, StatementKind, Value}, run, Interpreter, Parser, Status};
+
+use crate::RunArgs;
+
+const HELP: &str = "\
+step [n]        (s) executes the next n statements (default 1)
+continue        (c) runs until a breakpoint, a watchpoint or the end
+break <line>    (b) stops before the first statement of a line
+break <label>   (b) stops at the address defined by `[<] label`
+watch <value>   (w) stops after the value at this key changes, writing the same value again doesn't stop
+delete          (d) removes all breakpoints and watchpoints
+info            (i) lists breakpoints and watchpoints
+where           (l) shows the next statement
+stack               lists the calls that haven't returned, innermost first
+ptr                 prints the current pointer and the ones saved by `|>`
+print <expr>    (p) prints an expression, e.g. `p §§x`
+mem                 prints the memory (sorted)
+help                prints this message
+quit            (q) exits the debugger";
+
+struct Debugger<'a> {
+    code: &'a str,
+    interpreter: Interpreter,
+    // description and statement index
+    breakpoints: Vec<(String, usize)>,
+    // memory key and last seen value
+    watchpoints: Vec<(Value, Value)>,
+}
+
+impl<'a> Debugger<'a> {
+    fn line_of(&self, statement_index: usize) -> usize {
+        self.interpreter.program()[statement_index].area.line(self.code)
+    }
+
+    fn print_location(&self) {
+        let index = self.interpreter.program_counter();
+        match self.interpreter.program().get(index) {
+            Some(statement) => println!(
+                "{} line {}, statement {index}: {}",
+                "->".bright_yellow(),
+                self.line_of(index),
+                &self.code[statement.area.0..statement.area.1],
+            ),
+            None => println!("{} end of program", "->".bright_yellow()),
+        }
+    }
+
+    fn add_breakpoint(&mut self, target: &str) {
+        let program = self.interpreter.program();
+
+        let found = match target.parse::<usize>() {
+            Ok(line) => (0..program.len())
+                .find(|&index| self.line_of(index) >= line)
+                .map(|index| (format!("line {line}"), index)),
+            // `[<] label` stores the index of the statement after it
+            Err(_) => program.iter()
+                .position(|statement| matches!(
+                    &statement.kind,
+                    StatementKind::Define(ast::Expression { reader: 0, value: Value::Symbol(label), .. }) if label.as_str() == target
+                ))
+                .map(|index| (format!("label {target}"), index + 1)),
+        };
+
+        match found {
+            Some((description, index)) => {
+                println!("breakpoint at {description} (statement {index})");
+                self.breakpoints.push((description, index));
+            },
+            None => println!("no statement found for '{target}'"),
+        }
+    }
+
+    fn add_watchpoint(&mut self, key: &str) {
+        match Parser::new(key).parse_value() {
+            Ok(key) => {
+                let value = self.interpreter.memory().get(&key).cloned().unwrap_or_default();
+                println!("watching {}", run::value_to_string(&key, false));
+                self.watchpoints.push((key, value));
+            },
+            Err(error) => speckylang::parser::error::print_error(key, error),
+        }
+    }
+
+    /// Executes one statement, returns whether execution should stop
+    fn step(&mut self) -> bool {
+        let status = match self.interpreter.step() {
+            Ok(status) => status,
+            Err(error) => {
+                run::error::print_error(self.code, &error);
+                return true
+            },
+        };
+
+        // also after the last statement, which can change memory too
+        let mut stop = false;
+
+        for (key, last) in &mut self.watchpoints {
+            let current = self.interpreter.memory().get(key).unwrap_or(&Value::Null);
+            if current != last {
+                println!(
+                    "{} {}: {} => {}",
+                    "watchpoint".bright_cyan(),
+                    run::value_to_string(key, false),
+                    run::value_to_string(last, false),
+                    run::value_to_string(current, false),
+                );
+                *last = current.clone();
+                stop = true;
+            }
+        }
+
+        match status {
+            Status::Running => {},
+            Status::Halted => {
+                println!("program halted");
+                return true
+            },
+            Status::WaitingForInput => return true,
+        }
+
+        let index = self.interpreter.program_counter();
+        if let Some((description, _)) = self.breakpoints.iter().find(|(_, breakpoint)| *breakpoint == index) {
+            println!("{} at {description}", "breakpoint".bright_red());
+            stop = true;
+        }
+
+        stop
+    }
+
+    fn command(&mut self, line: &str) -> bool {
+        let (name, argument) = line.split_once(' ').unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        match name {
+            "step"|"s" => {
+                for _ in 0..argument.parse().unwrap_or(1) {
+                    if self.step() { break }
+                }
+                self.interpreter.flush();
+                self.print_location();
+            },
+            "continue"|"c" => {
+                while !self.step() {}
+                self.interpreter.flush();
+                self.print_location();
+            },
+            "break"|"b" if !argument.is_empty() => self.add_breakpoint(argument),
+            "watch"|"w" if !argument.is_empty() => self.add_watchpoint(argument),
+            "delete"|"d" => {
+                self.breakpoints.clear();
+                self.watchpoints.clear();
+            },
+            "info"|"i" => {
+                for (description, index) in &self.breakpoints {
+                    println!("breakpoint at {description} (statement {index})");
+                }
+                for (key, _) in &self.watchpoints {
+                    println!("watchpoint on {}", run::value_to_string(key, false));
+                }
+            },
+            "where"|"l" => self.print_location(),
+            "stack" => {
+                let calls = self.interpreter.call_stack().collect::<Vec<_>>();
+                for &index in calls.iter().rev() {
+                    println!("called from statement {index} (line {})", self.line_of(index));
+                }
+            },
+            "ptr" => {
+                println!("{}", run::value_to_string_at(self.interpreter.pointer(), false, self.interpreter.now()));
+                for pointer in self.interpreter.saved_pointers().iter().rev() {
+                    println!("saved {}", run::value_to_string_at(pointer, false, self.interpreter.now()));
+                }
+            },
+            "print"|"p" => match Parser::new(argument).parse_expression() {
+                Ok(expression) => {
+                    let value = self.interpreter.evaluate(&expression);
+                    println!("{}", run::value_to_string_at(value, false, self.interpreter.now()));
+                },
+                Err(error) => speckylang::parser::error::print_error(argument, error),
+            },
+            "mem" => println!("{}", run::memory_to_string(self.interpreter.memory(), true)),
+            "help" => println!("{HELP}"),
+            "quit"|"q" => return false,
+            _ => println!("unknown command '{line}' (try help)"),
+        }
+
+        true
+    }
+}
+
+pub fn debug(code: &str, parsed: ast::Statements, args: &RunArgs) {
+    let mut debugger = Debugger {
+        code,
+        interpreter: args.interpreter(parsed),
+        breakpoints: vec![],
+        watchpoints: vec![],
+    };
+
+    println!("{} statements loaded (type help for commands)", debugger.interpreter.program().len());
+    debugger.print_location();
+
+    let stdin = io::stdin();
+
+    loop {
+        print!("(debug) ");
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0)|Err(_) => break,
+            Ok(_) => {},
+        }
+
+        let line = line.trim();
+        if !line.is_empty() && !debugger.command(line) {
+            break
+        }
+    }
+}
@@ -0,0 +1,288 @@
+use std::ops::Range;
+
+use logos::Logos;
+
+use crate::{ast::{Statement, StatementKind}, parser::{ParseResult, error::{CodeArea, ParsingError}}, token::{self, Piece, Token}};
+
+const INDENT: &str = "    ";
+
+/// Statements that share a line in the output
+#[derive(Default)]
+struct Line<'a> {
+    statements: Vec<usize>,
+    comment: Option<&'a str>,
+    depth: usize,
+}
+
+enum Entry<'a> {
+    Line(Line<'a>),
+    Comment(&'a str),
+    Blank,
+}
+
+/// Whether two tokens can be written without a space and still get lexed the same way
+fn glues(left: &str, left_token: Token, right: &str, right_token: Token) -> bool {
+    let joined = format!("{left}{right}");
+    let mut lexer = Token::lexer(&joined);
+    lexer.next() == Some(Ok(left_token))
+        && lexer.span().end == left.len()
+        && lexer.next() == Some(Ok(right_token))
+        && lexer.next().is_none()
+}
+
+/// Whether two tokens of an expression are consecutive values of a list or map, which get a space
+/// like `[1 /a/ [2]]`, and so does the `=>` of the entries of maps (`{a => 1}`)
+fn separates_values(last: Token, token: Token) -> bool {
+    use Token::*;
+    matches!(last, Overwrite) || matches!(token, Overwrite)
+        || matches!(last, Symbol|Null|True|False|Mu|StringLiteral|IntegerLiteral|FloatLiteral|SquareBracketClose|CurlyBracketClose)
+        && matches!(token, Symbol|Null|True|False|Mu|StringLiteral|IntegerLiteral|FloatLiteral|SquareBracketOpen|CurlyBracketOpen|Minus|Plus)
+}
+
+/// Operators and their options are written together (`{%\}`, `[<]`), the expression goes after a space.
+/// Conditions keep their groups (`?? ????`), since they are often used to show what each part skips.
+fn statement_text(code: &str, statement: &Statement, tokens: &[(Token, Range<usize>)]) -> String {
+    let expression_start = statement.kind.expression().map(|expression| expression.area.0);
+    let condition = statement.kind.condition_length() > 0;
+    let mut text = String::new();
+
+    for (index, (token, span)) in tokens.iter().enumerate() {
+        if let Some((last, last_span)) = index.checked_sub(1).map(|last| &tokens[last]) {
+            let space = match condition {
+                true => last_span.end < span.start,
+                false => Some(span.start) == expression_start
+                    || expression_start.is_some_and(|start| span.start > start) && separates_values(*last, *token)
+                    || !glues(&code[last_span.clone()], *last, &code[span.clone()], *token),
+            };
+            if space {
+                text.push(' ');
+            }
+        }
+        text.push_str(&code[span.clone()]);
+    }
+
+    text
+}
+
+/// A load (or push) on its own line followed by an operation on the new pointer, e.g. `|< a` and `<= 50` on the next line
+fn continues_load(statements: &[Statement], line: &Line, next: &StatementKind) -> bool {
+    matches!(line.statements[..], [index] if matches!(statements[index].kind, StatementKind::Load(_)|StatementKind::Push(_)))
+        && next.expression().is_some()
+        && !matches!(next, StatementKind::Load(_)|StatementKind::Push(_)|StatementKind::Define(_)|StatementKind::Jump(_)|StatementKind::Call { .. })
+}
+
+/// A loop from the line ending with `[<] label` to the line jumping back to it
+struct Block {
+    start: usize,
+    end: usize,
+    /// The jump is at the end of a line of the body (`|< i + 1 [>] loop`),
+    /// instead of being alone after the body
+    end_inside: bool,
+}
+
+/// Finds the loops, a `[>]` line that jumps to another label (like a function return)
+/// closes the innermost loop
+fn loop_blocks(code: &str, statements: &[Statement], lines: &[&mut Line]) -> Vec<Block> {
+    let label = |index: usize| match &statements[index].kind {
+        StatementKind::Define(expression) => Some((true, &code[expression.area.0..expression.area.1])),
+        StatementKind::Jump(expression) => Some((false, &code[expression.area.0..expression.area.1])),
+        _ => None,
+    };
+
+    let mut blocks = vec![];
+    let mut open: Vec<(usize, &str)> = vec![];
+
+    for (index, line) in lines.iter().enumerate() {
+        let alone = line.statements.len() == 1;
+        let last = *line.statements.last().unwrap();
+
+        match label(last) {
+            Some((true, name)) => open.push((index, name)),
+            Some((false, name)) => {
+                let start = open.iter().rposition(|&(_, open)| open == name);
+                let start = match alone {
+                    true => start.or(open.len().checked_sub(1)),
+                    // jumps in the middle of the loop (`continue`s) don't close it
+                    false => start.filter(|_| !lines[index + 1..].iter()
+                        .map(|line| label(*line.statements.last().unwrap()))
+                        .take_while(|label| *label != Some((true, name)))
+                        .any(|label| label == Some((false, name)))
+                    ),
+                };
+                if let Some(start) = start {
+                    blocks.push(Block { start: open[start].0, end: index, end_inside: !alone });
+                    open.truncate(start);
+                }
+            },
+            None => {},
+        }
+    }
+
+    blocks
+}
+
+/// Formats a program in the canonical style:
+/// single spaces between statements, expressions after a space, comments kept,
+/// loop bodies and the lines skipped by a condition indented one level deeper.
+pub fn format(code: &str) -> ParseResult<String> {
+    let statements = crate::parse(code)?;
+    let pieces = token::lossless(code);
+
+    // tokens of each statement, and the whitespace and comments before each statement (and after the last one)
+    let mut tokens = vec![vec![]; statements.len()];
+    let mut gaps = vec![vec![]; statements.len() + 1];
+    let mut index = 0;
+
+    for (piece, span) in pieces {
+        while index < statements.len() && span.start >= statements[index].area.1 {
+            index += 1;
+        }
+        let inside = index < statements.len() && span.start >= statements[index].area.0;
+        match piece {
+            Piece::Token(token) => tokens[index].push((token, span)),
+            Piece::Invalid => return Err(ParsingError::CustomError {
+                text: "Unexpected character".to_string(),
+                area: CodeArea::from_span(span),
+            }),
+            Piece::Whitespace if inside => {},
+            // comments inside of a statement get moved on their own line before it
+            Piece::Comment if inside => {
+                gaps[index].push((Piece::Whitespace, "\n"));
+                gaps[index].push((piece, &code[span]));
+            },
+            _ => gaps[index].push((piece, &code[span])),
+        }
+    }
+
+    let mut entries = vec![];
+    // index in `entries` of the line the last statement is on
+    let mut last_line = None;
+
+    for (index, gap) in gaps.iter().enumerate() {
+        let mut newlines = 0;
+        let mut comments = false;
+
+        for (piece, text) in gap {
+            match piece {
+                Piece::Whitespace => newlines += text.matches('\n').count(),
+                Piece::Comment => {
+                    let comment = text.trim_end();
+                    match last_line {
+                        Some(line) if newlines == 0 && !comments => {
+                            if let Entry::Line(line) = &mut entries[line] {
+                                line.comment = Some(comment);
+                            }
+                        },
+                        _ => {
+                            if newlines > 1 {
+                                entries.push(Entry::Blank);
+                            }
+                            entries.push(Entry::Comment(comment));
+                        },
+                    }
+                    comments = true;
+                    newlines = 0;
+                },
+                _ => {},
+            }
+        }
+
+        let Some(statement) = statements.get(index) else { break };
+
+        let same_line = match last_line.and_then(|line| match &entries[line] { Entry::Line(line) => Some(line), _ => None }) {
+            Some(_) if comments => false,
+            Some(line) => newlines == 0 || continues_load(&statements, line, &statement.kind),
+            None => false,
+        };
+
+        if same_line {
+            if let Some(Entry::Line(line)) = last_line.map(|line| &mut entries[line]) {
+                line.statements.push(index);
+            }
+        } else {
+            if newlines > 1 {
+                entries.push(Entry::Blank);
+            }
+            entries.push(Entry::Line(Line { statements: vec![index], ..Default::default() }));
+            last_line = Some(entries.len() - 1);
+        }
+    }
+
+    let lines = entries.iter_mut()
+        .filter_map(|entry| match entry { Entry::Line(line) => Some(line), _ => None })
+        .collect::<Vec<_>>();
+    let blocks = loop_blocks(code, &statements, &lines);
+
+    // depth of the lines inside of the open loops
+    let mut open = vec![];
+    // last statement skipped by each condition of the previous lines, and the depth of the skipped lines
+    let mut conditions: Vec<(usize, usize)> = vec![];
+
+    for (index, line) in lines.into_iter().enumerate() {
+        let first = line.statements[0];
+        conditions.retain(|&(last, _)| last >= first);
+
+        let body_depth = open.last().copied().unwrap_or(0);
+        let condition_depth = conditions.iter().map(|&(_, depth)| depth).max().unwrap_or(0);
+
+        line.depth = match blocks.iter().find(|block| block.end == index) {
+            // the jump back goes under the `[<] label`, even if a condition skips it
+            Some(Block { end_inside: false, .. }) => open.pop().map(|depth| depth - 1).unwrap_or(0),
+            Some(_) => {
+                open.pop();
+                body_depth.max(condition_depth)
+            },
+            None => body_depth.max(condition_depth),
+        };
+
+        if blocks.iter().any(|block| block.start == index) {
+            open.push(line.depth + 1);
+        }
+        for &index in &line.statements {
+            let length = statements[index].kind.condition_length();
+            if length > 0 {
+                conditions.push((index + length, line.depth + 1));
+            }
+        }
+    }
+
+    // comments are indented like the line after them
+    let mut depths = vec![0; entries.len()];
+    let mut depth = 0;
+    for (index, entry) in entries.iter().enumerate().rev() {
+        if let Entry::Line(line) = entry {
+            depth = line.depth;
+        }
+        depths[index] = depth;
+    }
+
+    let mut output = String::new();
+    let mut blank = false;
+
+    for (entry, depth) in entries.iter().zip(depths) {
+        match entry {
+            Entry::Blank => blank = !output.is_empty(),
+            Entry::Comment(comment) => {
+                if std::mem::take(&mut blank) { output.push('\n') }
+                output.push_str(&INDENT.repeat(depth));
+                output.push_str(comment);
+                output.push('\n');
+            },
+            Entry::Line(line) => {
+                if std::mem::take(&mut blank) { output.push('\n') }
+                output.push_str(&INDENT.repeat(depth));
+                let texts = line.statements.iter()
+                    .map(|&index| statement_text(code, &statements[index], &tokens[index]))
+                    .collect::<Vec<_>>();
+                output.push_str(&texts.join(" "));
+                if let Some(comment) = line.comment {
+                    output.push(' ');
+                    output.push_str(comment);
+                }
+                output.push('\n');
+            },
+        }
+    }
+
+    Ok(output)
+}
@@ -0,0 +1,172 @@
+//! Golden-output tests (`speckylang test`): every `.specky` file is run with
+//! scripted input and what it prints is compared with the expected output.
+//!
+//! Expectations come from files next to the program (`name.stdout`, `name.stdin`)
+//! or from comments at the top of it:
+//!
+//! ```specky
+//! # stdin: 5
+//! # stdout: 120
+//! # max-steps: 10000
+//! ```
+//!
+//! Every program needs an expected output, except if it says why it has none with `# unchecked: <reason>`.
+//!
+//! `µ` never moves (see [`Clock::Fixed`]) unless there's a `# clock:` comment like `# clock: step:1ms`,
+//! and running out of input reads empty lines like stdin does at its end.
+//! Programs that don't end need a `# max-steps:` comment, their output is compared until then,
+//! going over any other limit is an error.
+
+use std::{fs, io, path::{Path, PathBuf}, time::Duration};
+
+use crate::{run::{BufferIo, Clock, Limit, Limits, RuntimeError, RuntimeErrorKind}, Interpreter, Status};
+
+/// Steps for programs that don't set `# max-steps`, going over them is an error
+pub const DEFAULT_MAX_STEPS: u64 = 1_000_000;
+
+/// A program with its expectations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Case {
+    pub path: PathBuf,
+    pub code: String,
+    /// Lines read by the input statements
+    pub input: Vec<String>,
+    /// `None` if there's no `.stdout` file nor `# stdout:` comments yet
+    pub expected: Option<String>,
+    /// Why the program has no expected output, from `# unchecked:`
+    pub unchecked: Option<String>,
+    /// `max_steps` is only set by `# max-steps:`, the program is stopped after [`DEFAULT_MAX_STEPS`] otherwise
+    pub limits: Limits,
+    pub clock: Clock,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Passed,
+    /// The program printed something else, or there's nothing to compare it with
+    Failed { expected: Option<String>, actual: String },
+    /// The program ran, it has no expected output for this reason
+    Unchecked(String),
+    /// The program couldn't be parsed or stopped with an error
+    Error(String),
+}
+
+/// Every `.specky` file in `paths` and in their directories, sorted
+pub fn discover(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut pending = paths.to_vec();
+
+    while let Some(path) = pending.pop() {
+        if path.is_dir() {
+            for entry in fs::read_dir(&path)? {
+                pending.push(entry?.path());
+            }
+        } else if path.extension().is_some_and(|extension| extension == "specky") || paths.contains(&path) {
+            files.push(path);
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// `name.specky` becomes `name.<extension>`
+fn companion(path: &Path, extension: &str) -> PathBuf {
+    path.with_extension(extension)
+}
+
+impl Case {
+    /// Reads a program and its expectations
+    pub fn load(path: &Path) -> Result<Case, String> {
+        let code = fs::read_to_string(path).map_err(|error| format!("can't read {}: {error}", path.display()))?;
+        let mut case = Case {
+            path: path.to_path_buf(),
+            input: vec![],
+            expected: None,
+            unchecked: None,
+            limits: Limits::default(),
+            clock: Clock::Fixed,
+            code,
+        };
+
+        // the comments before the first statement
+        let mut expected = vec![];
+        for line in case.code.lines().map(str::trim).take_while(|line| line.is_empty() || line.starts_with('#')) {
+            let Some((key, value)) = line.trim_start_matches('#').trim_start().split_once(':') else {
+                continue
+            };
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match key {
+                "stdin" => case.input.push(value.to_string()),
+                "stdout" => expected.push(value.to_string() + "\n"),
+                "max-steps" => case.limits.max_steps = Some(value.parse().map_err(|_| format!("invalid max-steps: {value}"))?),
+                "timeout" => case.limits.timeout = Some(
+                    value.parse().ok().and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+                        .ok_or_else(|| format!("invalid timeout: {value}"))?
+                ),
+                "clock" => case.clock = value.parse()?,
+                "unchecked" => case.unchecked = Some(value.to_string()),
+                _ => {},
+            }
+        }
+        if !expected.is_empty() {
+            case.expected = Some(expected.concat());
+        }
+
+        if let Ok(input) = fs::read_to_string(companion(path, "stdin")) {
+            case.input = input.lines().map(str::to_string).collect();
+        }
+        if let Ok(stdout) = fs::read_to_string(companion(path, "stdout")) {
+            case.expected = Some(stdout);
+        }
+
+        Ok(case)
+    }
+
+    /// What the program prints, until it ends or runs the steps of its `# max-steps:`
+    pub fn run(&self) -> Result<String, String> {
+        let statements = crate::parse(&self.code).map_err(|error| format!("{error:?}"))?;
+        let mut interpreter = Interpreter::with_io(statements, BufferIo::with_input(self.input.iter().cloned()));
+        interpreter.set_limits(Limits { max_steps: self.limits.max_steps.or(Some(DEFAULT_MAX_STEPS)), ..self.limits });
+        interpreter.set_clock(self.clock);
+
+        loop {
+            match interpreter.run_to_end() {
+                Ok(Status::WaitingForInput) => interpreter.provide_input(""),
+                Ok(_) => return Ok(interpreter.stdout().to_string()),
+                Err(RuntimeError { kind: RuntimeErrorKind::LimitExceeded(Limit::Steps(_)), .. }) if self.limits.max_steps.is_some() => {
+                    return Ok(interpreter.stdout().to_string())
+                },
+                Err(error) => return Err(format!("{} (statement {})", error.kind, error.statement_index)),
+            }
+        }
+    }
+
+    pub fn check(&self) -> Outcome {
+        match self.run() {
+            Ok(actual) if self.expected.as_ref() == Some(&actual) => Outcome::Passed,
+            Ok(_) if self.expected.is_none() && self.unchecked.is_some() => Outcome::Unchecked(self.unchecked.clone().unwrap_or_default()),
+            Ok(actual) => Outcome::Failed { expected: self.expected.clone(), actual },
+            Err(error) => Outcome::Error(error),
+        }
+    }
+
+    /// Makes `actual` the expected output, in `name.stdout`
+    pub fn bless(&self, actual: &str) -> io::Result<()> {
+        fs::write(companion(&self.path, "stdout"), actual)
+    }
+}
+
+/// The first line where `expected` and `actual` differ, numbered from 1, with both versions of it
+pub fn first_difference<'a>(expected: &'a str, actual: &'a str) -> Option<(usize, Option<&'a str>, Option<&'a str>)> {
+    let (mut expected_lines, mut actual_lines) = (expected.split_inclusive('\n'), actual.split_inclusive('\n'));
+    let mut line = 0;
+    loop {
+        line += 1;
+        match (expected_lines.next(), actual_lines.next()) {
+            (None, None) => return None,
+            (expected, actual) if expected != actual => return Some((line, expected, actual)),
+            _ => {},
+        }
+    }
+}
@@ -0,0 +1,25 @@
+//! SpeckyLang as a library: parse programs with [`parse`] and execute them
+//! with [`run`] or step by step with an [`Interpreter`].
+
+pub mod ast;
+pub mod symbol;
+pub mod map;
+pub mod token;
+pub mod parser;
+pub mod run;
+pub mod fmt;
+pub mod lint;
+pub mod cfg;
+pub mod compile;
+pub mod golden;
+
+#[cfg(test)]
+mod test;
+
+pub use parser::{Parser, ParseResult, error::{CodeArea, ParsingError}};
+pub use run::{run, run_with_io, Interpreter, RunOutput, SpeckyIo, Status};
+
+/// Parses a whole program
+pub fn parse(code: &str) -> ParseResult<ast::Statements> {
+    Parser::new(code).parse_statements()
+}
@@ -0,0 +1,239 @@
+use std::fmt;
+
+use ahash::AHashMap;
+use ariadne::{Color, Report, ReportKind};
+use logos::Logos;
+
+use crate::{ast::{Statement, StatementKind, Statements, Value}, parser::error::{report_config, CodeArea}, token::{self, Piece, Token}};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Rule {
+    /// `[>] label` without any `[<] label` (or other write to `label`)
+    UndefinedLabel,
+    /// A condition that skips past the end of the program
+    ConditionPastEnd,
+    /// The same `[<] label` more than once, jumps go to the last executed one
+    DuplicateLabel,
+    /// More than one of `@`, `%`, `*` and `+` in a log statement, only the last one is used
+    ConflictingLogOptions,
+    /// Statements after an unconditional jump that no label leads to
+    Unreachable,
+}
+
+impl Rule {
+    pub const ALL: [Rule; 5] = [
+        Rule::UndefinedLabel,
+        Rule::ConditionPastEnd,
+        Rule::DuplicateLabel,
+        Rule::ConflictingLogOptions,
+        Rule::Unreachable,
+    ];
+
+    /// The name used in `# lint: allow(...)` comments
+    pub fn id(&self) -> &'static str {
+        match self {
+            Rule::UndefinedLabel => "undefined-label",
+            Rule::ConditionPastEnd => "condition-past-end",
+            Rule::DuplicateLabel => "duplicate-label",
+            Rule::ConflictingLogOptions => "conflicting-log-options",
+            Rule::Unreachable => "unreachable",
+        }
+    }
+
+    pub fn from_id(id: &str) -> Option<Rule> {
+        Rule::ALL.into_iter().find(|rule| rule.id() == id)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Warning {
+    pub rule: Rule,
+    pub message: String,
+    pub area: CodeArea,
+    /// Another place that explains the warning, like the first definition of a duplicate label
+    pub related: Option<(String, CodeArea)>,
+}
+
+/// Rules allowed by `# lint: allow(rule, ...)` comments for each line, a comment
+/// applies to its own line and the next one
+fn allowed_rules(code: &str) -> AHashMap<usize, Vec<Rule>> {
+    let mut allowed = AHashMap::<usize, Vec<Rule>>::new();
+
+    for (piece, span) in token::lossless(code) {
+        if piece != Piece::Comment { continue }
+
+        let comment = code[span.clone()].trim_start_matches('#').trim();
+        let Some(rules) = comment.strip_prefix("lint:")
+            .and_then(|rest| rest.trim().strip_prefix("allow("))
+            .and_then(|rest| rest.split_once(')'))
+            .map(|(rules, _)| rules)
+        else { continue };
+
+        let line = CodeArea::from_span(span).line(code);
+        for rule in rules.split(',').filter_map(|rule| Rule::from_id(rule.trim())) {
+            allowed.entry(line).or_default().push(rule);
+            allowed.entry(line + 1).or_default().push(rule);
+        }
+    }
+
+    allowed
+}
+
+/// Labels of `[<] label` statements with their index, and other literal values that can be written to
+pub(crate) fn labels(statements: &Statements) -> (Vec<(&Value, usize)>, Vec<&Value>) {
+    let mut defined = vec![];
+    let mut written = vec![];
+
+    for (index, statement) in statements.iter().enumerate() {
+        match &statement.kind {
+            StatementKind::Define(expression) => defined.extend(expression.literal().map(|value| (value, index))),
+            // `|< label <= 4` and `=> label` can set addresses too
+            StatementKind::Load(expression)|StatementKind::Push(expression)|StatementKind::Overwrite(expression)|StatementKind::Swap(expression) => {
+                written.extend(expression.literal())
+            },
+            _ => {},
+        }
+    }
+
+    (defined, written)
+}
+
+fn log_options(code: &str, statement: &Statement, warnings: &mut Vec<Warning>) {
+    let kinds = Token::lexer(&code[statement.area.0..statement.area.1])
+        .flatten()
+        .filter(|token| matches!(token, Token::At|Token::Percent|Token::Asterisk|Token::Plus))
+        .collect::<Vec<_>>();
+
+    if kinds.windows(2).any(|pair| pair[0] != pair[1]) {
+        warnings.push(Warning {
+            rule: Rule::ConflictingLogOptions,
+            message: "Only the last of `@`, `%`, `*` and `+` is used".to_string(),
+            area: statement.area,
+            related: None,
+        });
+    }
+}
+
+/// `[<] return [>] function` saves where a function returns to, these labels get redefined
+/// on every call, and the function usually moves them after the jump
+fn is_call(statements: &Statements, index: usize) -> bool {
+    matches!(statements[index].kind, StatementKind::Define(_))
+        && matches!(statements.get(index + 1).map(|statement| &statement.kind), Some(StatementKind::Jump(_)))
+}
+
+/// Finds common mistakes in a parsed program, `code` is used for the allow comments and the log options
+pub fn lint(code: &str, statements: &Statements) -> Vec<Warning> {
+    let mut warnings = vec![];
+    let (defined, written) = labels(statements);
+    let is_defined = |value: &Value| defined.iter().any(|(label, _)| *label == value);
+    // return addresses only exist while a function is called, before that the jump does nothing
+    let is_loop = |value: &Value| defined.iter().any(|&(label, index)| label == value && !is_call(statements, index));
+
+    // statements that a condition can skip
+    let mut conditional = vec![false; statements.len()];
+
+    for (index, statement) in statements.iter().enumerate() {
+        let length = statement.kind.condition_length();
+        if length > 0 {
+            if index + length >= statements.len() {
+                warnings.push(Warning {
+                    rule: Rule::ConditionPastEnd,
+                    message: format!("Skips {length} statements, but only {} follow", statements.len() - index - 1),
+                    area: statement.area,
+                    related: None,
+                });
+            }
+            for skipped in conditional.iter_mut().skip(index + 1).take(length) {
+                *skipped = true;
+            }
+        }
+
+        match &statement.kind {
+            StatementKind::Jump(expression) => match expression.literal() {
+                Some(label) if !is_defined(label) && !written.contains(&label) => warnings.push(Warning {
+                    rule: Rule::UndefinedLabel,
+                    message: format!("No `[<] {}` in the program, the jump does nothing", crate::run::value_to_string(label, false)),
+                    area: expression.area,
+                    related: None,
+                }),
+                // an unconditional jump skips everything until the next label
+                Some(label) if is_loop(label) && !conditional[index] && !(index > 0 && is_call(statements, index - 1)) => {
+                    let unreachable = statements[index + 1..].iter()
+                        .take_while(|statement| !matches!(statement.kind, StatementKind::Define(_)))
+                        .collect::<Vec<_>>();
+                    if let (Some(first), Some(last)) = (unreachable.first(), unreachable.last()) {
+                        warnings.push(Warning {
+                            rule: Rule::Unreachable,
+                            message: "Unreachable statements".to_string(),
+                            area: CodeArea(first.area.0, last.area.1),
+                            related: Some(("Always jumps from here".to_string(), statement.area)),
+                        });
+                    }
+                },
+                _ => {},
+            },
+            StatementKind::Call { expression, .. } => match expression.literal() {
+                Some(label) if !is_defined(label) && !written.contains(&label) => warnings.push(Warning {
+                    rule: Rule::UndefinedLabel,
+                    message: format!("No `[<] {}` in the program, the call does nothing", crate::run::value_to_string(label, false)),
+                    area: expression.area,
+                    related: None,
+                }),
+                _ => {},
+            },
+            StatementKind::Define(expression) if !is_call(statements, index) => {
+                let first = expression.literal()
+                    .and_then(|label| defined.iter().find(|&&(defined, first)| defined == label && !is_call(statements, first)))
+                    .filter(|&&(_, first)| first != index);
+                if let Some(&(_, first)) = first {
+                    warnings.push(Warning {
+                        rule: Rule::DuplicateLabel,
+                        message: "Label defined more than once".to_string(),
+                        area: expression.area,
+                        related: Some(("First defined here".to_string(), statements[first].area)),
+                    });
+                }
+            },
+            StatementKind::Log { .. } => log_options(code, statement, &mut warnings),
+            _ => {},
+        }
+    }
+
+    let allowed = allowed_rules(code);
+    warnings.retain(|warning| {
+        !allowed.get(&warning.area.line(code)).is_some_and(|rules| rules.contains(&warning.rule))
+    });
+
+    warnings
+}
+
+pub fn print_warning(code: &str, warning: &Warning) {
+    let mut report = Report::build(ReportKind::Warning, warning.area)
+        .with_config(report_config())
+        .with_code(warning.rule)
+        .with_message(&warning.message)
+        .with_label(
+            ariadne::Label::new(warning.area)
+                .with_message(&warning.message)
+                .with_color(Color::Yellow),
+        )
+        .with_help(format!("add `# lint: allow({})` to ignore it", warning.rule));
+
+    if let Some((message, area)) = &warning.related {
+        report = report.with_label(
+            ariadne::Label::new(*area)
+                .with_message(message)
+                .with_color(Color::Cyan),
+        );
+    }
+
+    report.finish()
+        .print(ariadne::sources([("code", code)]))
+        .expect("Failed to print warning report");
+}
@@ -1,31 +1,218 @@
 use std::{fs, path::PathBuf, time::{Duration, Instant}, process};
-use clap::Parser;
+use clap::{Parser, Subcommand};
+use speckylang::{ast, parser, run::{self, Clock, Engine, ErrorPolicy, Limits, RuntimeErrorKind}, Interpreter};
 
-mod ast;
-mod token;
-mod parser;
-mod run;
-
-#[cfg(test)]
-mod test;
+mod repl;
+mod debugger;
+mod profile;
 
 #[derive(Parser)]
-#[command(author, version, about, long_about = None)]
+#[command(author, version, about, long_about = None, args_conflicts_with_subcommands = true)]
 struct Args {
-    file: PathBuf,
+    #[command(subcommand)]
+    command: Option<Command>,
+    /// Program to run (starts the repl if missing)
+    file: Option<PathBuf>,
     #[arg(short, long)]
     benchmark: bool,
+    #[command(flatten)]
+    run: RunArgs,
+}
+
+/// Options for executing programs
+#[derive(clap::Args)]
+struct RunArgs {
+    /// What to do when an operation fails (abort, null)
+    #[arg(long, default_value = "abort")]
+    on_error: ErrorPolicy,
+    /// How statements get executed (bytecode, ast)
+    #[arg(long, default_value = "bytecode")]
+    engine: Engine,
+    /// Stops after executing this many statements
+    #[arg(long)]
+    max_steps: Option<u64>,
+    /// Stops after running for this many seconds
+    #[arg(long, value_parser = parse_seconds)]
+    timeout: Option<Duration>,
+    /// Stops when the memory has more keys than this
+    #[arg(long)]
+    max_memory_entries: Option<usize>,
+    /// Stops when the texts, symbols and big integers in memory take more bytes than this
+    #[arg(long)]
+    max_memory_bytes: Option<usize>,
+    /// Stops when more calls than this haven't returned yet
+    #[arg(long)]
+    max_call_depth: Option<usize>,
+    /// Where `µ` gets the time from (real, fixed, step:<duration> per statement like step:1ms)
+    #[arg(long, default_value = "real")]
+    clock: Clock,
+}
+
+impl RunArgs {
+    fn interpreter(&self, parsed: ast::Statements) -> Interpreter {
+        let mut interpreter = Interpreter::new(parsed);
+        // the output goes straight to stdout, keeping a copy would only grow with every line (mostly in the repl)
+        interpreter.set_capture_output(false);
+        interpreter.set_error_policy(self.on_error);
+        interpreter.set_engine(self.engine);
+        interpreter.set_clock(self.clock);
+        interpreter.set_limits(Limits {
+            max_steps: self.max_steps,
+            timeout: self.timeout,
+            max_memory_entries: self.max_memory_entries,
+            max_memory_bytes: self.max_memory_bytes,
+            max_call_depth: self.max_call_depth,
+        });
+        interpreter
+    }
+}
+
+fn parse_seconds(seconds: &str) -> Result<Duration, String> {
+    seconds.parse::<f64>()
+        .map_err(|error| error.to_string())
+        .and_then(|seconds| Duration::try_from_secs_f64(seconds).map_err(|error| error.to_string()))
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Starts an interactive session
+    Repl {
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Runs a program step by step with breakpoints
+    Debug {
+        file: PathBuf,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+    /// Rewrites programs in the canonical style
+    Fmt {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+        /// Only lists the files that aren't formatted, failing if there are any
+        #[arg(long)]
+        check: bool,
+    },
+    /// Warns about common mistakes, failing if there are any
+    Lint {
+        #[arg(required = true)]
+        files: Vec<PathBuf>,
+    },
+    /// Prints the control flow graph of a program
+    Cfg {
+        file: PathBuf,
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+    },
+    /// Translates a program to another language
+    Compile {
+        file: PathBuf,
+        #[arg(long, value_enum)]
+        target: CompileTarget,
+        /// Where to write the generated code (stdout if missing)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Lets C programs wrap integers at 128 bits and use doubles for floats
+        #[arg(long)]
+        fixed_width: bool,
+    },
+    /// Runs programs and compares what they print with their `.stdout` files or `# stdout:` comments
+    Test {
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
+        /// Writes what the programs print to their `.stdout` files instead of comparing
+        #[arg(long)]
+        bless: bool,
+    },
+    /// Runs a program and reports where it spent its time
+    Profile {
+        file: PathBuf,
+        /// How many entries each table shows
+        #[arg(long, default_value_t = 20)]
+        top: usize,
+        /// Writes collapsed stacks (for flamegraph tools) to this file
+        #[arg(long)]
+        collapsed: Option<PathBuf>,
+        #[command(flatten)]
+        run: RunArgs,
+    },
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum GraphFormat {
+    /// Graphviz
+    Dot,
+    Mermaid,
+}
+
+#[derive(Clone, Copy, clap::ValueEnum)]
+enum CompileTarget {
+    /// A program that runs on the library's `Runtime`, with the values and operations of the interpreter
+    Rust,
+    /// A single file with its runtime
+    C,
+    /// An ES module with its runtime, BigInt integers and a hook for floats
+    Js,
 }
 
 fn main() {
     let args = Args::parse();
 
-    let test = fs::read_to_string(args.file).unwrap();
+    let file = match (args.command, args.file) {
+        (Some(Command::Repl { run }), _) => return repl::repl(&run),
+        (Some(Command::Debug { file, run }), _) => {
+            let code = fs::read_to_string(file).unwrap();
+            let parsed = parse(&code);
+            return debugger::debug(&code, parsed, &run)
+        },
+        (Some(Command::Fmt { files, check }), _) => return fmt(&files, check),
+        (Some(Command::Lint { files }), _) => return lint(&files),
+        (Some(Command::Test { paths, bless }), _) => return test(&paths, bless),
+        (Some(Command::Cfg { file, format }), _) => {
+            let code = fs::read_to_string(file).unwrap();
+            let parsed = parse(&code);
+            let graph = speckylang::cfg::Graph::build(&parsed);
+            return match format {
+                GraphFormat::Dot => print!("{}", graph.to_dot(&code, &parsed)),
+                GraphFormat::Mermaid => print!("{}", graph.to_mermaid(&code, &parsed)),
+            }
+        },
+        (Some(Command::Compile { file, target, output, fixed_width }), _) => {
+            let code = fs::read_to_string(&file).unwrap();
+            let parsed = parse(&code);
+            let name = file.display().to_string();
+            let generated = match target {
+                CompileTarget::Rust => speckylang::compile::rust::transpile(&code, &parsed, &name),
+                CompileTarget::Js => speckylang::compile::js::transpile(&code, &parsed, &name),
+                CompileTarget::C => match speckylang::compile::c::transpile(&code, &parsed, &name, fixed_width) {
+                    Ok(generated) => generated,
+                    Err(unsupported) => {
+                        speckylang::compile::print_unsupported(&code, &unsupported);
+                        process::exit(1)
+                    },
+                },
+            };
+            return match output {
+                Some(output) => fs::write(output, generated).unwrap(),
+                None => print!("{generated}"),
+            }
+        },
+        (Some(Command::Profile { file, top, collapsed, run }), _) => {
+            let code = fs::read_to_string(&file).unwrap();
+            let parsed = parse(&code);
+            return profile::profile(&code, &file, parsed, &run, top, collapsed.as_deref())
+        },
+        (None, None) => return repl::repl(&args.run),
+        (None, Some(file)) => file,
+    };
+
+    let test = fs::read_to_string(file).unwrap();
 
     let parsed = parse(&test);
 
     match args.benchmark {
-        false => run(&parsed),
+        false => run(&test, &parsed, &args.run),
         true => {
             let mut min = Duration::MAX;
             let mut max = Duration::ZERO;
@@ -38,10 +225,8 @@ fn main() {
                     break
                 }
 
-                let operations = parsed.clone();
-
                 let begin = Instant::now();
-                run(&operations);
+                run(&test, &parsed, &args.run);
                 let taken = begin.elapsed();
 
                 min = min.min(taken);
@@ -57,9 +242,114 @@ fn main() {
     };
 }
 
-fn parse(code: &str) -> Vec<ast::Statement> {
-    let mut parser = parser::Parser::new(code);
-    match parser.parse_statements() {
+fn fmt(files: &[PathBuf], check: bool) {
+    let mut failed = false;
+
+    for file in files {
+        let code = fs::read_to_string(file).unwrap();
+        let formatted = match speckylang::fmt::format(&code) {
+            Ok(formatted) => formatted,
+            Err(error) => {
+                eprintln!("{}:", file.display());
+                parser::error::print_error(&code, error);
+                failed = true;
+                continue
+            },
+        };
+
+        if formatted == code {
+            continue
+        }
+
+        if check {
+            println!("{} is not formatted", file.display());
+            failed = true;
+        } else {
+            fs::write(file, formatted).unwrap();
+        }
+    }
+
+    if failed {
+        process::exit(1)
+    }
+}
+
+fn lint(files: &[PathBuf]) {
+    let mut warnings = 0;
+
+    for file in files {
+        let code = fs::read_to_string(file).unwrap();
+        let parsed = parse(&code);
+        for warning in speckylang::lint::lint(&code, &parsed) {
+            eprintln!("{}:", file.display());
+            speckylang::lint::print_warning(&code, &warning);
+            warnings += 1;
+        }
+    }
+
+    if warnings > 0 {
+        eprintln!("{warnings} warnings");
+        process::exit(1)
+    }
+}
+
+fn test(paths: &[PathBuf], bless: bool) {
+    use speckylang::golden::{self, Case, Outcome};
+
+    let files = golden::discover(paths).unwrap();
+    let (mut passed, mut unchecked, mut blessed, mut failures) = (0, 0, 0, vec![]);
+
+    for file in &files {
+        let outcome = match Case::load(file) {
+            Ok(case) => match case.check() {
+                Outcome::Failed { actual, .. } if bless => {
+                    case.bless(&actual).unwrap();
+                    blessed += 1;
+                    println!("{} ... blessed", file.display());
+                    continue
+                },
+                outcome => outcome,
+            },
+            Err(error) => Outcome::Error(error),
+        };
+
+        if outcome == Outcome::Passed {
+            passed += 1;
+            println!("{} ... ok", file.display());
+        } else if let Outcome::Unchecked(reason) = &outcome {
+            unchecked += 1;
+            println!("{} ... unchecked ({reason})", file.display());
+        } else {
+            println!("{} ... FAILED", file.display());
+            failures.push((file, outcome));
+        }
+    }
+
+    for (file, outcome) in &failures {
+        println!("\n---- {} ----", file.display());
+        match outcome {
+            Outcome::Failed { expected: None, .. } => println!("no expected output, run with --bless to save it"),
+            Outcome::Failed { expected: Some(expected), actual } => {
+                if let Some((line, expected, actual)) = golden::first_difference(expected, actual) {
+                    println!("first difference on line {line}");
+                    println!("expected: {:?}", expected.unwrap_or("<end of output>"));
+                    println!("  actual: {:?}", actual.unwrap_or("<end of output>"));
+                }
+            },
+            Outcome::Error(error) => println!("{error}"),
+            Outcome::Passed | Outcome::Unchecked(_) => unreachable!(),
+        }
+    }
+
+    let result = if failures.is_empty() { "ok" } else { "FAILED" };
+    println!("\ntest result: {result}. {passed} passed; {} failed; {unchecked} unchecked; {blessed} blessed", failures.len());
+    if !failures.is_empty() {
+        process::exit(1)
+    }
+}
+
+fn parse(code: &str) -> ast::Statements {
+    match speckylang::parse(code) {
         Ok(statements) => statements,
         Err(error) => {
             parser::error::print_error(code, error);
@@ -68,6 +358,13 @@ fn parse(code: &str) -> Vec<ast::Statement> {
     }
 }
 
-fn run(parsed: &Vec<ast::Statement>) {
-    run::run(parsed);
+fn run(code: &str, parsed: &ast::Statements, args: &RunArgs) {
+    let mut interpreter = args.interpreter(parsed.clone());
+    if let Err(error) = interpreter.run_to_end() {
+        run::error::print_error(code, &error);
+        match error.kind {
+            RuntimeErrorKind::LimitExceeded(_) => process::exit(3),
+            _ => process::exit(1),
+        }
+    }
 }
\ No newline at end of file
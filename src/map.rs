@@ -0,0 +1,160 @@
+use std::{cmp::Ordering, fmt, hash::{Hash, Hasher}};
+
+use crate::ast::Value;
+
+/// A map from values to values (`{a => 1 b => 2}`). The entries are kept sorted by key,
+/// so maps with the same entries are equal, hash the same and can be memory keys too.
+/// Like in memory, a missing key reads as null, so null values aren't stored.
+#[derive(Clone, Default, Eq)]
+pub struct Map(Vec<(Value, Value)>);
+
+/// A total order of keys, like the one of values except that NaN floats go after the other floats
+/// and are equal to each other, also inside of lists and maps
+fn key_order(left: &Value, right: &Value) -> Ordering {
+    match (left, right) {
+        (Value::Float(left), Value::Float(right)) => match (left.is_nan(), right.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => left.partial_cmp(right).expect("numbers"),
+        },
+        (Value::List(left), Value::List(right)) => left.iter().zip(right)
+            .map(|(left, right)| key_order(left, right))
+            .find(|order| order.is_ne())
+            .unwrap_or_else(|| left.len().cmp(&right.len())),
+        (Value::Map(left), Value::Map(right)) => left.entries_order(right),
+        // other values of the same kind are always ordered, and different kinds are ordered by kind
+        _ => left.partial_cmp(right).expect("values without floats"),
+    }
+}
+
+impl Map {
+    pub fn new() -> Map {
+        Map::default()
+    }
+
+    fn position(&self, key: &Value) -> Result<usize, usize> {
+        self.0.binary_search_by(|(entry, _)| key_order(entry, key))
+    }
+
+    pub fn get(&self, key: &Value) -> Option<&Value> {
+        self.position(key).ok().map(|index| &self.0[index].1)
+    }
+
+    /// Adds an entry (removes it if `value` is null), returning the value it replaced
+    pub fn insert(&mut self, key: Value, value: Value) -> Option<Value> {
+        match self.position(&key) {
+            Ok(index) if value == Value::Null => Some(self.0.remove(index).1),
+            Ok(index) => Some(std::mem::replace(&mut self.0[index].1, value)),
+            Err(_) if value == Value::Null => None,
+            Err(index) => {
+                self.0.insert(index, (key, value));
+                None
+            },
+        }
+    }
+
+    pub fn remove(&mut self, key: &Value) -> Option<Value> {
+        self.position(key).ok().map(|index| self.0.remove(index).1)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The entries, sorted by key
+    pub fn iter(&self) -> impl Iterator<Item = (&Value, &Value)> {
+        self.0.iter().map(|(key, value)| (key, value))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &Value> {
+        self.0.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.0.iter().map(|(_, value)| value)
+    }
+
+    /// The order of the first different entry (keys first), then the number of entries
+    fn entries_order(&self, other: &Map) -> Ordering {
+        self.0.iter().zip(&other.0)
+            .map(|((key, value), (other_key, other_value))| key_order(key, other_key).then_with(|| key_order(value, other_value)))
+            .find(|order| order.is_ne())
+            .unwrap_or_else(|| self.0.len().cmp(&other.0.len()))
+    }
+}
+
+/// Compared like keys, so maps with NaN floats are still equal to themselves
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries_order(other).is_eq()
+    }
+}
+
+/// Every NaN hashes the same, since they're all the same key
+fn hash_key<H: Hasher>(value: &Value, state: &mut H) {
+    match value {
+        Value::Float(float) if float.is_nan() => {
+            std::mem::discriminant(value).hash(state);
+            f64::NAN.to_bits().hash(state);
+        },
+        Value::List(list) => {
+            std::mem::discriminant(value).hash(state);
+            list.len().hash(state);
+            list.iter().for_each(|value| hash_key(value, state));
+        },
+        value => value.hash(state),
+    }
+}
+
+impl Hash for Map {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.len().hash(state);
+        for (key, value) in &self.0 {
+            hash_key(key, state);
+            hash_key(value, state);
+        }
+    }
+}
+
+impl PartialOrd for Map {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.entries_order(other))
+    }
+}
+
+/// Later entries replace earlier ones with the same key
+impl FromIterator<(Value, Value)> for Map {
+    fn from_iter<I: IntoIterator<Item = (Value, Value)>>(entries: I) -> Self {
+        let mut map = Map::new();
+        map.extend(entries);
+        map
+    }
+}
+
+impl Extend<(Value, Value)> for Map {
+    fn extend<I: IntoIterator<Item = (Value, Value)>>(&mut self, entries: I) {
+        for (key, value) in entries {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl IntoIterator for Map {
+    type Item = (Value, Value);
+    type IntoIter = std::vec::IntoIter<(Value, Value)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Debug for Map {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
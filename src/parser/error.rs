@@ -1,5 +1,5 @@
 use std::ops::Range;
-use ariadne::{Color, Report, ReportKind, Span};
+use ariadne::{Color, Config, IndexType, Report, ReportKind, Span};
 
 use crate::token::Token;
 
@@ -10,6 +10,16 @@ impl CodeArea {
     pub fn from_span(range: Range<usize>) -> Self {
         Self(range.start, range.end)
     }
+
+    /// 1-based line of the start of the area
+    pub fn line(&self, code: &str) -> usize {
+        code[..self.0].matches('\n').count() + 1
+    }
+}
+
+/// Reports need byte offsets, like the spans of the lexer
+pub(crate) fn report_config() -> Config {
+    Config::default().with_index_type(IndexType::Byte)
 }
 
 impl Span for CodeArea {
@@ -57,6 +67,7 @@ pub fn print_error(code: &str, error: ParsingError) {
     };
 
     let report = Report::build(kind, area)
+        .with_config(report_config())
         .with_message(&title)
         .with_label(
             ariadne::Label::new(area)
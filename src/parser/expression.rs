@@ -1,9 +1,10 @@
 use crate::{ast, token::Token};
 
-use super::{Parser, ParseResult};
+use super::{Parser, ParseResult, error::CodeArea};
 
 impl<'a> Parser<'a> {
     pub fn parse_expression(&mut self) -> ParseResult<ast::Expression> {
+        let start = self.peek_span().map(|span| span.start).unwrap_or(self.span().end);
         let mut reader_count = 0;
 
         while let Some(Token::Reader) = self.peek() {
@@ -11,9 +12,21 @@ impl<'a> Parser<'a> {
             reader_count += 1;
         }
 
+        // `@` is the pointer saved by the last `|>`
+        let saved_pointer = self.peek() == Some(Token::At);
+        let value = match saved_pointer {
+            true => {
+                self.next()?;
+                ast::Value::Null
+            },
+            false => self.parse_value()?,
+        };
+
         Ok(ast::Expression {
             reader: reader_count,
-            value: self.parse_value()?,
+            value,
+            saved_pointer,
+            area: CodeArea(start, self.span().end),
         })
     }
 }
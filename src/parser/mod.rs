@@ -42,11 +42,15 @@ impl<'a> Parser<'a> {
     fn next_is_token(&self) -> bool {
         self.clone().next().is_ok()
     }
-    #[allow(dead_code)]
     pub fn span(&self) -> Range<usize> {  self.lexer.span() }
     pub fn slice(&self) -> &str { self.lexer.slice() }
     fn peek(&self) -> Option<Token> {
         let mut lexer = self.lexer.clone();
         lexer.next()?.ok()
     }
+    fn peek_span(&self) -> Option<Range<usize>> {
+        let mut lexer = self.lexer.clone();
+        lexer.next()?.ok()?;
+        Some(lexer.span())
+    }
 }
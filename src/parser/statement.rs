@@ -1,10 +1,20 @@
-use crate::{ast::{self, LogMemory, LogValue, Statement}, token::Token};
+use crate::{ast::{self, LogMemory, LogValue, Statement, StatementKind}, token::Token};
 
 use super::{Parser, ParseResult, error::ParsingError, error::CodeArea};
 
 impl<'a> Parser<'a> {
     pub fn parse_statement(&mut self) -> ParseResult<Statement> {
         let token = self.next()?;
+        let start = self.span().start;
+
+        let kind = self.parse_statement_kind(token)?;
+
+        Ok(Statement {
+            kind,
+            area: CodeArea(start, self.span().end),
+        })
+    }
+    fn parse_statement_kind(&mut self, token: Token) -> ParseResult<StatementKind> {
 
         macro_rules! match_operation {
             (
@@ -36,7 +46,7 @@ impl<'a> Parser<'a> {
                 }
             };
             (# $operation:ident Expression) => {
-                Ok(Statement::$operation(self.parse_expression()?))
+                Ok(StatementKind::$operation(self.parse_expression()?))
             };
             (# $operation:ident Sequential) => {
                 {
@@ -45,24 +55,35 @@ impl<'a> Parser<'a> {
                         self.next()?;
                         quantity += 1;
                     }
-                    Ok(Statement::$operation(quantity))
+                    Ok(StatementKind::$operation(quantity))
                 }
             };
             (# $operation:ident) => {
-                Ok(Statement::$operation)
+                Ok(StatementKind::$operation)
             };
         }
 
         match_operation!(
             Load => Load(Expression),
+            Push => Push(Expression),
+            Pop => Pop(),
+            Exchange => Exchange(),
             Assign => Assign(Expression),
             Overwrite => Overwrite(Expression),
             Swap => Swap(Expression),
             Tilde => Index(Expression),
+            TildeEqual => SetIndex(Expression),
+            TTilde => Property(Expression),
 
             And => And(Expression),
             Or => Or(Expression),
             Xor => Xor(Expression),
+            BitAnd => BitAnd(Expression),
+            BitOr => BitOr(Expression),
+            BitXor => BitXor(Expression),
+            BitNot => BitNot(Expression),
+            ShiftLeft => ShiftLeft(Expression),
+            ShiftRight => ShiftRight(Expression),
             Plus => Plus(Expression),
             Minus => Minus(Expression),
             Asterisk => Times(Expression),
@@ -90,12 +111,17 @@ impl<'a> Parser<'a> {
             SquareBracketOpen => {
                 // Define => Define(Expression),
                 // Jump => Jump(Expression),
+                // Call => Call(Expression),
+                // Return => Return,
 
                 let mut kind = None;
+                let mut scoped = false;
 
                 enum JumpKind {
                     Define,
                     Jump,
+                    Call,
+                    Return,
                 }
 
                 loop {
@@ -103,6 +129,10 @@ impl<'a> Parser<'a> {
                     match token {
                         Token::LessThan => kind = Some(JumpKind::Define),   // <
                         Token::GreaterThan => kind = Some(JumpKind::Jump),  // >
+                        Token::Call => kind = Some(JumpKind::Call),         // >:
+                        Token::Return => kind = Some(JumpKind::Return),     // <:
+                        Token::Tilde => scoped = !scoped,                   // ~
+                        Token::TTilde => {},                                // ~~, twice
                         Token::SquareBracketClose => break,
                         _ => return Err(ParsingError::SyntaxError {
                             expected: "jump option".to_string(),
@@ -112,13 +142,21 @@ impl<'a> Parser<'a> {
                     }
                 }
 
-                let expression = self.parse_expression()?;
+                if scoped && !matches!(kind, Some(JumpKind::Call)) {
+                    return Err(ParsingError::SyntaxError {
+                        expected: "`>:` with the `~` of a scoped call".to_string(),
+                        found: Token::Tilde,
+                        area: CodeArea::from_span(self.span()),
+                    })
+                }
 
                 match kind {
-                    Some(JumpKind::Define) => Ok(Statement::Define(expression)),
-                    Some(JumpKind::Jump) => Ok(Statement::Jump(expression)),
+                    Some(JumpKind::Define) => Ok(StatementKind::Define(self.parse_expression()?)),
+                    Some(JumpKind::Jump) => Ok(StatementKind::Jump(self.parse_expression()?)),
+                    Some(JumpKind::Call) => Ok(StatementKind::Call { expression: self.parse_expression()?, scoped }),
+                    Some(JumpKind::Return) => Ok(StatementKind::Return),
                     None => Err(ParsingError::SyntaxError {
-                        expected: "`>` or `<` inside of the []".to_string(),
+                        expected: "`>`, `<`, `>:` or `<:` inside of the []".to_string(),
                         found: Token::Mu,
                         area: CodeArea::from_span(self.span()),
                     })
@@ -156,6 +194,7 @@ impl<'a> Parser<'a> {
                         Token::Reader => reader += 1,
                         Token::Exists => special = !special,
                         Token::Tilde => reverse = !reverse,
+                        Token::TTilde => {},
                         Token::Backslash => newline = !newline,
                         Token::Empty => space += 1,
                         Token::Circumflex => vertical = !vertical,
@@ -169,7 +208,7 @@ impl<'a> Parser<'a> {
                     }
                 }
 
-                Ok(Statement::Log {
+                Ok(StatementKind::Log {
                     kind: kind.map(|kind| match kind {
                         ast::LogKind::Value(_) => ast::LogKind::Value(LogValue {
                             reader: default_reader + reader,
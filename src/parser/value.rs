@@ -7,7 +7,7 @@ impl<'a> Parser<'a> {
         match self.peek() {
             Some(Token::Symbol) => {
                 self.next()?;
-                Ok(ast::Value::Symbol(self.slice().to_string()))
+                Ok(ast::Value::Symbol(ast::Symbol::intern(self.slice())))
             }
 
             Some(Token::True|Token::False) => {
@@ -94,6 +94,35 @@ impl<'a> Parser<'a> {
                 Ok(ast::Value::Text(text))
             }
 
+            Some(Token::SquareBracketOpen) => {
+                self.next()?;
+                let mut list = vec![];
+                while self.peek() != Some(Token::SquareBracketClose) {
+                    list.push(self.parse_value()?);
+                }
+                self.next()?;
+                Ok(ast::Value::List(list))
+            }
+
+            Some(Token::CurlyBracketOpen) => {
+                self.next()?;
+                let mut map = ast::Map::new();
+                while self.peek() != Some(Token::CurlyBracketClose) {
+                    let key = self.parse_value()?;
+                    match self.next()? {
+                        Token::Overwrite => {},
+                        found => return Err(ParsingError::SyntaxError {
+                            expected: "`=>` between a key and its value".to_string(),
+                            found,
+                            area: CodeArea::from_span(self.span()),
+                        }),
+                    }
+                    map.insert(key, self.parse_value()?);
+                }
+                self.next()?;
+                Ok(ast::Value::Map(map))
+            }
+
             Some(Token::Mu) => {
                 self.next()?;
                 Ok(ast::Value::Time(None))
@@ -0,0 +1,118 @@
+use std::{collections::BTreeMap, fs, path::Path, time::Duration};
+
+use speckylang::{ast::{self, StatementKind, Value}, run::{self, Profile, StatementProfile}};
+
+use crate::RunArgs;
+
+fn percent(time: Duration, total: Duration) -> f64 {
+    if total.is_zero() { 0.0 } else { time.as_secs_f64() / total.as_secs_f64() * 100.0 }
+}
+
+fn print_rows(title: &str, rows: &[(StatementProfile, usize, &str)], total: Duration) {
+    eprintln!("\n{title}");
+    eprintln!("{:>12} {:>7} {:>10} {:>6}  source", "time", "%", "count", "line");
+    for (profile, line, source) in rows {
+        eprintln!(
+            "{:>12} {:>6.2}% {:>10} {:>6}  {source}",
+            format!("{:.2?}", profile.time),
+            percent(profile.time, total),
+            profile.count,
+            line,
+        );
+    }
+}
+
+/// Name of the closest `[<] label` before each statement
+fn enclosing_labels(parsed: &ast::Statements) -> Vec<String> {
+    let mut current = "main".to_string();
+    parsed.iter()
+        .map(|statement| {
+            if let StatementKind::Define(ast::Expression { reader: 0, value, saved_pointer: false, .. }) = &statement.kind {
+                current = run::value_to_string(value, false);
+            }
+            current.clone()
+        })
+        .collect()
+}
+
+fn report(code: &str, parsed: &ast::Statements, profile: &Profile, top: usize) {
+    let total = profile.total_time();
+    let executed = profile.statements.iter().map(|statement| statement.count).sum::<u64>();
+    let lines = code.lines().collect::<Vec<_>>();
+
+    eprintln!("\nprofile: {executed} statements executed in {total:.2?}");
+
+    let mut statements = profile.statements.iter()
+        .enumerate()
+        .filter(|(_, statement)| statement.count > 0)
+        .map(|(index, statement)| {
+            let area = parsed[index].area;
+            (*statement, area.line(code), &code[area.0..area.1])
+        })
+        .collect::<Vec<_>>();
+    statements.sort_by_key(|(profile, _, _)| std::cmp::Reverse(profile.time));
+    statements.truncate(top);
+    print_rows("hottest statements", &statements, total);
+
+    let mut by_line = BTreeMap::<usize, StatementProfile>::new();
+    for (index, statement) in profile.statements.iter().enumerate() {
+        let line = by_line.entry(parsed[index].area.line(code)).or_default();
+        line.count = line.count.max(statement.count);
+        line.time += statement.time;
+    }
+    let mut by_line = by_line.into_iter()
+        .filter(|(_, line)| line.count > 0)
+        .map(|(line, profile)| (profile, line, lines.get(line - 1).map(|l| l.trim()).unwrap_or("")))
+        .collect::<Vec<_>>();
+    by_line.sort_by_key(|(profile, _, _)| std::cmp::Reverse(profile.time));
+    by_line.truncate(top);
+    print_rows("hottest lines (count is the most executed statement of the line)", &by_line, total);
+
+    let mut jumps = profile.jumps.iter().collect::<Vec<(&Value, &u64)>>();
+    jumps.sort_by_key(|(_, count)| std::cmp::Reverse(**count));
+    jumps.truncate(top);
+    eprintln!("\nhottest jump labels");
+    eprintln!("{:>10}  label", "count");
+    for (label, count) in jumps {
+        eprintln!("{count:>10}  {}", run::value_to_string(label, false));
+    }
+}
+
+/// Writes `file;label;line N nanoseconds` lines, which flamegraph tools can read
+fn write_collapsed(path: &Path, file_name: &str, code: &str, parsed: &ast::Statements, profile: &Profile) {
+    let labels = enclosing_labels(parsed);
+
+    let mut stacks = BTreeMap::<String, u128>::new();
+    for (index, statement) in profile.statements.iter().enumerate() {
+        if statement.count == 0 { continue }
+        let stack = format!("{file_name};{};line {}", labels[index], parsed[index].area.line(code));
+        *stacks.entry(stack).or_default() += statement.time.as_nanos();
+    }
+
+    let collapsed = stacks.into_iter()
+        .map(|(stack, nanos)| format!("{stack} {nanos}\n"))
+        .collect::<String>();
+
+    match fs::write(path, collapsed) {
+        Ok(()) => eprintln!("\ncollapsed stacks written to {}", path.display()),
+        Err(error) => eprintln!("\ncouldn't write {}: {error}", path.display()),
+    }
+}
+
+pub fn profile(code: &str, file: &Path, parsed: ast::Statements, args: &RunArgs, top: usize, collapsed: Option<&Path>) {
+    let mut interpreter = args.interpreter(parsed.clone());
+    interpreter.set_profiling(true);
+
+    if let Err(error) = interpreter.run_to_end() {
+        run::error::print_error(code, &error);
+    }
+
+    let profile = interpreter.profile().expect("profiling is enabled");
+
+    report(code, &parsed, profile, top);
+
+    if let Some(path) = collapsed {
+        let file_name = file.file_name().map(|name| name.to_string_lossy()).unwrap_or_default();
+        write_collapsed(path, &file_name, code, &parsed, profile);
+    }
+}
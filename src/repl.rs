@@ -0,0 +1,96 @@
+use std::{fs, io::{self, Write}};
+
+use speckylang::{parser, run};
+
+use crate::RunArgs;
+
+const HELP: &str = "\
+:mem            prints the memory (sorted)
+:ptr            prints the current pointer
+:reset          clears memory, pointer and previous statements
+:load <file>    runs a file with the current memory
+:help           prints this message
+:quit           exits the repl";
+
+struct Repl {
+    // keeps the statements from all previous inputs, so that old jump addresses keep working
+    interpreter: run::Interpreter,
+    // index of the first statement of each input and its code, for error reports
+    sources: Vec<(usize, String)>,
+}
+
+impl Repl {
+    fn new(args: &RunArgs) -> Self {
+        Self {
+            interpreter: args.interpreter(vec![]),
+            sources: vec![],
+        }
+    }
+
+    fn execute(&mut self, code: &str) {
+        match speckylang::parse(code) {
+            Ok(statements) => {
+                let start = self.interpreter.program().len();
+                self.sources.push((start, code.to_string()));
+                self.interpreter.extend_program(statements);
+                self.interpreter.set_program_counter(start);
+                self.interpreter.restart_timeout();
+                if let Err(error) = self.interpreter.run_to_end() {
+                    let (_, source) = self.sources.iter()
+                        .rfind(|(start, _)| *start <= error.statement_index)
+                        .unwrap_or(&self.sources[0]);
+                    run::error::print_error(source, &error);
+                }
+            },
+            Err(error) => parser::error::print_error(code, error),
+        }
+    }
+
+    fn command(&mut self, command: &str, args: &RunArgs) -> bool {
+        let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+
+        match (name, argument.trim()) {
+            ("mem", _) => println!("{}", run::memory_to_string(self.interpreter.memory(), true)),
+            ("ptr", _) => println!("{}", run::value_to_string_at(self.interpreter.pointer(), false, self.interpreter.now())),
+            ("reset", _) => *self = Self::new(args),
+            ("load", "") => println!("usage: :load <file>"),
+            ("load", file) => match fs::read_to_string(file) {
+                Ok(code) => self.execute(&code),
+                Err(error) => println!("couldn't read '{file}': {error}"),
+            },
+            ("help", _) => println!("{HELP}"),
+            ("quit"|"q", _) => return false,
+            _ => println!("unknown command ':{name}' (try :help)"),
+        }
+
+        true
+    }
+}
+
+pub fn repl(args: &RunArgs) {
+    let mut repl = Repl::new(args);
+    let stdin = io::stdin();
+
+    println!("SpeckyLang {} (type :help for commands)", env!("CARGO_PKG_VERSION"));
+
+    loop {
+        print!("> ");
+        let _ = io::stdout().flush();
+
+        let mut line = String::new();
+        match stdin.read_line(&mut line) {
+            Ok(0)|Err(_) => break,
+            Ok(_) => {},
+        }
+
+        let line = line.trim();
+
+        if let Some(command) = line.strip_prefix(':') {
+            if !repl.command(command.trim(), args) {
+                break
+            }
+        } else if !line.is_empty() {
+            repl.execute(line);
+        }
+    }
+}
@@ -1,653 +0,0 @@
-use std::time::{Instant, Duration};
-use std::io::{self, Write};
-
-use ahash::AHashMap;
-use num_bigfloat::BigFloat;
-use num_bigint::{BigInt, Sign};
-
-type SpeckyDataContainer<V> = AHashMap<Value, V>;
-
-use crate::ast::{Float, Integer, LogKind, LogMemory, LogValue, SmallInt, Statement, Statements, Value};
-
-const NULL: Value = Value::Null;
-
-#[derive(Debug)]
-pub struct RunOutput {
-    #[allow(unused)]
-    pub stdout: String,
-    #[allow(unused)]
-    pub variables: SpeckyDataContainer<Value>,
-}
-
-pub fn run(parsed: &Statements) -> RunOutput {
-    let mut variables: SpeckyDataContainer<Value> = SpeckyDataContainer::default();
-    let mut current_pointer = Value::Null;
-
-    let mut statement_index = 0;
-
-    let mut max_time: (Duration, &Statement) = (Duration::ZERO, &Statement::Truthy(0));
-
-    let stdout = io::stdout();
-    let lock = stdout.lock();
-    let mut w = io::BufWriter::new(lock);
-    let mut output = String::new();
-    let mut output_updated = false;
-    let mut last_flush = Instant::now();
-
-    loop {
-        if statement_index >= parsed.len() { break; }
-
-        let mut next_statement = true;
-
-        macro_rules! match_statement {
-            { $($statement:ident $($expr:tt)? => $code:tt $(,)?)* } => {
-                match &parsed[statement_index] {
-                    $(
-                        match_statement!(@pat $statement $($expr)?) => {
-                            $(
-                                #[allow(unused_macros)]
-                                macro_rules! operand {
-                                    () => {
-                                        value_reader(&variables, &$expr.value, $expr.reader)
-                                    };
-                                }
-
-                                #[allow(unused_macros)]
-                                macro_rules! condition_jump {
-                                    ($condition:expr, $quantity:expr) => {
-                                        {
-                                            let value = variables.get(&current_pointer).unwrap_or(&Value::Null);
-                                            #[allow(clippy::redundant_closure)]
-                                            #[allow(clippy::redundant_closure_call)]
-                                            if !$condition(value) { statement_index += $quantity; }
-                                        }
-                                    }
-                                }
-                            )?
-            
-                            #[allow(unused_macros)]
-                            macro_rules! left_right_operator {
-                                ($callback:expr) => {
-                                    let right = operand!().clone();
-                                    let left = variables.get(&current_pointer).unwrap_or(&Value::Null).clone();
-                                    // Number conversion
-                                    let (left, right) = match (&left, &right) {
-                                        // SmallInt -> Integer
-                                        (Value::Integer(_),Value::SmallInt(si)) => (left, Value::Integer(Integer::from(*si))),
-                                        (Value::SmallInt(si),Value::Integer(_)) => (Value::Integer(Integer::from(*si)), right),
-                                        // SmallInt -> Float
-                                        (Value::Float(_),Value::SmallInt(si)) => (left, Value::Float(BigFloat::from(*si))),
-                                        (Value::SmallInt(si),Value::Float(_)) => (Value::Float(BigFloat::from(*si)), right),
-                                        // Integer -> Float
-                                        (Value::Float(_),Value::Integer(bi)) => (left, Value::Float(BigFloat::parse(&bi.to_string()).unwrap())),
-                                        (Value::Integer(bi),Value::Float(_)) => (Value::Float(BigFloat::parse(&bi.to_string()).unwrap()), right),
-                                        // Other
-                                        _ => (left, right),
-                                    };
-                                    #[allow(clippy::redundant_closure_call)]
-                                    let result = $callback(left, right);
-                                    variables.insert(current_pointer.clone(), result);
-                                }
-                            }
-
-                            $code
-                        },
-                    )*
-                }
-            };
-            (@pat $ident:ident $(())?) => { Statement::$ident };
-            (@pat $ident:ident $expr:tt) => { Statement::$ident $expr };
-        }
-
-        let start_operation = Instant::now();
-
-        match_statement! {
-            Load(expr) => {
-                current_pointer = operand!().clone();
-                compress_value(&mut current_pointer);
-            },
-            Define(expr) => {
-                variables.insert(operand!().clone(), Value::SmallInt(statement_index as SmallInt + 1));
-            },
-            Jump(expr) => {
-                match variables.get(operand!()) {
-                    Some(Value::Integer(index)) if index > &BigInt::ZERO && index < &BigInt::from(usize::MAX) => {
-                        statement_index = *index.to_u64_digits().1.first().unwrap() as usize;
-                        next_statement = false;
-                    },
-                    Some(Value::SmallInt(index)) if (0..=usize::MAX as i128).contains(index) => {
-                        statement_index = *index as usize;
-                        next_statement = false;
-                    },
-                    _ => {},
-                }
-            },
-            Assign(expr) => {
-                variables.insert(
-                    current_pointer.clone(),
-                    match operand!() {
-                        Value::Time(time) => Value::Time(Some(time.unwrap_or(Instant::now()))),
-                        rest => rest.clone(),
-                    },
-                );
-            },
-            Overwrite(expr) => {
-                variables.insert(
-                    match operand!() {
-                        Value::Time(time) => Value::Time(Some(time.unwrap_or(Instant::now()))),
-                        rest => rest.clone(),
-                    },
-                    current_pointer.clone(),
-                );
-            },
-            Swap(expr) => {
-                let current_operand = operand!().clone();
-                let temp = variables.get(&current_pointer).unwrap_or(&Value::Null).clone();
-                variables.insert(current_pointer.clone(), variables.get(&current_operand).unwrap_or(&Value::Null).clone());
-                variables.insert(current_operand, temp);
-            },
-            Index(expr) => {
-                left_right_operator!(|left, right| {
-                    match (left, right) {
-                        (Value::Text(string)|Value::Symbol(string), right) => {
-                            let integer: Option<usize> = match right {
-                                Value::Integer(int) => int.try_into().ok(),
-                                Value::SmallInt(int) => int.try_into().ok(),
-                                Value::Float(float) => float.floor().to_u128().and_then(|a| a.try_into().ok()),
-                                _ => return Value::Null,
-                            };
-                            if let Some(ch) = integer.and_then(|int| string.chars().nth(int)) {
-                                Value::Text(ch.to_string())
-                            } else {
-                                Value::Null
-                            }
-                            
-                        }
-                        _ => Value::Null,
-                    }
-                });
-            },
-            And(expr) => {
-                left_right_operator!(|left, right|{
-                    match (value_is_truthy(&left), value_is_truthy(&right)) {
-                        (true, true) => Value::Boolean(true),
-                        _ => Value::Boolean(false),
-                    }
-                });
-            },
-            Or(expr) => {
-                left_right_operator!(|left, right|{
-                    match (value_is_truthy(&left), value_is_truthy(&right)) {
-                        (false, false) => Value::Boolean(false),
-                        _ => Value::Boolean(true),
-                    }
-                });
-            },
-            Xor(expr) => {
-                left_right_operator!(|left, right|{
-                    match (value_is_truthy(&left), value_is_truthy(&right)) {
-                        (true, false)|(false, true) => Value::Boolean(true),
-                        _ => Value::Boolean(false),
-                    }
-                });
-            },
-            Plus(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer(left + &right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_add(right)
-                            .map(Value::SmallInt)
-                            .unwrap_or(Value::Integer(Integer::from(left) + Integer::from(right))),
-                        (Value::Float(left), Value::Float(right)) => Value::Float(left + right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)) => Value::Text(left + &right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Integer(right)) => Value::Text(left + &right.to_string()),
-                        (Value::Text(left)|Value::Symbol(left), Value::SmallInt(right)) => Value::Text(left + &right.to_string()),
-                        (Value::Symbol(left), Value::Symbol(right)) => Value::Symbol(left + &right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Minus(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer(left - &right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_sub(right)
-                            .map(Value::SmallInt)
-                            .unwrap_or(Value::Integer(Integer::from(left) - Integer::from(right))),
-                        (Value::Float(left), Value::Float(right)) => Value::Float(left - right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => {
-                            let left = Integer::from_bytes_be(Sign::Plus, left.as_bytes());
-                            let right = Integer::from_bytes_be(Sign::Plus, right.as_bytes());
-                            compress_integer(left - right)
-                        },
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Times(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer(left * &right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_mul(right)
-                            .map(Value::SmallInt)
-                            .unwrap_or(Value::Integer(Integer::from(left) * Integer::from(right))),
-                        (Value::Float(left), Value::Float(right)) => Value::Float(left * right),
-                        (Value::Text(mut left)|Value::Symbol(mut left), Value::Integer(mut right)) => {
-                            if matches!(right.sign(), Sign::Minus) {
-                                left = left.chars().rev().collect();
-                                right = -right;
-                            }
-                            Value::Text(left.repeat(right.try_into().unwrap_or(usize::MAX)))
-                        },
-                        (Value::Text(mut left)|Value::Symbol(mut left), Value::SmallInt(mut right)) => {
-                            if right < 0 {
-                                left = left.chars().rev().collect();
-                                right = right.abs();
-                            }
-                            Value::Text(left.repeat(right.try_into().unwrap_or(usize::MAX)))
-                        },
-                        (Value::Text(left)|Value::Symbol(left), Value::Float(right)) => {
-                            let integer = right.int().abs().to_u128().map(|i| i.try_into().unwrap_or(usize::MAX)).unwrap_or(usize::MAX);
-                            let fraction = (right.frac().to_f64().abs() * left.len() as f64).round() as usize;
-                            let fraction_string = &left[0..fraction];
-                            if right.is_negative() {
-                                let mut output: String = fraction_string.chars().rev().collect();
-                                let rev_string = left.chars().rev().collect::<String>().repeat(integer);
-                                output.push_str(&rev_string);
-                                Value::Text(output)
-                            } else {
-                                let mut output = left.repeat(integer);
-                                output.push_str(fraction_string);
-                                Value::Text(output)
-                            }
-                        },
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Divide(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer(left / &right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left / right),
-                        (Value::Float(left), Value::Float(right)) => Value::Float(left / right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Modulo(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer(left % &right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left % right),
-                        (Value::Float(left), Value::Float(right)) => Value::Float(left % right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            PPercent(expr) => {
-                left_right_operator!(|left, right| {
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer((left % &right + &right) % &right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt((left % right + right) % right),
-                        (Value::Float(left), Value::Float(right)) => Value::Float((left % right + right) % right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Exponential(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => compress_integer(left.pow(right.try_into().unwrap())),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left.pow(right.try_into().unwrap())),
-                        (Value::Float(left), Value::Float(right)) => Value::Float(left.pow(&right)),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Unequal(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Boolean(left), Value::Boolean(right)) => Value::Boolean(left != right),
-                        (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left != right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left != right),
-                        (Value::Float(left), Value::Float(right)) => Value::Boolean(left != right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => Value::Boolean(left != right),
-                        _ => Value::Boolean(true),
-                    }
-                });
-            },
-            Equal(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Boolean(left), Value::Boolean(right)) => Value::Boolean(left == right),
-                        (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left == right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left == right),
-                        (Value::Float(left), Value::Float(right)) => Value::Boolean(left == right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => Value::Boolean(left == right),
-                        _ => Value::Boolean(false),
-                    }
-                });
-            },
-            LessThan(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left < right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left < right),
-                        (Value::Float(left), Value::Float(right)) => Value::Boolean(left < right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => Value::Boolean(left < right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            GreaterThan(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left > right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left > right),
-                        (Value::Float(left), Value::Float(right)) => Value::Boolean(left > right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => Value::Boolean(left > right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            LessThanOrEqual(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left <= right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left <= right),
-                        (Value::Float(left), Value::Float(right)) => Value::Boolean(left <= right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => Value::Boolean(left <= right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            GreaterThanOrEqual(expr) => {
-                left_right_operator!(|left, right|{
-                    match (left, right) {
-                        (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left >= right),
-                        (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left >= right),
-                        (Value::Float(left), Value::Float(right)) => Value::Boolean(left >= right),
-                        (Value::Text(left)|Value::Symbol(left), Value::Text(right)|Value::Symbol(right)) => Value::Boolean(left >= right),
-                        _ => Value::Null,
-                    }
-                });
-            },
-            Truthy(quantity) => { condition_jump!(|value| value_is_truthy(value), quantity); },
-            Falsy(quantity) => { condition_jump!(|value| !value_is_truthy(value), quantity); },
-            Exists(quantity) => { condition_jump!(|value| value_exists(value), quantity); },
-            Empty(quantity) => { condition_jump!(|value| !value_exists(value), quantity); },
-            Log { kind, reverse, newline, space, vertical, assign } => {
-                let string = match kind {
-                    Some(LogKind::Value(LogValue { reader, pretty })) => {
-                        let print = value_reader(&variables, &current_pointer, *reader);
-                        value_to_string(print, *pretty)
-                    },
-                    Some(LogKind::Type) => {
-                        // TODO: idk, reader and selecting what the value actually is, maybe make it a property
-                        match variables.get(&current_pointer).unwrap_or(&NULL) {
-                            Value::Symbol(_) => "Symbol",
-                            Value::Boolean(_) => "Boolean",
-                            Value::Integer(_) => "Integer",
-                            Value::SmallInt(_) => "SmallInt",
-                            Value::Float(_) => "Float",
-                            Value::Text(_) => "Text",
-                            Value::Time(_) => "Time",
-                            Value::Null => "Null",
-                        }.to_string()
-                    },
-                    Some(LogKind::Memory(LogMemory { sort })) => {
-                        let mut variables_string = String::new();
-                        variables_string += "{\n";
-
-                        let mut push_str = |key, value| {
-                            variables_string += &format!("\t{key:?} => {value:?}\n");
-                        };
-
-                        if *sort {
-                            let mut sorted_vars = variables.iter().collect::<Vec<_>>();
-
-                            sorted_vars.sort_unstable_by(|(a,_), (b,_)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
-
-                            for (key, value) in sorted_vars {
-                                push_str(key, value)
-                            }
-                        } else {
-                            for (key, value) in &variables {
-                                push_str(key, value)
-                            }
-                        };
-
-                        variables_string += "}";
-
-                        variables_string
-                    },
-                    None => "".to_string(),
-                };
-
-                let string = if *reverse {
-                    string.chars().rev().collect()
-                } else {
-                    string
-                };
-
-                let string = string + &" ".repeat(*space);
-                let string = string + if *newline { "\n" } else { "" };
-
-                let string = if *vertical {
-                    let max = string.lines().map(|l| l.len()).max().unwrap_or(0);
-                    let lines = string.lines().collect::<Vec<&str>>();
-                    let mut output_lines: Vec<String> = vec![];
-
-                    for i in 0..max {
-                        output_lines.push(lines.iter().map(|l| l.chars().nth(i).unwrap_or(' ')).collect());
-                    }
-                    output_lines.join("\n")
-                } else {
-                    string
-                };
-
-                if *assign {
-                    let _ = variables.insert(
-                        current_pointer.clone(),
-                        string_to_value(&string),
-                    );
-                } else {
-                    write!(w, "{string}").unwrap();
-                    output.push_str(&string);
-                    output_updated = true;
-                }
-            },
-            Input() => {
-                w.flush().unwrap();
-                variables.insert(current_pointer.clone(), value_input());
-            },
-        }
-
-        if start_operation.elapsed() > max_time.0 {
-            max_time = (start_operation.elapsed(), &parsed[statement_index])
-        }
-
-        if output_updated && last_flush.elapsed() > Duration::from_millis(50) {
-            w.flush().unwrap();
-            last_flush = Instant::now();
-            output_updated = false;
-        }
-
-        if next_statement {
-            statement_index += 1;
-        }
-    }
-
-    w.flush().unwrap();
-
-    RunOutput {
-        stdout: output,
-        variables,
-    }
-}
-
-#[inline(always)]
-fn compress_value(value: &mut Value) {
-    if let Value::Integer(int) = &value {
-        let si = int.try_into();
-        if let Ok(si) = si { 
-            *value = Value::SmallInt(si);
-        }
-    };
-}
-
-#[inline(always)]
-fn compress_integer(integer: Integer) -> Value {
-    (&integer).try_into()
-    .map(Value::SmallInt)
-    .unwrap_or(Value::Integer(integer))
-}
-
-#[inline(always)]
-fn value_input() -> Value {
-    let _ = io::stdout().flush();
-    let mut s = String::new();
-    io::stdin().read_line(&mut s).unwrap_or(0);
-    string_to_value(&s)
-}
-
-#[inline(always)]
-fn string_to_value(string: &str) -> Value {
-    let string = string.trim();
-
-    if string.is_empty() {
-        return Value::Null
-    }
-
-    if string.chars().all(|c| char::is_ascii_digit(&c) || c == '.') {
-        match string.chars().filter(|&c| c == '.').count() {
-            0 => 
-                return string.parse::<SmallInt>()
-                .map(Value::SmallInt)
-                .unwrap_or(Value::Integer(string.parse::<Integer>().unwrap())),
-            1 => 
-                if let Ok(float) = string.parse::<Float>() {
-                    return Value::Float(float)
-                },
-            _ => {}
-        }
-    }
-
-    return match string {
-        "true"|"on"|"yes" => Value::Boolean(true),
-        "false"|"off"|"no" => Value::Boolean(false),
-        "null" => Value::Null,
-        "µ" => Value::Time(Some(Instant::now())),
-        string if string.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
-            Value::Symbol(string.to_string()),
-        string => Value::Text(string.to_string()),
-    };
-}
-
-fn value_reader<'a>(memory: &'a SpeckyDataContainer<Value>, value: &'a Value, reader: usize) -> &'a Value {
-    let mut chain: Vec<&Value> = Vec::with_capacity(memory.len());
-    chain.push(value);
-
-    let mut current_value = value;
-
-    for i in 0..reader {
-        let temp_value: Value;
-
-        let current_pointer = match current_value {
-            Value::SmallInt(int) => {
-                temp_value = Value::Integer(Integer::from(*int));
-                if memory.contains_key(current_value) {
-                    current_value
-                } else {
-                    &temp_value
-                }
-            },
-            Value::Integer(int) => {
-                let small_form = int.try_into()
-                .map(Value::SmallInt);
-
-                if small_form.is_ok() && memory.contains_key(small_form.as_ref().unwrap()) {
-                    temp_value = small_form.unwrap();
-                    &temp_value
-                } else {
-                    current_value
-                }
-            },
-            other => other,
-        };
-
-        current_value = memory.get(current_pointer).unwrap_or(&NULL);
-
-        let exists = chain.iter().enumerate().find_map(|(i, v)| if v == &current_value { Some(i) } else { None });
-
-        if let Some(index) = exists {
-            let base = reader + i + index + 1;
-            let modulo = chain.len() - index;
-            let chain_index = base % modulo + index;
-
-            return chain[chain_index];
-        }
-
-        chain.push(current_value);
-    }
-
-    current_value
-}
-
-fn value_to_string(value: &Value, special: bool) -> String {
-    match (value, special) {
-        (Value::Symbol(s), false) => s.to_string(),
-        (Value::Symbol(s), true) => Integer::from_bytes_be(num_bigint::Sign::Plus, s.as_bytes()).to_string(),
-
-        (Value::Boolean(b), false) => b.to_string(),
-        (Value::Boolean(b), true) => format!("{}", if *b { 1 } else { 0 }),
-
-        (Value::Integer(i), false) => i.to_string(),
-        (Value::Integer(i), true) =>
-            i.try_into()
-            .ok()
-            .and_then(char::from_u32)
-            .map(|c| c.to_string())
-            .unwrap_or_else(|| char::REPLACEMENT_CHARACTER.to_string()),
-
-        (Value::SmallInt(i), false) => i.to_string(),
-        (Value::SmallInt(i), true) =>
-            (*i as u128).try_into()
-            .ok()
-            .and_then(char::from_u32)
-            .map(|c| c.to_string())
-            .unwrap_or_else(|| char::REPLACEMENT_CHARACTER.to_string()),
-
-        (Value::Float(f), false) => f.to_string(),
-        (Value::Float(f), true) => f.to_f64().to_string(),
-
-        (Value::Text(s), false) => format!("/{}/", s.replace('/', r"\/")),
-        (Value::Text(s), true) => s.to_string(),
-
-        (Value::Time(d), false) => format!("{:?}", d.unwrap_or(Instant::now()).elapsed()),
-        (Value::Time(d), true) => format!("{}", d.unwrap_or(Instant::now()).elapsed().as_secs_f64()),
-
-        (Value::Null, false) => "null".to_string(),
-        (Value::Null, true) => "\0".to_string(),
-    }
-}
-
-#[inline(always)]
-fn value_is_truthy(value: &Value) -> bool {
-    match value {
-        Value::Symbol(_) => true,
-        Value::Boolean(b) => *b,
-        Value::Integer(n) => *n != Integer::from(0),
-        Value::SmallInt(i) => *i != 0,
-        Value::Float(f) => !f.is_nan() && !f.is_inf() && *f != Float::from(0.0),
-        Value::Text(s) => !s.is_empty(),
-        Value::Time(_) => true,
-        Value::Null => false,
-    }
-}
-
-#[inline(always)]
-fn value_exists(value: &Value) -> bool {
-    !matches!(value, Value::Null)
-}
@@ -0,0 +1,395 @@
+use std::str::FromStr;
+
+use ahash::AHashMap;
+
+use crate::ast::{SmallInt, StatementKind, Statements, Value};
+
+use super::{Interpreter, RuntimeErrorKind, SpeckyDataContainer, SpeckyIo, operation::*, value::*};
+
+/// How the interpreter executes statements
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// Walks the syntax tree, reading every expression through memory
+    Ast,
+    /// Runs the program compiled to bytecode, with literals and jump addresses resolved ahead of time
+    #[default]
+    Bytecode,
+}
+
+impl FromStr for Engine {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ast" => Ok(Engine::Ast),
+            "bytecode" => Ok(Engine::Bytecode),
+            other => Err(format!("unknown engine '{other}' (expected 'ast' or 'bytecode')")),
+        }
+    }
+}
+
+/// Where an instruction gets its value from, indexes into [`Program::constants`]
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Operand {
+    /// A literal, used without touching memory
+    Constant(u32),
+    /// `§§value`, read through memory that many times
+    Read(u32, usize),
+    /// `§§@`, the pointer saved by the last `|>` read through memory that many times
+    Saved(usize),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Condition {
+    Truthy,
+    Falsy,
+    Exists,
+    Empty,
+}
+
+impl Condition {
+    #[inline(always)]
+    fn holds(self, value: &Value) -> bool {
+        match self {
+            Condition::Truthy => value_is_truthy(value),
+            Condition::Falsy => !value_is_truthy(value),
+            Condition::Exists => value_exists(value),
+            Condition::Empty => !value_exists(value),
+        }
+    }
+}
+
+/// One instruction for each statement, so statement indexes (and jump addresses) stay the same
+#[derive(Debug, Clone, Copy)]
+pub(super) enum Instruction {
+    /// `|< value`, the constant is already compressed.
+    /// With `fuse` the next statement runs in the same step (`|< a <= 5`, `|< i + 1`).
+    Load { pointer: Operand, fuse: bool },
+    Push(Operand),
+    Pop,
+    Exchange,
+    /// `[<] label`, `slot` is set for labels in [`Program::labels`]
+    Define { label: Operand, slot: Option<u32> },
+    Jump { label: Operand, slot: Option<u32> },
+    Call { label: Operand, slot: Option<u32>, scoped: bool },
+    Return,
+    Assign(Operand),
+    Overwrite(Operand),
+    Swap(Operand),
+    Operation(Operator, Operand),
+    Condition(Condition, usize),
+    /// Reads its options from the statement
+    Log,
+    Input,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LabelState {
+    /// Nothing stored yet, jumps do nothing
+    Undefined,
+    /// The memory has the address of the label
+    Defined,
+    /// The memory could have anything (changed from outside, or the pointer is the label),
+    /// jumps read it like [`Engine::Ast`]
+    Changed,
+}
+
+/// A jump address only written by one `[<] label`, so jumps don't have to look it up
+#[derive(Debug, Clone)]
+struct Label {
+    key: u32,
+    address: usize,
+    state: LabelState,
+}
+
+/// A program compiled from [`Statements`]
+#[derive(Debug, Clone, Default)]
+pub(super) struct Program {
+    instructions: Vec<Instruction>,
+    /// Every literal of the program, once
+    constants: Vec<Value>,
+    labels: Vec<Label>,
+}
+
+impl Program {
+    pub fn compile(statements: &Statements) -> Self {
+        let mut program = Program::default();
+        let mut interned = AHashMap::<Value, u32>::new();
+
+        let mut intern = |program: &mut Program, value: Value| *interned.entry(value).or_insert_with_key(|value| {
+            program.constants.push(value.clone());
+            program.constants.len() as u32 - 1
+        });
+
+        macro_rules! operand {
+            ($expr:expr) => {
+                operand!($expr, $expr.value.clone())
+            };
+            ($expr:expr, $value:expr) => {
+                match $expr.reader {
+                    reader if $expr.saved_pointer => Operand::Saved(reader),
+                    0 => Operand::Constant(intern(&mut program, $value)),
+                    reader => Operand::Read(intern(&mut program, $expr.value.clone()), reader),
+                }
+            };
+        }
+
+        // labels are only resolved if every key that can get written to is known:
+        // the pointer can only be a literal, and so can the keys of `[<]`, `=>` and `<=>`
+        let mut written = vec![Value::Null];
+        let mut dynamic = false;
+        for statement in statements {
+            match &statement.kind {
+                StatementKind::Load(expr)|StatementKind::Push(expr)|StatementKind::Overwrite(expr)|StatementKind::Swap(expr) => match expr.literal() {
+                    Some(value) => {
+                        let mut compressed = value.clone();
+                        compress_value(&mut compressed);
+                        written.extend([value.clone(), compressed]);
+                    },
+                    None => dynamic = true,
+                },
+                StatementKind::Define(expr) => dynamic |= expr.literal().is_none(),
+                _ => {},
+            }
+        }
+
+        let mut defines = AHashMap::<&Value, Vec<usize>>::new();
+        for (index, statement) in statements.iter().enumerate() {
+            if let StatementKind::Define(expr) = &statement.kind {
+                if let Some(label) = expr.literal() {
+                    defines.entry(label).or_default().push(index);
+                }
+            }
+        }
+
+        let mut slots = AHashMap::<&Value, u32>::new();
+        if !dynamic {
+            for (label, indexes) in &defines {
+                if let ([index], false, false) = (&indexes[..], written.contains(label), matches!(label, Value::Time(_))) {
+                    let key = intern(&mut program, (*label).clone());
+                    program.labels.push(Label { key, address: index + 1, state: LabelState::Undefined });
+                    slots.insert(label, program.labels.len() as u32 - 1);
+                }
+            }
+        }
+        let slot = |expr: &crate::ast::Expression| expr.literal().and_then(|label| slots.get(label).copied());
+
+        for (index, statement) in statements.iter().enumerate() {
+            let instruction = match &statement.kind {
+                StatementKind::Load(expr) => {
+                    let mut pointer = expr.value.clone();
+                    compress_value(&mut pointer);
+                    Instruction::Load {
+                        pointer: operand!(expr, pointer),
+                        fuse: !matches!(statements.get(index + 1).map(|statement| &statement.kind), None|Some(StatementKind::Load(_))),
+                    }
+                },
+                StatementKind::Push(expr) => {
+                    let mut pointer = expr.value.clone();
+                    compress_value(&mut pointer);
+                    Instruction::Push(operand!(expr, pointer))
+                },
+                StatementKind::Pop => Instruction::Pop,
+                StatementKind::Exchange => Instruction::Exchange,
+                StatementKind::Define(expr) => Instruction::Define { label: operand!(expr), slot: slot(expr) },
+                StatementKind::Jump(expr) => Instruction::Jump { label: operand!(expr), slot: slot(expr) },
+                StatementKind::Call { expression: expr, scoped } => Instruction::Call { label: operand!(expr), slot: slot(expr), scoped: *scoped },
+                StatementKind::Return => Instruction::Return,
+                StatementKind::Assign(expr) => Instruction::Assign(operand!(expr)),
+                StatementKind::Overwrite(expr) => Instruction::Overwrite(operand!(expr)),
+                StatementKind::Swap(expr) => Instruction::Swap(operand!(expr)),
+                StatementKind::Truthy(quantity) => Instruction::Condition(Condition::Truthy, *quantity),
+                StatementKind::Falsy(quantity) => Instruction::Condition(Condition::Falsy, *quantity),
+                StatementKind::Exists(quantity) => Instruction::Condition(Condition::Exists, *quantity),
+                StatementKind::Empty(quantity) => Instruction::Condition(Condition::Empty, *quantity),
+                StatementKind::Log { .. } => Instruction::Log,
+                StatementKind::Input => Instruction::Input,
+                kind => {
+                    let (operator, expr) = Operator::of(kind).expect("every other statement is an operation");
+                    Instruction::Operation(operator, operand!(expr))
+                },
+            };
+            program.instructions.push(instruction);
+        }
+
+        program
+    }
+
+    /// Updates the resolved labels after the memory or the pointer changed from outside of the program
+    pub fn sync(&mut self, variables: &SpeckyDataContainer<Value>, pointer: &Value, saved_pointers: &[Value]) {
+        for label in &mut self.labels {
+            let key = &self.constants[label.key as usize];
+            label.state = match variables.get(key) {
+                _ if key == pointer || saved_pointers.contains(key) => LabelState::Changed,
+                None => LabelState::Undefined,
+                Some(Value::SmallInt(address)) if *address == label.address as SmallInt => LabelState::Defined,
+                Some(_) => LabelState::Changed,
+            };
+        }
+    }
+}
+
+/// The value of an operand
+#[inline(always)]
+fn read<'a>(constants: &'a [Value], variables: &'a SpeckyDataContainer<Value>, saved_pointers: &'a [Value], operand: Operand) -> &'a Value {
+    match operand {
+        Operand::Constant(constant) => &constants[constant as usize],
+        Operand::Read(constant, reader) => value_reader(variables, &constants[constant as usize], reader),
+        Operand::Saved(reader) => value_reader(variables, saved_pointers.last().unwrap_or(&Value::Null), reader),
+    }
+}
+
+/// The pointer after `|< value`
+#[inline(always)]
+fn load(constants: &[Value], variables: &SpeckyDataContainer<Value>, saved_pointers: &[Value], pointer: Operand) -> Value {
+    match pointer {
+        Operand::Constant(constant) => constants[constant as usize].clone(),
+        operand => {
+            let mut pointer = read(constants, variables, saved_pointers, operand).clone();
+            compress_value(&mut pointer);
+            pointer
+        },
+    }
+}
+
+/// The statement index a jump or call continues from, `None` if it does nothing
+#[inline(always)]
+fn address(constants: &[Value], labels: &[Label], variables: &SpeckyDataContainer<Value>, saved_pointers: &[Value], label: Operand, slot: Option<u32>) -> Option<usize> {
+    match slot.map(|slot| &labels[slot as usize]) {
+        Some(Label { state: LabelState::Undefined, .. }) => None,
+        Some(Label { state: LabelState::Defined, address, .. }) => Some(*address),
+        _ => jump_address(variables.get(read(constants, variables, saved_pointers, label))),
+    }
+}
+
+impl<IO: SpeckyIo> Interpreter<IO> {
+    /// Executes the load at the current statement index if it can run in the same step as the statement after it,
+    /// `false` if there's no such load
+    pub(super) fn fused_load(&mut self) -> bool {
+        let Instruction::Load { pointer, fuse: true } = self.bytecode.instructions[self.statement_index] else {
+            return false
+        };
+        self.current_pointer = load(&self.bytecode.constants, &self.variables, &self.saved_pointers, pointer);
+        self.statement_index += 1;
+        true
+    }
+
+    /// Executes the instruction at the current statement index, like [`Interpreter::execute_statement`]
+    pub(super) fn execute_instruction(&mut self) -> Result<bool, RuntimeErrorKind> {
+        let instruction = self.bytecode.instructions[self.statement_index];
+
+        match instruction {
+            Instruction::Log => return self.log().map(|()| true),
+            Instruction::Input => return self.input(),
+            Instruction::Call { label, slot, scoped } => {
+                let address = address(&self.bytecode.constants, &self.bytecode.labels, &self.variables, &self.saved_pointers, label, slot);
+                return self.call(address, scoped)
+            },
+            Instruction::Return => return Ok(self.return_from_call()),
+            _ => {},
+        }
+
+        let Self {
+            bytecode: Program { constants, labels, .. },
+            variables,
+            current_pointer,
+            saved_pointers,
+            statement_index,
+            error_policy,
+            limits,
+            memory_bytes,
+            clock,
+            created,
+            wall_clock,
+            steps,
+            ..
+        } = self;
+
+        macro_rules! operand {
+            ($operand:expr) => {
+                read(constants, variables, saved_pointers, $operand)
+            };
+        }
+
+        macro_rules! store {
+            ($key:expr, $value:expr $(,)?) => {
+                store(variables, memory_bytes, $key, $value)
+            };
+        }
+
+        match instruction {
+            Instruction::Load { pointer, .. } => *current_pointer = load(constants, variables, saved_pointers, pointer),
+            Instruction::Push(pointer) => {
+                let pointer = load(constants, variables, saved_pointers, pointer);
+                saved_pointers.push(std::mem::replace(current_pointer, pointer));
+            },
+            Instruction::Pop => {
+                if let Some(pointer) = saved_pointers.pop() {
+                    *current_pointer = pointer;
+                }
+            },
+            Instruction::Exchange => {
+                if let Some(saved) = saved_pointers.last_mut() {
+                    std::mem::swap(current_pointer, saved);
+                }
+            },
+            Instruction::Define { label, slot } => {
+                store!(operand!(label).clone(), Value::SmallInt(*statement_index as SmallInt + 1));
+                if let Some(label) = slot.map(|slot| &mut labels[slot as usize]) {
+                    if label.state == LabelState::Undefined {
+                        label.state = LabelState::Defined;
+                    }
+                }
+            },
+            Instruction::Jump { label, slot } => {
+                if let Some(address) = address(constants, labels, variables, saved_pointers, label, slot) {
+                    *statement_index = address;
+                    return Ok(false)
+                }
+            },
+            Instruction::Assign(value) => store!(current_pointer.clone(), timed(operand!(value), || clock.now(*created, *steps))),
+            Instruction::Overwrite(key) => store!(timed(operand!(key), || clock.now(*created, *steps)), current_pointer.clone()),
+            Instruction::Swap(key) => swap(variables, memory_bytes, current_pointer, operand!(key).clone()),
+            Instruction::Operation(operator, right) => {
+                // small integers are changed in place, they don't count towards the memory size
+                if let &Value::SmallInt(right) = operand!(right) {
+                    if let Some(slot @ Value::SmallInt(_)) = variables.get_mut(current_pointer) {
+                        let Value::SmallInt(left) = *slot else { unreachable!() };
+                        let result = match operator {
+                            Operator::Plus => left.checked_add(right).map(Value::SmallInt),
+                            Operator::Minus => left.checked_sub(right).map(Value::SmallInt),
+                            Operator::Times => left.checked_mul(right).map(Value::SmallInt),
+                            Operator::BitAnd => Some(Value::SmallInt(left & right)),
+                            Operator::BitOr => Some(Value::SmallInt(left | right)),
+                            Operator::BitXor => Some(Value::SmallInt(left ^ right)),
+                            Operator::Equal => Some(Value::Boolean(left == right)),
+                            Operator::Unequal => Some(Value::Boolean(left != right)),
+                            Operator::LessThan => Some(Value::Boolean(left < right)),
+                            Operator::LessThanOrEqual => Some(Value::Boolean(left <= right)),
+                            Operator::GreaterThan => Some(Value::Boolean(left > right)),
+                            Operator::GreaterThanOrEqual => Some(Value::Boolean(left >= right)),
+                            _ => None,
+                        };
+                        if let Some(result) = result {
+                            *slot = result;
+                            return Ok(true)
+                        }
+                    }
+                }
+
+                let right = timed(operand!(right), || clock.now(*created, *steps));
+                let left = variables.get(current_pointer).unwrap_or(&Value::Null).clone();
+                let result = operate_with(operator, left, right, limits.max_memory_bytes, *wall_clock, *error_policy)?;
+                store!(current_pointer.clone(), result);
+            },
+            Instruction::Condition(condition, quantity) => {
+                if !condition.holds(variables.get(current_pointer).unwrap_or(&Value::Null)) {
+                    *statement_index += quantity;
+                }
+            },
+            Instruction::Log|Instruction::Input|Instruction::Call { .. }|Instruction::Return => unreachable!("executed above"),
+        }
+
+        Ok(true)
+    }
+}
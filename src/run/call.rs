@@ -0,0 +1,75 @@
+use crate::ast::Value;
+
+use super::{Interpreter, Limit, RuntimeErrorKind, SpeckyDataContainer, SpeckyIo};
+
+/// A `[>:] label` that hasn't returned yet
+#[derive(Debug, Clone)]
+pub(super) struct Frame {
+    /// Statement index of the call, the return continues after it
+    pub call: usize,
+    /// The memory before a scoped call (`[~>:] label`), put back by the return
+    saved: Option<SpeckyDataContainer<Value>>,
+    /// Size of the saved memory, like the memory limits count it
+    saved_bytes: usize,
+}
+
+impl Frame {
+    pub fn new(call: usize, scoped: bool, variables: &SpeckyDataContainer<Value>, memory_bytes: usize) -> Self {
+        Self {
+            call,
+            saved: scoped.then(|| variables.clone()),
+            saved_bytes: if scoped { memory_bytes } else { 0 },
+        }
+    }
+
+    /// Entries and bytes of the memory that the frame keeps for its return
+    pub fn saved(&self) -> (usize, usize) {
+        (self.saved.as_ref().map_or(0, |saved| saved.len()), self.saved_bytes)
+    }
+
+    /// Puts back the memory saved by a scoped call, except for the value at the pointer
+    /// (the result of the call). Returns whether the memory got replaced.
+    pub fn restore(self, variables: &mut SpeckyDataContainer<Value>, pointer: &Value) -> bool {
+        let Some(mut saved) = self.saved else { return false };
+        if let Some(result) = variables.remove(pointer) {
+            saved.insert(pointer.clone(), result);
+        }
+        *variables = saved;
+        true
+    }
+}
+
+impl<IO: SpeckyIo> Interpreter<IO> {
+    /// Executes a call to `address` (`None` if the label isn't defined, then nothing happens),
+    /// returns whether the statement index should move to the next statement
+    pub(super) fn call(&mut self, address: Option<usize>, scoped: bool) -> Result<bool, RuntimeErrorKind> {
+        let Some(address) = address else { return Ok(true) };
+
+        if let Some(max_call_depth) = self.limits.max_call_depth {
+            if self.call_stack.len() >= max_call_depth {
+                return Err(RuntimeErrorKind::LimitExceeded(Limit::CallDepth(max_call_depth)))
+            }
+        }
+
+        let frame = Frame::new(self.statement_index, scoped, &self.variables, self.memory_bytes);
+        let (entries, bytes) = frame.saved();
+        self.saved_memory = (self.saved_memory.0 + entries, self.saved_memory.1 + bytes);
+        self.call_stack.push(frame);
+        self.statement_index = address;
+        Ok(false)
+    }
+
+    /// Executes `[<:]`, going back to the statement after the last call.
+    /// Without calls nothing happens, so functions can be written where they are first used.
+    pub(super) fn return_from_call(&mut self) -> bool {
+        let Some(frame) = self.call_stack.pop() else { return true };
+        let (entries, bytes) = frame.saved();
+        self.saved_memory = (self.saved_memory.0 - entries, self.saved_memory.1 - bytes);
+
+        self.statement_index = frame.call + 1;
+        if frame.restore(&mut self.variables, &self.current_pointer) {
+            self.memory_bytes_outdated = true;
+        }
+        false
+    }
+}
@@ -0,0 +1,107 @@
+use std::{str::FromStr, time::{Duration, Instant, SystemTime, UNIX_EPOCH}};
+
+/// Virtual clocks stop moving after this long, not every platform can hold instants much further away
+pub const MAX_VIRTUAL_TIME: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+/// Where `µ` gets the time from
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Clock {
+    /// The system clock
+    #[default]
+    Real,
+    /// Time stands still where the interpreter got created, so times always read zero
+    /// and programs that use `µ` print the same on every run
+    Fixed,
+    /// Time only moves by this much for every executed statement, deterministic like
+    /// [`Clock::Fixed`] but loops that wait for some time to pass still end.
+    /// It stops after [`MAX_VIRTUAL_TIME`].
+    Step(Duration),
+}
+
+impl Clock {
+    /// The current time of a program that started at `start` and executed `steps` statements
+    #[inline(always)]
+    pub(crate) fn now(self, start: Instant, steps: u64) -> Instant {
+        match self {
+            Clock::Real => Instant::now(),
+            Clock::Fixed => start,
+            Clock::Step(step) => {
+                let passed = duration_from_nanos(step.as_nanos().saturating_mul(steps as u128)).unwrap_or(Duration::MAX);
+                start.checked_add(passed.min(MAX_VIRTUAL_TIME)).unwrap_or(start)
+            },
+        }
+    }
+
+    /// The wall clock of a program that started at `start`, virtual clocks start at the Unix epoch
+    /// so dates print the same on every run
+    pub(crate) fn wall_clock(self, start: Instant) -> WallClock {
+        match self {
+            Clock::Real => WallClock::system(),
+            Clock::Fixed | Clock::Step(_) => WallClock { instant: start, system: UNIX_EPOCH },
+        }
+    }
+}
+
+/// Turns the instants `µ` reads into wall-clock times
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WallClock {
+    instant: Instant,
+    system: SystemTime,
+}
+
+impl WallClock {
+    /// The clock of the system
+    pub fn system() -> Self {
+        WallClock { instant: Instant::now(), system: SystemTime::now() }
+    }
+
+    /// The wall-clock time of `instant`, `None` if the system can't represent it
+    pub fn at(self, instant: Instant) -> Option<SystemTime> {
+        match instant.checked_duration_since(self.instant) {
+            Some(after) => self.system.checked_add(after),
+            None => self.system.checked_sub(self.instant - instant),
+        }
+    }
+}
+
+/// `None` if it doesn't fit in a [`Duration`]
+pub(crate) fn duration_from_nanos(nanos: u128) -> Option<Duration> {
+    let seconds = u64::try_from(nanos / 1_000_000_000).ok()?;
+    Some(Duration::new(seconds, (nanos % 1_000_000_000) as u32))
+}
+
+/// `real`, `fixed` or `step:<duration>`, where the duration is a number followed by `ns`, `us`, `µs`, `ms` or `s`
+/// and at most [`MAX_VIRTUAL_TIME`]
+impl FromStr for Clock {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "real" => Ok(Clock::Real),
+            "fixed" => Ok(Clock::Fixed),
+            other => match other.strip_prefix("step:") {
+                Some(step) => match parse_duration(step)? {
+                    step if step > MAX_VIRTUAL_TIME => Err(format!("step '{other}' is longer than {}s", MAX_VIRTUAL_TIME.as_secs())),
+                    step => Ok(Clock::Step(step)),
+                },
+                None => Err(format!("unknown clock '{other}' (expected 'real', 'fixed' or 'step:<duration>')")),
+            },
+        }
+    }
+}
+
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let split = duration.find(|c: char| !c.is_ascii_digit() && c != '.').unwrap_or(duration.len());
+    let (number, unit) = duration.split_at(split);
+    let scale = match unit {
+        "ns" => 1e-9,
+        "us" | "µs" => 1e-6,
+        "ms" => 1e-3,
+        "s" => 1.0,
+        _ => return Err(format!("invalid duration '{duration}' (expected a number followed by ns, us, ms or s)")),
+    };
+    number.parse::<f64>()
+        .ok()
+        .and_then(|number| Duration::try_from_secs_f64(number * scale).ok())
+        .ok_or_else(|| format!("invalid duration '{duration}'"))
+}
@@ -0,0 +1,116 @@
+use std::{fmt, str::FromStr};
+
+use ariadne::{Color, Report, ReportKind};
+
+use crate::parser::error::{report_config, CodeArea};
+
+use super::limits::Limit;
+
+pub type RunResult<T> = Result<T, RuntimeError>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuntimeErrorKind {
+    DivisionByZero,
+    NegativeExponent,
+    ExponentTooLarge,
+    ShiftTooLarge,
+    /// A text, list or integer that wouldn't fit in memory, when there's no memory limit to go over
+    ValueTooLarge,
+    LimitExceeded(Limit),
+    Io(String),
+}
+
+impl RuntimeErrorKind {
+    /// Errors that stop the program regardless of the [`ErrorPolicy`]
+    pub fn always_aborts(&self) -> bool {
+        matches!(self, RuntimeErrorKind::LimitExceeded(_) | RuntimeErrorKind::Io(_))
+    }
+}
+
+impl fmt::Display for RuntimeErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuntimeErrorKind::DivisionByZero => write!(f, "Division by zero"),
+            RuntimeErrorKind::NegativeExponent => write!(f, "Integer raised to a negative exponent"),
+            RuntimeErrorKind::ExponentTooLarge => write!(f, "Exponent is too large"),
+            RuntimeErrorKind::ShiftTooLarge => write!(f, "Shift is too large"),
+            RuntimeErrorKind::ValueTooLarge => write!(f, "Value is too large"),
+            RuntimeErrorKind::LimitExceeded(limit) => write!(f, "Limit exceeded: {limit}"),
+            RuntimeErrorKind::Io(error) => write!(f, "IO error: {error}"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeError {
+    pub kind: RuntimeErrorKind,
+    pub statement_index: usize,
+    pub area: CodeArea,
+    /// The calls that haven't returned yet, outermost first
+    pub stack: Vec<CallSite>,
+}
+
+/// A `[>:] label` statement in the stack trace of a [`RuntimeError`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CallSite {
+    pub statement_index: usize,
+    pub area: CodeArea,
+}
+
+/// At most this many calls are listed in a stack trace, the innermost ones
+const STACK_TRACE_LENGTH: usize = 10;
+
+/// What happens when an operation fails
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorPolicy {
+    /// Stop the program with a [`RuntimeError`]
+    #[default]
+    Abort,
+    /// Store `null` as the result of the operation and keep going
+    /// (io errors and exceeded limits still abort)
+    Null,
+}
+
+impl FromStr for ErrorPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "abort" => Ok(ErrorPolicy::Abort),
+            "null" => Ok(ErrorPolicy::Null),
+            other => Err(format!("unknown error policy '{other}' (expected 'abort' or 'null')")),
+        }
+    }
+}
+
+/// The calls of a stack trace, innermost first: `called from statement 12 (line 4)`
+fn stack_trace(code: &str, stack: &[CallSite]) -> String {
+    let mut trace = stack.iter().rev()
+        .take(STACK_TRACE_LENGTH)
+        .map(|call| format!("called from statement {} (line {})", call.statement_index, call.area.line(code)))
+        .collect::<Vec<_>>();
+    if stack.len() > STACK_TRACE_LENGTH {
+        trace.push(format!("... and {} more calls", stack.len() - STACK_TRACE_LENGTH));
+    }
+    trace.join("\n")
+}
+
+pub fn print_error(code: &str, error: &RuntimeError) {
+    let title = format!("{} (statement {})", error.kind, error.statement_index);
+
+    let mut report = Report::build(ReportKind::Error, error.area)
+        .with_config(report_config())
+        .with_message(&title)
+        .with_label(
+            ariadne::Label::new(error.area)
+                .with_message(&error.kind)
+                .with_color(Color::Red),
+        );
+    if !error.stack.is_empty() {
+        report = report.with_note(stack_trace(code, &error.stack));
+    }
+    let report = report.finish();
+
+    report.print(ariadne::sources([("code", code)]))
+        .expect("Failed to print error report");
+}
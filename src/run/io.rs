@@ -0,0 +1,101 @@
+use std::collections::VecDeque;
+use std::io::{self, BufWriter, Stdout, Write};
+
+/// Where `{...}` log statements write to and `'` input statements read from
+pub trait SpeckyIo {
+    fn write_str(&mut self, string: &str) -> io::Result<()>;
+    fn flush(&mut self) -> io::Result<()>;
+    /// Reads the next line of input, `None` means that there's nothing to read yet
+    /// and the interpreter will wait with [`Status::WaitingForInput`](super::Status::WaitingForInput)
+    fn read_line(&mut self) -> Option<String>;
+}
+
+/// Buffered stdout and blocking stdin
+#[derive(Debug)]
+pub struct StdIo {
+    writer: BufWriter<Stdout>,
+}
+
+impl Default for StdIo {
+    fn default() -> Self {
+        Self {
+            writer: BufWriter::new(io::stdout()),
+        }
+    }
+}
+
+impl SpeckyIo for StdIo {
+    fn write_str(&mut self, string: &str) -> io::Result<()> {
+        self.writer.write_all(string.as_bytes())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        let _ = self.writer.flush();
+        let mut s = String::new();
+        io::stdin().read_line(&mut s).unwrap_or(0);
+        Some(s)
+    }
+}
+
+/// Writes into a string and reads from a queue of scripted lines
+#[derive(Debug, Default, Clone)]
+pub struct BufferIo {
+    pub output: String,
+    pub input: VecDeque<String>,
+}
+
+impl BufferIo {
+    pub fn with_input<I: IntoIterator<Item = S>, S: Into<String>>(input: I) -> Self {
+        Self {
+            output: String::new(),
+            input: input.into_iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl SpeckyIo for BufferIo {
+    fn write_str(&mut self, string: &str) -> io::Result<()> {
+        self.output.push_str(string);
+        Ok(())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        self.input.pop_front()
+    }
+}
+
+impl<T: SpeckyIo + ?Sized> SpeckyIo for &mut T {
+    fn write_str(&mut self, string: &str) -> io::Result<()> {
+        (**self).write_str(string)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        (**self).read_line()
+    }
+}
+
+impl<T: SpeckyIo + ?Sized> SpeckyIo for Box<T> {
+    fn write_str(&mut self, string: &str) -> io::Result<()> {
+        (**self).write_str(string)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+    fn read_line(&mut self) -> Option<String> {
+        (**self).read_line()
+    }
+}
@@ -0,0 +1,38 @@
+use std::{fmt, time::Duration};
+
+/// Bounds for running untrusted programs, `None` means unlimited
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Limits {
+    /// Executed statements
+    pub max_steps: Option<u64>,
+    /// Time since the first executed statement, or since [`Interpreter::restart_timeout`](super::Interpreter::restart_timeout)
+    pub timeout: Option<Duration>,
+    /// Keys in memory, with the ones that scoped calls keep for their returns
+    pub max_memory_entries: Option<usize>,
+    /// Total size of the texts, symbols and big integers in memory, with the ones that scoped calls keep
+    pub max_memory_bytes: Option<usize>,
+    /// Calls that haven't returned yet
+    pub max_call_depth: Option<usize>,
+}
+
+/// The limit that stopped a program, with its configured value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Steps(u64),
+    Timeout(Duration),
+    MemoryEntries(usize),
+    MemoryBytes(usize),
+    CallDepth(usize),
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::Steps(steps) => write!(f, "more than {steps} statements executed"),
+            Limit::Timeout(timeout) => write!(f, "running for more than {timeout:?}"),
+            Limit::MemoryEntries(entries) => write!(f, "more than {entries} memory entries"),
+            Limit::MemoryBytes(bytes) => write!(f, "more than {bytes} bytes of memory"),
+            Limit::CallDepth(depth) => write!(f, "more than {depth} nested calls"),
+        }
+    }
+}
@@ -0,0 +1,446 @@
+mod value;
+mod statement;
+mod operation;
+mod bytecode;
+mod runtime;
+mod call;
+pub mod io;
+pub mod error;
+pub mod profile;
+pub mod limits;
+pub mod clock;
+
+use std::time::{Instant, Duration};
+
+use ahash::AHashMap;
+
+pub use self::value::{memory_to_string, value_to_string, value_to_string_at};
+pub use self::io::{BufferIo, SpeckyIo, StdIo};
+pub use self::error::{CallSite, ErrorPolicy, RunResult, RuntimeError, RuntimeErrorKind};
+pub use self::profile::{Profile, StatementProfile};
+pub use self::limits::{Limit, Limits};
+pub use self::clock::{Clock, WallClock};
+pub use self::bytecode::Engine;
+pub use self::operation::Operator;
+pub use self::runtime::Runtime;
+
+pub type SpeckyDataContainer<V> = AHashMap<Value, V>;
+
+use crate::ast::{Expression, Statements, Value};
+
+use self::bytecode::Program;
+use self::call::Frame;
+
+const NULL: Value = Value::Null;
+
+#[derive(Debug)]
+pub struct RunOutput {
+    pub stdout: String,
+    pub variables: SpeckyDataContainer<Value>,
+    pub error: Option<RuntimeError>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    /// There are statements left to execute
+    Running,
+    /// The statement index went past the end of the program
+    Halted,
+    /// An input statement found nothing to read, see [`Interpreter::provide_input`]
+    WaitingForInput,
+}
+
+/// Executes a program one statement at a time, keeping memory, pointer
+/// and statement index between calls.
+#[derive(Debug)]
+pub struct Interpreter<IO: SpeckyIo = StdIo> {
+    program: Statements,
+    bytecode: Program,
+    engine: Engine,
+    variables: SpeckyDataContainer<Value>,
+    current_pointer: Value,
+    saved_pointers: Vec<Value>,
+    statement_index: usize,
+    call_stack: Vec<Frame>,
+    status: Status,
+
+    io: IO,
+    output: String,
+    capture_output: bool,
+    output_updated: bool,
+    last_flush: Instant,
+
+    pending_input: Option<String>,
+    error_policy: ErrorPolicy,
+
+    max_time: (Duration, usize),
+    profile: Option<Profile>,
+
+    limits: Limits,
+    steps: u64,
+    started: Option<Instant>,
+    memory_bytes: usize,
+    // set by `memory_mut` and scoped returns, since the size of the memory can't be tracked there
+    memory_bytes_outdated: bool,
+    /// Entries and bytes of the memory that scoped calls keep for their returns
+    saved_memory: (usize, usize),
+
+    clock: Clock,
+    created: Instant,
+    wall_clock: WallClock,
+}
+
+impl Interpreter {
+    /// Creates an interpreter that uses stdout and stdin
+    pub fn new(program: Statements) -> Self {
+        Self::with_io(program, StdIo::default())
+    }
+}
+
+impl<IO: SpeckyIo> Interpreter<IO> {
+    pub fn with_io(program: Statements, io: IO) -> Self {
+        let mut interpreter = Self {
+            bytecode: Program::compile(&program),
+            engine: Engine::default(),
+            program,
+            variables: SpeckyDataContainer::default(),
+            current_pointer: Value::Null,
+            saved_pointers: vec![],
+            statement_index: 0,
+            call_stack: vec![],
+            status: Status::Running,
+
+            io,
+            output: String::new(),
+            capture_output: true,
+            output_updated: false,
+            last_flush: Instant::now(),
+
+            pending_input: None,
+            error_policy: ErrorPolicy::default(),
+
+            max_time: (Duration::ZERO, 0),
+            profile: None,
+
+            limits: Limits::default(),
+            steps: 0,
+            started: None,
+            memory_bytes: 0,
+            memory_bytes_outdated: false,
+            saved_memory: (0, 0),
+
+            clock: Clock::default(),
+            created: Instant::now(),
+            wall_clock: WallClock::system(),
+        };
+        interpreter.update_status();
+        interpreter
+    }
+
+    /// Executes a single statement.
+    /// On errors the statement index stays on the failing statement.
+    pub fn step(&mut self) -> RunResult<Status> {
+        self.step_with(false)
+    }
+
+    /// Like [`Interpreter::step`], with `fuse` the bytecode can execute two statements at once
+    fn step_with(&mut self, fuse: bool) -> RunResult<Status> {
+        match self.status {
+            Status::Halted => return Ok(self.status),
+            Status::WaitingForInput if self.pending_input.is_none() => return Ok(self.status),
+            _ => self.status = Status::Running,
+        }
+
+        if self.memory_bytes_outdated {
+            self.memory_bytes = value::memory_bytes(&self.variables);
+            self.memory_bytes_outdated = false;
+            self.bytecode.sync(&self.variables, &self.current_pointer, &self.saved_pointers);
+        }
+
+        let mut start_operation = Instant::now();
+
+        // a load that the bytecode fuses with the next statement gets checked and counted on its own,
+        // the statement after it goes through the checks below like any other
+        if fuse && self.engine == Engine::Bytecode && self.check_time_limits(start_operation).is_ok() && self.fused_load() {
+            self.record_step(self.statement_index - 1, true, start_operation);
+            start_operation = Instant::now();
+        }
+
+        let statement_index = self.statement_index;
+
+        let next_statement = match self.check_time_limits(start_operation)
+            .and_then(|()| match self.engine {
+                Engine::Ast => self.execute_statement(),
+                Engine::Bytecode => self.execute_instruction(),
+            })
+            .and_then(|next_statement| self.check_memory_limits().map(|()| next_statement))
+        {
+            Ok(next_statement) => next_statement,
+            Err(kind) => {
+                self.flush();
+                return Err(RuntimeError {
+                    kind,
+                    statement_index: self.statement_index,
+                    area: self.program[self.statement_index].area,
+                    stack: self.call_stack()
+                        .map(|statement_index| CallSite { statement_index, area: self.program[statement_index].area })
+                        .collect(),
+                })
+            },
+        };
+
+        self.record_step(statement_index, next_statement, start_operation);
+
+        if self.output_updated && self.last_flush.elapsed() > Duration::from_millis(50) {
+            self.flush();
+        }
+
+        if next_statement {
+            self.statement_index += 1;
+        }
+
+        self.update_status();
+        Ok(self.status)
+    }
+
+    /// Counts the executed statement at `statement_index`, with how long it took for the slowest statement and the profile
+    fn record_step(&mut self, statement_index: usize, next_statement: bool, start_operation: Instant) {
+        self.steps += 1;
+
+        let elapsed = start_operation.elapsed();
+
+        if elapsed > self.max_time.0 {
+            self.max_time = (elapsed, statement_index)
+        }
+
+        // an input statement that is still waiting didn't really execute
+        if let (Some(profile), Status::Running) = (&mut self.profile, self.status) {
+            profile.record(statement_index, &self.program[statement_index], !next_statement, elapsed, &self.variables, &self.saved_pointers);
+        }
+    }
+
+    /// Executes at most `n_steps` statements
+    pub fn run_until(&mut self, n_steps: usize) -> RunResult<Status> {
+        for _ in 0..n_steps {
+            if self.step()? != Status::Running { break }
+        }
+        self.flush();
+        Ok(self.status)
+    }
+
+    /// Executes statements until the program halts or waits for input
+    pub fn run_to_end(&mut self) -> RunResult<Status> {
+        while self.step_with(true)? == Status::Running {}
+        self.flush();
+        Ok(self.status)
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    pub fn program(&self) -> &Statements {
+        &self.program
+    }
+
+    /// Appends statements to the program, a halted interpreter will continue with them
+    pub fn extend_program(&mut self, statements: Statements) {
+        self.program.extend(statements);
+        self.bytecode = Program::compile(&self.program);
+        self.bytecode.sync(&self.variables, &self.current_pointer, &self.saved_pointers);
+        self.update_status();
+    }
+
+    pub fn memory(&self) -> &SpeckyDataContainer<Value> {
+        &self.variables
+    }
+
+    pub fn memory_mut(&mut self) -> &mut SpeckyDataContainer<Value> {
+        self.memory_bytes_outdated = true;
+        &mut self.variables
+    }
+
+    pub fn pointer(&self) -> &Value {
+        &self.current_pointer
+    }
+
+    /// Reads `value` through `reader` levels of memory, like a `§§value` expression
+    pub fn read<'a>(&'a self, value: &'a Value, reader: usize) -> &'a Value {
+        value::value_reader(&self.variables, value, reader)
+    }
+
+    /// What an expression reads, `@` is the last of [`Interpreter::saved_pointers`]
+    pub fn evaluate<'a>(&'a self, expression: &'a Expression) -> &'a Value {
+        value::expression_value(&self.variables, &self.saved_pointers, expression)
+    }
+
+    pub fn set_pointer(&mut self, pointer: Value) {
+        self.current_pointer = pointer;
+        self.bytecode.sync(&self.variables, &self.current_pointer, &self.saved_pointers);
+    }
+
+    /// Pointers saved by `|>` that haven't been popped yet, the last one gets popped first
+    pub fn saved_pointers(&self) -> &[Value] {
+        &self.saved_pointers
+    }
+
+    /// Index of the next statement to execute
+    pub fn program_counter(&self) -> usize {
+        self.statement_index
+    }
+
+    pub fn set_program_counter(&mut self, statement_index: usize) {
+        self.statement_index = statement_index;
+        self.update_status();
+    }
+
+    /// Statement indexes of the calls that haven't returned yet, outermost first
+    pub fn call_stack(&self) -> impl Iterator<Item = usize> + '_ {
+        self.call_stack.iter().map(|frame| frame.call)
+    }
+
+    /// Everything that got logged so far (empty if the capture is disabled)
+    pub fn stdout(&self) -> &str {
+        &self.output
+    }
+
+    /// The slowest statement executed so far and how long it took
+    pub fn max_time(&self) -> (Duration, usize) {
+        self.max_time
+    }
+
+    /// Starts (or stops) collecting a [`Profile`] of the executed statements
+    pub fn set_profiling(&mut self, profiling: bool) {
+        self.profile = profiling.then(Profile::default);
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Switches between walking the syntax tree and running the compiled bytecode, both behave the same
+    pub fn set_engine(&mut self, engine: Engine) {
+        self.engine = engine;
+    }
+
+    pub fn engine(&self) -> Engine {
+        self.engine
+    }
+
+    pub fn set_error_policy(&mut self, error_policy: ErrorPolicy) {
+        self.error_policy = error_policy;
+    }
+
+    /// Stops the program with [`RuntimeErrorKind::LimitExceeded`] once one of the limits is hit
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Changes where `µ` gets the time from, see [`Clock`]
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+        self.wall_clock = clock.wall_clock(self.created);
+    }
+
+    pub fn clock(&self) -> Clock {
+        self.clock
+    }
+
+    /// The time `µ` reads right now
+    pub fn now(&self) -> Instant {
+        self.clock.now(self.created, self.steps)
+    }
+
+    /// How many statements got executed so far
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// The timeout counts from the next executed statement again, like for a new program (the repl does it for every input)
+    pub fn restart_timeout(&mut self) {
+        self.started = None;
+    }
+
+    /// Whether logged output is also kept in [`Interpreter::stdout`] and [`RunOutput::stdout`]
+    pub fn set_capture_output(&mut self, capture_output: bool) {
+        self.capture_output = capture_output;
+    }
+
+    pub fn io(&self) -> &IO {
+        &self.io
+    }
+
+    pub fn io_mut(&mut self) -> &mut IO {
+        &mut self.io
+    }
+
+    /// Gives the next input statement this line, before asking the io backend
+    pub fn provide_input(&mut self, line: impl Into<String>) {
+        self.pending_input = Some(line.into());
+    }
+
+    pub fn flush(&mut self) {
+        self.io.flush().unwrap();
+        self.last_flush = Instant::now();
+        self.output_updated = false;
+    }
+
+    pub fn into_output(mut self, error: Option<RuntimeError>) -> RunOutput {
+        self.flush();
+        RunOutput {
+            stdout: self.output,
+            variables: self.variables,
+            error,
+        }
+    }
+
+    fn check_time_limits(&mut self, now: Instant) -> Result<(), RuntimeErrorKind> {
+        let started = *self.started.get_or_insert(now);
+        match self.limits {
+            Limits { max_steps: Some(max_steps), .. } if self.steps >= max_steps => {
+                Err(RuntimeErrorKind::LimitExceeded(Limit::Steps(max_steps)))
+            },
+            Limits { timeout: Some(timeout), .. } if now - started > timeout => {
+                Err(RuntimeErrorKind::LimitExceeded(Limit::Timeout(timeout)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn check_memory_limits(&self) -> Result<(), RuntimeErrorKind> {
+        match self.limits {
+            Limits { max_memory_entries: Some(entries), .. } if self.variables.len() + self.saved_memory.0 > entries => {
+                Err(RuntimeErrorKind::LimitExceeded(Limit::MemoryEntries(entries)))
+            },
+            Limits { max_memory_bytes: Some(bytes), .. } if self.memory_bytes + self.saved_memory.1 > bytes => {
+                Err(RuntimeErrorKind::LimitExceeded(Limit::MemoryBytes(bytes)))
+            },
+            _ => Ok(()),
+        }
+    }
+
+    fn update_status(&mut self) {
+        if self.statement_index >= self.program.len() {
+            if self.status != Status::Halted {
+                self.flush();
+            }
+            self.status = Status::Halted;
+        } else if self.status == Status::Halted {
+            self.status = Status::Running;
+        }
+    }
+}
+
+pub fn run(parsed: &Statements) -> RunOutput {
+    run_with_io(parsed, StdIo::default())
+}
+
+pub fn run_with_io<IO: SpeckyIo>(parsed: &Statements, io: IO) -> RunOutput {
+    let mut interpreter = Interpreter::with_io(parsed.clone(), io);
+    let error = interpreter.run_to_end().err();
+    interpreter.into_output(error)
+}
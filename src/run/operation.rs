@@ -0,0 +1,695 @@
+use std::{cmp::Ordering, time::{Duration, Instant, UNIX_EPOCH}};
+
+use num_bigfloat::BigFloat;
+use num_bigint::{BigInt, Sign};
+
+use crate::ast::{Expression, Integer, LogKind, LogMemory, LogValue, StatementKind, Symbol, Value};
+
+use super::{ErrorPolicy, Limit, RuntimeErrorKind, SpeckyDataContainer, WallClock, NULL, clock::duration_from_nanos, value::*};
+
+/// Statements that combine the value at the pointer with their expression and store the result there
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Index,
+    SetIndex,
+    Property,
+    And,
+    Or,
+    Xor,
+    BitAnd,
+    BitOr,
+    BitXor,
+    BitNot,
+    ShiftLeft,
+    ShiftRight,
+    Plus,
+    Minus,
+    Times,
+    Divide,
+    Modulo,
+    PPercent,
+    Exponential,
+    Unequal,
+    Equal,
+    LessThan,
+    LessThanOrEqual,
+    GreaterThan,
+    GreaterThanOrEqual,
+}
+
+impl Operator {
+    pub fn of(kind: &StatementKind) -> Option<(Operator, &Expression)> {
+        use StatementKind as S;
+        Some(match kind {
+            S::Index(expr) => (Operator::Index, expr),
+            S::SetIndex(expr) => (Operator::SetIndex, expr),
+            S::Property(expr) => (Operator::Property, expr),
+            S::And(expr) => (Operator::And, expr),
+            S::Or(expr) => (Operator::Or, expr),
+            S::Xor(expr) => (Operator::Xor, expr),
+            S::BitAnd(expr) => (Operator::BitAnd, expr),
+            S::BitOr(expr) => (Operator::BitOr, expr),
+            S::BitXor(expr) => (Operator::BitXor, expr),
+            S::BitNot(expr) => (Operator::BitNot, expr),
+            S::ShiftLeft(expr) => (Operator::ShiftLeft, expr),
+            S::ShiftRight(expr) => (Operator::ShiftRight, expr),
+            S::Plus(expr) => (Operator::Plus, expr),
+            S::Minus(expr) => (Operator::Minus, expr),
+            S::Times(expr) => (Operator::Times, expr),
+            S::Divide(expr) => (Operator::Divide, expr),
+            S::Modulo(expr) => (Operator::Modulo, expr),
+            S::PPercent(expr) => (Operator::PPercent, expr),
+            S::Exponential(expr) => (Operator::Exponential, expr),
+            S::Unequal(expr) => (Operator::Unequal, expr),
+            S::Equal(expr) => (Operator::Equal, expr),
+            S::LessThan(expr) => (Operator::LessThan, expr),
+            S::LessThanOrEqual(expr) => (Operator::LessThanOrEqual, expr),
+            S::GreaterThan(expr) => (Operator::GreaterThan, expr),
+            S::GreaterThanOrEqual(expr) => (Operator::GreaterThanOrEqual, expr),
+            _ => return None,
+        })
+    }
+}
+
+/// Inserts into memory, keeping track of its size
+#[inline(always)]
+pub fn store(variables: &mut SpeckyDataContainer<Value>, memory_bytes: &mut usize, key: Value, value: Value) {
+    let key_bytes = value_bytes(&key);
+    *memory_bytes += value_bytes(&value);
+    match variables.insert(key, value) {
+        Some(old) => *memory_bytes -= value_bytes(&old),
+        None => *memory_bytes += key_bytes,
+    }
+}
+
+/// The statement index stored at a jump address, `None` if the jump does nothing
+#[inline(always)]
+pub fn jump_address(address: Option<&Value>) -> Option<usize> {
+    match address {
+        Some(Value::Integer(index)) if index > &BigInt::ZERO && index < &BigInt::from(usize::MAX) => {
+            Some(*index.to_u64_digits().1.first().unwrap() as usize)
+        },
+        Some(Value::SmallInt(index)) if (0..=usize::MAX as i128).contains(index) => Some(*index as usize),
+        _ => None,
+    }
+}
+
+/// `value` as it gets stored or operated with, `µ` reads the time from `now`
+#[inline(always)]
+pub fn timed(value: &Value, now: impl FnOnce() -> Instant) -> Value {
+    match value {
+        Value::Time(time) => Value::Time(Some(time.unwrap_or_else(now))),
+        rest => rest.clone(),
+    }
+}
+
+/// `<=>`, exchanges the values at `pointer` and at `key`
+#[inline(always)]
+pub fn swap(variables: &mut SpeckyDataContainer<Value>, memory_bytes: &mut usize, pointer: &Value, key: Value) {
+    let temp = variables.get(pointer).unwrap_or(&NULL).clone();
+    store(variables, memory_bytes, pointer.clone(), variables.get(&key).unwrap_or(&NULL).clone());
+    store(variables, memory_bytes, key, temp);
+}
+
+/// [`operate`], the errors that `error_policy` doesn't stop at give `null`
+#[inline(always)]
+pub fn operate_with(operator: Operator, left: Value, right: Value, max_bytes: Option<usize>, wall_clock: WallClock, error_policy: ErrorPolicy) -> Result<Value, RuntimeErrorKind> {
+    match operate(operator, left, right, max_bytes, wall_clock) {
+        Err(kind) if error_policy == ErrorPolicy::Null && !kind.always_aborts() => Ok(Value::Null),
+        result => result,
+    }
+}
+
+/// A `{...}` statement, the text to print or `None` if it got stored at the pointer
+pub fn log(variables: &mut SpeckyDataContainer<Value>, memory_bytes: &mut usize, pointer: &Value, kind: &StatementKind, now: Instant) -> Option<String> {
+    let string = log_text(variables, pointer, kind, now);
+    match kind {
+        StatementKind::Log { assign: true, .. } => {
+            store(variables, memory_bytes, pointer.clone(), string_to_value(&string, now));
+            None
+        },
+        _ => Some(string),
+    }
+}
+
+/// Numbers of different kinds get converted to the bigger kind
+fn promote(left: Value, right: Value) -> (Value, Value) {
+    match (&left, &right) {
+        // SmallInt -> Integer
+        (Value::Integer(_),Value::SmallInt(si)) => (left, Value::Integer(Integer::from(*si))),
+        (Value::SmallInt(si),Value::Integer(_)) => (Value::Integer(Integer::from(*si)), right),
+        // SmallInt -> Float
+        (Value::Float(_),Value::SmallInt(si)) => (left, Value::Float(BigFloat::from(*si))),
+        (Value::SmallInt(si),Value::Float(_)) => (Value::Float(BigFloat::from(*si)), right),
+        // Integer -> Float
+        (Value::Float(_),Value::Integer(bi)) => (left, Value::Float(BigFloat::parse(&bi.to_string()).unwrap())),
+        (Value::Integer(bi),Value::Float(_)) => (Value::Float(BigFloat::parse(&bi.to_string()).unwrap()), right),
+        // Other
+        _ => (left, right),
+    }
+}
+
+/// Applies `operator` with the value at the pointer on the left.
+/// `max_bytes` is the memory limit, no text, list or integer can be created that is bigger than it
+/// (or than `isize::MAX` bytes without a limit), `wall_clock` gives the dates of times.
+pub fn operate(operator: Operator, left: Value, right: Value, max_bytes: Option<usize>, wall_clock: WallClock) -> Result<Value, RuntimeErrorKind> {
+    let (max_bytes, too_large) = match max_bytes {
+        Some(max_bytes) => (max_bytes, RuntimeErrorKind::LimitExceeded(Limit::MemoryBytes(max_bytes))),
+        None => (isize::MAX as usize, RuntimeErrorKind::ValueTooLarge),
+    };
+
+    // the complement only uses its value, which doesn't get converted to the kind of the value at the pointer
+    if operator == Operator::BitNot {
+        return Ok(match right {
+            Value::SmallInt(right) => Value::SmallInt(!right),
+            Value::Integer(right) => compress_integer(!right),
+            _ => Value::Null,
+        })
+    }
+
+    let (left, right) = promote(left, right);
+
+    // texts and symbols are compared (and subtracted) by their text, without copying them
+    if let (Some(left), Some(right)) = (value_text(&left), value_text(&right)) {
+        let result = match operator {
+            Operator::Minus => {
+                let left = Integer::from_bytes_be(Sign::Plus, left.as_bytes());
+                let right = Integer::from_bytes_be(Sign::Plus, right.as_bytes());
+                Some(compress_integer(left - right))
+            },
+            Operator::Unequal => Some(Value::Boolean(left != right)),
+            Operator::Equal => Some(Value::Boolean(left == right)),
+            Operator::LessThan => Some(Value::Boolean(left < right)),
+            Operator::GreaterThan => Some(Value::Boolean(left > right)),
+            Operator::LessThanOrEqual => Some(Value::Boolean(left <= right)),
+            Operator::GreaterThanOrEqual => Some(Value::Boolean(left >= right)),
+            _ => None,
+        };
+        if let Some(result) = result {
+            return Ok(result)
+        }
+    }
+
+    let result = match operator {
+        Operator::Index => match (left, right) {
+            (left @ (Value::Text(_)|Value::Symbol(_)), right) => {
+                let string = value_text(&left).expect("text or symbol");
+                if let Some(ch) = list_index(&right).and_then(|int| string.chars().nth(int)) {
+                    Value::Text(ch.to_string())
+                } else {
+                    Value::Null
+                }
+            },
+            (Value::List(mut list), right) => match list_index(&right) {
+                Some(index) if index < list.len() => list.swap_remove(index),
+                _ => Value::Null,
+            },
+            (Value::Map(mut map), key) => map.remove(&key).unwrap_or(Value::Null),
+            (Value::Duration(duration), Value::Symbol(unit)) => duration_in(duration, unit.as_str()),
+            (Value::Time(Some(time)), Value::Symbol(key)) => time_in(time, key.as_str(), wall_clock),
+            (number @ (Value::Integer(_)|Value::SmallInt(_)|Value::Float(_)), Value::Symbol(unit)) => duration_of(number, unit.as_str()),
+            _ => Value::Null,
+        },
+        Operator::SetIndex => match (left, right) {
+            (Value::List(mut list), Value::List(mut pair)) if pair.len() == 2 => {
+                let value = pair.pop().expect("two elements");
+                match list_index(&pair[0]) {
+                    Some(index) if index < list.len() => {
+                        list[index] = value;
+                        Value::List(list)
+                    },
+                    _ => Value::Null,
+                }
+            },
+            (Value::Map(mut map), Value::List(mut pair)) if pair.len() == 2 => {
+                let (value, key) = (pair.pop().expect("two elements"), pair.pop().expect("two elements"));
+                map.insert(key, value);
+                Value::Map(map)
+            },
+            _ => Value::Null,
+        },
+        Operator::Property => match (left, right) {
+            (Value::List(list), Value::Symbol(property)) if property.as_str() == "len" => Value::SmallInt(list.len() as i128),
+            (Value::Map(map), Value::Symbol(property)) if property.as_str() == "len" => Value::SmallInt(map.len() as i128),
+            (Value::Map(map), Value::Symbol(property)) if property.as_str() == "keys" => Value::List(map.into_iter().map(|(key, _)| key).collect()),
+            _ => Value::Null,
+        },
+        Operator::And => match (value_is_truthy(&left), value_is_truthy(&right)) {
+            (true, true) => Value::Boolean(true),
+            _ => Value::Boolean(false),
+        },
+        Operator::Or => match (value_is_truthy(&left), value_is_truthy(&right)) {
+            (false, false) => Value::Boolean(false),
+            _ => Value::Boolean(true),
+        },
+        Operator::Xor => match (value_is_truthy(&left), value_is_truthy(&right)) {
+            (true, false)|(false, true) => Value::Boolean(true),
+            _ => Value::Boolean(false),
+        },
+        Operator::BitAnd => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left & right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left & right),
+            _ => Value::Null,
+        },
+        Operator::BitOr => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left | right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left | right),
+            _ => Value::Null,
+        },
+        Operator::BitXor => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left ^ right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left ^ right),
+            _ => Value::Null,
+        },
+        Operator::BitNot => unreachable!("returned above"),
+        Operator::ShiftLeft => shift(left, right, true, max_bytes, too_large)?,
+        Operator::ShiftRight => shift(left, right, false, max_bytes, too_large)?,
+        Operator::Plus => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left + &right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_add(right)
+                .map(Value::SmallInt)
+                .unwrap_or(Value::Integer(Integer::from(left) + Integer::from(right))),
+            (Value::Float(left), Value::Float(right)) => Value::Float(left + right),
+            (Value::Time(Some(left)), Value::Duration(right))|(Value::Duration(right), Value::Time(Some(left))) => {
+                left.checked_add(right).map(|time| Value::Time(Some(time))).unwrap_or(Value::Null)
+            },
+            (Value::Duration(left), Value::Duration(right)) => left.checked_add(right).map(Value::Duration).unwrap_or(Value::Null),
+            (Value::List(mut left), Value::List(right)) => {
+                left.extend(right);
+                Value::List(left)
+            },
+            (Value::List(mut left), right) => {
+                left.push(right);
+                Value::List(left)
+            },
+            (Value::Map(mut left), Value::Map(right)) => {
+                left.extend(right);
+                Value::Map(left)
+            },
+            (Value::Symbol(left), Value::Symbol(right)) => Value::Symbol(Symbol::new(&format!("{left}{right}"))),
+            (left @ (Value::Text(_)|Value::Symbol(_)), right @ (Value::Text(_)|Value::Integer(_)|Value::SmallInt(_))) => {
+                let mut left = into_text(left).expect("text or symbol");
+                match right {
+                    Value::Text(right) => left.push_str(&right),
+                    right => left.push_str(&value_to_string(&right, false)),
+                }
+                Value::Text(left)
+            },
+            _ => Value::Null,
+        },
+        Operator::Minus => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left - &right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_sub(right)
+                .map(Value::SmallInt)
+                .unwrap_or(Value::Integer(Integer::from(left) - Integer::from(right))),
+            (Value::Float(left), Value::Float(right)) => Value::Float(left - right),
+            (Value::Time(Some(left)), Value::Time(Some(right))) => left.checked_duration_since(right).map(Value::Duration).unwrap_or(Value::Null),
+            (Value::Time(Some(left)), Value::Duration(right)) => left.checked_sub(right).map(|time| Value::Time(Some(time))).unwrap_or(Value::Null),
+            (Value::Duration(left), Value::Duration(right)) => left.checked_sub(right).map(Value::Duration).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Operator::Times => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left * &right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_mul(right)
+                .map(Value::SmallInt)
+                .unwrap_or(Value::Integer(Integer::from(left) * Integer::from(right))),
+            (Value::Float(left), Value::Float(right)) => Value::Float(left * right),
+            (Value::Duration(left), right @ (Value::Integer(_)|Value::SmallInt(_))) => {
+                integer_nanos(&right)
+                    .and_then(|times| left.as_nanos().checked_mul(times))
+                    .and_then(duration_from_nanos)
+                    .map(Value::Duration)
+                    .unwrap_or(Value::Null)
+            },
+            (Value::Duration(left), Value::Float(right)) => {
+                Duration::try_from_secs_f64(left.as_secs_f64() * right.to_f64()).map(Value::Duration).unwrap_or(Value::Null)
+            },
+            (Value::List(mut left), right @ (Value::Integer(_)|Value::SmallInt(_))) => {
+                let (negative, count) = match right {
+                    Value::SmallInt(right) => (right < 0, right.unsigned_abs().try_into().unwrap_or(usize::MAX)),
+                    Value::Integer(right) => (right.sign() == Sign::Minus, right.magnitude().try_into().unwrap_or(usize::MAX)),
+                    _ => unreachable!("integers"),
+                };
+                if negative {
+                    left.reverse();
+                }
+                if left.is_empty() {
+                    Value::List(left)
+                } else if list_bytes(&left).checked_mul(count).is_none_or(|bytes| bytes > max_bytes) {
+                    return Err(too_large)
+                } else {
+                    Value::List(std::iter::repeat_n(left.iter(), count).flatten().cloned().collect())
+                }
+            },
+            (left @ (Value::Text(_)|Value::Symbol(_)), Value::Integer(mut right)) => {
+                let mut left = into_text(left).expect("text or symbol");
+                if matches!(right.sign(), Sign::Minus) {
+                    left = left.chars().rev().collect();
+                    right = -right;
+                }
+                Value::Text(repeat_text(&left, right.try_into().unwrap_or(usize::MAX), max_bytes).ok_or(too_large)?)
+            },
+            (left @ (Value::Text(_)|Value::Symbol(_)), Value::SmallInt(right)) => {
+                let mut left = into_text(left).expect("text or symbol");
+                if right < 0 {
+                    left = left.chars().rev().collect();
+                }
+                Value::Text(repeat_text(&left, right.unsigned_abs().try_into().unwrap_or(usize::MAX), max_bytes).ok_or(too_large)?)
+            },
+            (left @ (Value::Text(_)|Value::Symbol(_)), Value::Float(right)) => {
+                let left = into_text(left).expect("text or symbol");
+                let integer = right.int().abs().to_u128().map(|i| i.try_into().unwrap_or(usize::MAX)).unwrap_or(usize::MAX);
+                // a fraction of the characters, not of the bytes
+                let fraction = (right.frac().to_f64().abs() * left.chars().count() as f64).round() as usize;
+                let fraction_string = &left[..left.char_indices().nth(fraction).map_or(left.len(), |(index, _)| index)];
+                if right.is_negative() {
+                    let mut output: String = fraction_string.chars().rev().collect();
+                    let rev_string = repeat_text(&left.chars().rev().collect::<String>(), integer, max_bytes).ok_or(too_large)?;
+                    output.push_str(&rev_string);
+                    Value::Text(output)
+                } else {
+                    let mut output = repeat_text(&left, integer, max_bytes).ok_or(too_large)?;
+                    output.push_str(fraction_string);
+                    Value::Text(output)
+                }
+            },
+            _ => Value::Null,
+        },
+        Operator::Divide => match (left, right) {
+            (Value::Integer(_), Value::Integer(right)) if right == BigInt::ZERO => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::SmallInt(_), Value::SmallInt(0)) => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left / &right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_div(right)
+                .map(Value::SmallInt)
+                .unwrap_or(Value::Integer(Integer::from(left) / Integer::from(right))),
+            (Value::Float(left), Value::Float(right)) => Value::Float(left / right),
+            (Value::Duration(_), Value::Duration(right)) if right.is_zero() => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::Duration(left), Value::Duration(right)) => Value::Float(BigFloat::from(left.as_secs_f64() / right.as_secs_f64())),
+            (Value::Duration(_), Value::SmallInt(0)) => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::Duration(left), right @ (Value::Integer(_)|Value::SmallInt(_))) => {
+                integer_nanos(&right).and_then(|times| left.as_nanos().checked_div(times)).and_then(duration_from_nanos).map(Value::Duration).unwrap_or(Value::Null)
+            },
+            (Value::Duration(left), Value::Float(right)) => {
+                Duration::try_from_secs_f64(left.as_secs_f64() / right.to_f64()).map(Value::Duration).unwrap_or(Value::Null)
+            },
+            _ => Value::Null,
+        },
+        Operator::Modulo => match (left, right) {
+            (Value::Integer(_), Value::Integer(right)) if right == BigInt::ZERO => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::SmallInt(_), Value::SmallInt(0)) => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::Integer(left), Value::Integer(right)) => compress_integer(left % &right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::SmallInt(left.checked_rem(right).unwrap_or(0)),
+            (Value::Float(left), Value::Float(right)) => Value::Float(left % right),
+            (Value::Duration(_), Value::Duration(right)) if right.is_zero() => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::Duration(left), Value::Duration(right)) => duration_from_nanos(left.as_nanos() % right.as_nanos()).map(Value::Duration).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Operator::PPercent => match (left, right) {
+            (Value::Integer(_), Value::Integer(right)) if right == BigInt::ZERO => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::SmallInt(_), Value::SmallInt(0)) => return Err(RuntimeErrorKind::DivisionByZero),
+            (Value::Integer(left), Value::Integer(right)) => compress_integer((left % &right + &right) % &right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => left.checked_rem(right)
+                .and_then(|rem| rem.checked_add(right))
+                .and_then(|sum| sum.checked_rem(right))
+                .map(Value::SmallInt)
+                .unwrap_or_else(|| {
+                    let (left, right) = (Integer::from(left), Integer::from(right));
+                    compress_integer((left % &right + &right) % &right)
+                }),
+            (Value::Float(left), Value::Float(right)) => Value::Float((left % right + right) % right),
+            _ => Value::Null,
+        },
+        Operator::Exponential => match (left, right) {
+            (Value::Integer(_), Value::Integer(right)) if right.sign() == Sign::Minus => return Err(RuntimeErrorKind::NegativeExponent),
+            (Value::SmallInt(_), Value::SmallInt(right)) if right < 0 => return Err(RuntimeErrorKind::NegativeExponent),
+            (Value::Integer(left), Value::Integer(right)) => match u32::try_from(&right) {
+                Ok(right) if left.bits().saturating_mul(right as u64) / 8 > max_bytes as u64 => return Err(too_large),
+                Ok(right) => compress_integer(left.pow(right)),
+                Err(_) => return Err(RuntimeErrorKind::ExponentTooLarge),
+            },
+            (Value::SmallInt(left), Value::SmallInt(right)) => match u32::try_from(right) {
+                Ok(right) => match left.checked_pow(right) {
+                    Some(result) => Value::SmallInt(result),
+                    None if Integer::from(left).bits().saturating_mul(right as u64) / 8 > max_bytes as u64 => return Err(too_large),
+                    None => compress_integer(Integer::from(left).pow(right)),
+                },
+                Err(_) => return Err(RuntimeErrorKind::ExponentTooLarge),
+            },
+            (Value::Float(left), Value::Float(right)) => Value::Float(left.pow(&right)),
+            _ => Value::Null,
+        },
+        Operator::Unequal => match (left, right) {
+            (Value::Boolean(left), Value::Boolean(right)) => Value::Boolean(left != right),
+            (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left != right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left != right),
+            (Value::Float(left), Value::Float(right)) => Value::Boolean(left != right),
+            (Value::Time(left), Value::Time(right)) => Value::Boolean(left != right),
+            (Value::Duration(left), Value::Duration(right)) => Value::Boolean(left != right),
+            (Value::List(left), Value::List(right)) => Value::Boolean(left != right),
+            (Value::Map(left), Value::Map(right)) => Value::Boolean(left != right),
+            _ => Value::Boolean(true),
+        },
+        Operator::Equal => match (left, right) {
+            (Value::Boolean(left), Value::Boolean(right)) => Value::Boolean(left == right),
+            (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left == right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left == right),
+            (Value::Float(left), Value::Float(right)) => Value::Boolean(left == right),
+            (Value::Time(left), Value::Time(right)) => Value::Boolean(left == right),
+            (Value::Duration(left), Value::Duration(right)) => Value::Boolean(left == right),
+            (Value::List(left), Value::List(right)) => Value::Boolean(left == right),
+            (Value::Map(left), Value::Map(right)) => Value::Boolean(left == right),
+            _ => Value::Boolean(false),
+        },
+        Operator::LessThan => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left < right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left < right),
+            (Value::Float(left), Value::Float(right)) => Value::Boolean(left < right),
+            (Value::Time(Some(left)), Value::Time(Some(right))) => Value::Boolean(left < right),
+            (Value::Duration(left), Value::Duration(right)) => Value::Boolean(left < right),
+            (Value::List(left), Value::List(right)) => compare_lists(&left, &right).map(|order| Value::Boolean(order.is_lt())).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Operator::GreaterThan => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left > right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left > right),
+            (Value::Float(left), Value::Float(right)) => Value::Boolean(left > right),
+            (Value::Time(Some(left)), Value::Time(Some(right))) => Value::Boolean(left > right),
+            (Value::Duration(left), Value::Duration(right)) => Value::Boolean(left > right),
+            (Value::List(left), Value::List(right)) => compare_lists(&left, &right).map(|order| Value::Boolean(order.is_gt())).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Operator::LessThanOrEqual => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left <= right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left <= right),
+            (Value::Float(left), Value::Float(right)) => Value::Boolean(left <= right),
+            (Value::Time(Some(left)), Value::Time(Some(right))) => Value::Boolean(left <= right),
+            (Value::Duration(left), Value::Duration(right)) => Value::Boolean(left <= right),
+            (Value::List(left), Value::List(right)) => compare_lists(&left, &right).map(|order| Value::Boolean(order.is_le())).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+        Operator::GreaterThanOrEqual => match (left, right) {
+            (Value::Integer(left), Value::Integer(right)) => Value::Boolean(left >= right),
+            (Value::SmallInt(left), Value::SmallInt(right)) => Value::Boolean(left >= right),
+            (Value::Float(left), Value::Float(right)) => Value::Boolean(left >= right),
+            (Value::Time(Some(left)), Value::Time(Some(right))) => Value::Boolean(left >= right),
+            (Value::Duration(left), Value::Duration(right)) => Value::Boolean(left >= right),
+            (Value::List(left), Value::List(right)) => compare_lists(&left, &right).map(|order| Value::Boolean(order.is_ge())).unwrap_or(Value::Null),
+            _ => Value::Null,
+        },
+    };
+
+    Ok(result)
+}
+
+/// `.<<` and `.>>`, a negative count shifts the other way.
+/// Integers act like two's complement numbers without a width: left shifts become big integers
+/// instead of losing bits, and right shifts round down. Results bigger than `max_bytes` are `too_large`.
+fn shift(value: Value, count: Value, to_left: bool, max_bytes: usize, too_large: RuntimeErrorKind) -> Result<Value, RuntimeErrorKind> {
+    let (negative, count) = match &count {
+        Value::SmallInt(count) => (*count < 0, u32::try_from(count.unsigned_abs()).ok()),
+        Value::Integer(count) => (count.sign() == Sign::Minus, u32::try_from(count.magnitude()).ok()),
+        _ => return Ok(Value::Null),
+    };
+    let left = to_left != negative;
+
+    let value = match value {
+        Value::SmallInt(value) => match count {
+            Some(count) if !left => return Ok(Value::SmallInt(value >> count.min(127))),
+            Some(count) if count < 128 && (value << count) >> count == value => return Ok(Value::SmallInt(value << count)),
+            _ => Integer::from(value),
+        },
+        Value::Integer(value) => value,
+        _ => return Ok(Value::Null),
+    };
+
+    Ok(match count {
+        Some(count) if !left => compress_integer(value >> count),
+        // right shifts past all of the bits only leave the sign
+        None if !left => Value::SmallInt(if value.sign() == Sign::Minus { -1 } else { 0 }),
+        Some(count) if (value.bits() + count as u64) / 8 > max_bytes as u64 => return Err(too_large),
+        Some(count) => compress_integer(value << count),
+        None => return Err(RuntimeErrorKind::ShiftTooLarge),
+    })
+}
+
+/// The position that a number indexes in a text or list, floats are rounded down
+fn list_index(index: &Value) -> Option<usize> {
+    match index {
+        Value::Integer(int) => int.try_into().ok(),
+        Value::SmallInt(int) => (*int).try_into().ok(),
+        Value::Float(float) => float.floor().to_u128().and_then(|a| a.try_into().ok()),
+        _ => None,
+    }
+}
+
+/// Lists are ordered by their first different element, then by their length.
+/// `None` if two elements can't be compared, like with `<`.
+fn compare_lists(left: &[Value], right: &[Value]) -> Option<Ordering> {
+    for (left, right) in left.iter().zip(right) {
+        let order = match (left, right) {
+            (Value::List(left), Value::List(right)) => compare_lists(left, right)?,
+            (left, right) => match (value_text(left), value_text(right)) {
+                (Some(left), Some(right)) => left.cmp(right),
+                _ => match promote(left.clone(), right.clone()) {
+                    (Value::Integer(left), Value::Integer(right)) => left.cmp(&right),
+                    (Value::SmallInt(left), Value::SmallInt(right)) => left.cmp(&right),
+                    (Value::Float(left), Value::Float(right)) => left.partial_cmp(&right)?,
+                    (Value::Time(Some(left)), Value::Time(Some(right))) => left.cmp(&right),
+                    (Value::Duration(left), Value::Duration(right)) => left.cmp(&right),
+                    _ => return None,
+                },
+            },
+        };
+        if order != Ordering::Equal {
+            return Some(order)
+        }
+    }
+    Some(left.len().cmp(&right.len()))
+}
+
+/// Non-negative integers that fit in a `u128`
+fn integer_nanos(integer: &Value) -> Option<u128> {
+    match integer {
+        Value::Integer(integer) => integer.try_into().ok(),
+        Value::SmallInt(integer) => (*integer).try_into().ok(),
+        _ => None,
+    }
+}
+
+const UNITS: [(&str, u128); 4] = [("s", 1_000_000_000), ("ms", 1_000_000), ("us", 1_000), ("ns", 1)];
+
+/// `duration ~ s` is a float of seconds, `ms`, `us` and `ns` are integers
+fn duration_in(duration: Duration, unit: &str) -> Value {
+    match UNITS.iter().find(|(name, _)| *name == unit) {
+        Some(("s", _)) => Value::Float(BigFloat::parse(&format!("{}.{:09}", duration.as_secs(), duration.subsec_nanos())).unwrap()),
+        Some((_, nanos)) => compress_integer(Integer::from(duration.as_nanos() / nanos)),
+        None => Value::Null,
+    }
+}
+
+/// `5 ~ ms` is a duration of 5 milliseconds
+fn duration_of(number: Value, unit: &str) -> Value {
+    let Some((_, nanos)) = UNITS.iter().find(|(name, _)| *name == unit) else {
+        return Value::Null
+    };
+    let duration = match number {
+        Value::Float(float) => Duration::try_from_secs_f64(float.to_f64() * *nanos as f64 / 1e9).ok(),
+        integer => integer_nanos(&integer).and_then(|integer| integer.checked_mul(*nanos)).and_then(duration_from_nanos),
+    };
+    duration.map(Value::Duration).unwrap_or(Value::Null)
+}
+
+/// `time ~ unix` and `unix_ms` are integer Unix timestamps, `date` and `datetime` are UTC texts like
+/// `/2024-02-29/` and `/2024-02-29T13:45:00Z/`
+fn time_in(time: Instant, key: &str, wall_clock: WallClock) -> Value {
+    let Some(system) = wall_clock.at(time) else {
+        return Value::Null
+    };
+    let nanos = match system.duration_since(UNIX_EPOCH) {
+        Ok(after) => after.as_nanos() as i128,
+        Err(before) => -(before.duration().as_nanos() as i128),
+    };
+    let seconds = nanos.div_euclid(1_000_000_000);
+    let (days, second) = (seconds.div_euclid(86_400), seconds.rem_euclid(86_400));
+    let (year, month, day) = civil_date(days);
+
+    match key {
+        "unix" => Value::SmallInt(seconds),
+        "unix_ms" => Value::SmallInt(nanos.div_euclid(1_000_000)),
+        "date" => Value::Text(format!("{year:04}-{month:02}-{day:02}")),
+        "datetime" => Value::Text(format!(
+            "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+            second / 3600, second / 60 % 60, second % 60,
+        )),
+        _ => Value::Null,
+    }
+}
+
+/// Year, month and day of the days since 1970-01-01 in the proleptic Gregorian calendar
+fn civil_date(days: i128) -> (i128, i128, i128) {
+    // eras of 400 years starting on March 1st, so leap days are at the end of the year
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// The text of a `{...}` statement
+pub fn log_text(variables: &SpeckyDataContainer<Value>, pointer: &Value, kind: &StatementKind, now: Instant) -> String {
+    let StatementKind::Log { kind, reverse, newline, space, vertical, .. } = kind else {
+        unreachable!("not a log statement")
+    };
+
+    let string = match kind {
+        Some(LogKind::Value(LogValue { reader, pretty })) => {
+            let print = value_reader(variables, pointer, *reader);
+            value_to_string_at(print, *pretty, now)
+        },
+        Some(LogKind::Type) => {
+            // TODO: idk, reader and selecting what the value actually is, maybe make it a property
+            match variables.get(pointer).unwrap_or(&NULL) {
+                Value::Symbol(_) => "Symbol",
+                Value::Boolean(_) => "Boolean",
+                Value::Integer(_) => "Integer",
+                Value::SmallInt(_) => "SmallInt",
+                Value::Float(_) => "Float",
+                Value::Text(_) => "Text",
+                Value::Time(_) => "Time",
+                Value::Duration(_) => "Duration",
+                Value::List(_) => "List",
+                Value::Map(_) => "Map",
+                Value::Null => "Null",
+            }.to_string()
+        },
+        Some(LogKind::Memory(LogMemory { sort })) => memory_to_string(variables, *sort),
+        None => "".to_string(),
+    };
+
+    let string = if *reverse {
+        string.chars().rev().collect()
+    } else {
+        string
+    };
+
+    let string = string + &" ".repeat(*space);
+    let string = string + if *newline { "\n" } else { "" };
+
+    if *vertical {
+        let max = string.lines().map(|l| l.len()).max().unwrap_or(0);
+        let lines = string.lines().collect::<Vec<&str>>();
+        let mut output_lines: Vec<String> = vec![];
+
+        for i in 0..max {
+            output_lines.push(lines.iter().map(|l| l.chars().nth(i).unwrap_or(' ')).collect());
+        }
+        output_lines.join("\n")
+    } else {
+        string
+    }
+}
@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use crate::ast::{Statement, StatementKind, Value};
+
+use super::{value::expression_value, SpeckyDataContainer};
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct StatementProfile {
+    pub count: u64,
+    pub time: Duration,
+}
+
+/// Execution counts and times, collected when profiling is enabled
+#[derive(Debug, Default, Clone)]
+pub struct Profile {
+    /// Indexed by statement index
+    pub statements: Vec<StatementProfile>,
+    /// How many times each jump address got jumped to
+    pub jumps: SpeckyDataContainer<u64>,
+}
+
+impl Profile {
+    pub(super) fn record(
+        &mut self,
+        statement_index: usize,
+        statement: &Statement,
+        jumped: bool,
+        time: Duration,
+        variables: &SpeckyDataContainer<Value>,
+        saved_pointers: &[Value],
+    ) {
+        if self.statements.len() <= statement_index {
+            self.statements.resize(statement_index + 1, StatementProfile::default());
+        }
+
+        let profile = &mut self.statements[statement_index];
+        profile.count += 1;
+        profile.time += time;
+
+        if let (StatementKind::Jump(expr), true) = (&statement.kind, jumped) {
+            let label = expression_value(variables, saved_pointers, expr);
+            *self.jumps.entry(label.clone()).or_default() += 1;
+        }
+    }
+
+    pub fn total_time(&self) -> Duration {
+        self.statements.iter().map(|statement| statement.time).sum()
+    }
+}
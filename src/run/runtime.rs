@@ -0,0 +1,255 @@
+use std::time::{Duration, Instant};
+
+use crate::ast::{SmallInt, StatementKind, Value};
+
+use super::{Clock, ErrorPolicy, Limit, RuntimeErrorKind, WallClock, SpeckyDataContainer, SpeckyIo, StdIo, call::Frame, operation::*, value::*};
+
+/// Memory, pointer and io of a transpiled program (see `speckylang compile`),
+/// each method does the same as the statement of the same name in the interpreter.
+/// The generated code takes care of the statement index and calls [`Runtime::step`] before every statement.
+#[derive(Debug)]
+pub struct Runtime<IO: SpeckyIo = StdIo> {
+    variables: SpeckyDataContainer<Value>,
+    pointer: Value,
+    saved_pointers: Vec<Value>,
+    memory_bytes: usize,
+    calls: Vec<Frame>,
+    io: IO,
+    last_flush: Instant,
+    clock: Clock,
+    created: Instant,
+    wall_clock: WallClock,
+    steps: u64,
+    max_steps: Option<u64>,
+}
+
+impl Runtime {
+    /// Creates a runtime that uses stdout and stdin
+    pub fn new() -> Self {
+        Self::with_io(StdIo::default())
+    }
+
+    /// Creates a runtime that uses stdout and stdin, with the options of the interpreter
+    /// that a transpiled program takes: `--clock <clock>` and `--max-steps <steps>`
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        let mut runtime = Self::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| format!("missing value for '{arg}'"))?;
+            match arg.as_str() {
+                "--clock" => runtime.set_clock(value.parse()?),
+                "--max-steps" => runtime.set_max_steps(Some(value.parse().map_err(|_| format!("invalid max steps '{value}'"))?)),
+                _ => return Err(format!("unknown option '{arg}' (expected '--clock' or '--max-steps')")),
+            }
+        }
+        Ok(runtime)
+    }
+}
+
+impl Default for Runtime {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<IO: SpeckyIo> Runtime<IO> {
+    pub fn with_io(io: IO) -> Self {
+        Self {
+            variables: SpeckyDataContainer::default(),
+            pointer: Value::Null,
+            saved_pointers: vec![],
+            memory_bytes: 0,
+            calls: vec![],
+            io,
+            last_flush: Instant::now(),
+            clock: Clock::default(),
+            created: Instant::now(),
+            wall_clock: WallClock::system(),
+            steps: 0,
+            max_steps: None,
+        }
+    }
+
+    /// Changes where `µ` gets the time from, see [`Clock`]
+    pub fn set_clock(&mut self, clock: Clock) {
+        self.clock = clock;
+        self.wall_clock = clock.wall_clock(self.created);
+    }
+
+    /// Like [`Limits::max_steps`](super::Limits::max_steps), the other limits aren't checked
+    pub fn set_max_steps(&mut self, max_steps: Option<u64>) {
+        self.max_steps = max_steps;
+    }
+
+    /// Counts the statement that runs next, an error if that's more than the maximum steps
+    pub fn step(&mut self) -> Result<(), RuntimeErrorKind> {
+        match self.max_steps {
+            Some(max_steps) if self.steps >= max_steps => Err(RuntimeErrorKind::LimitExceeded(Limit::Steps(max_steps))),
+            _ => {
+                self.steps += 1;
+                Ok(())
+            },
+        }
+    }
+
+    /// The time `µ` reads right now, the running statement isn't counted yet like in the interpreter
+    pub fn now(&self) -> Instant {
+        self.clock.now(self.created, self.steps.saturating_sub(1))
+    }
+
+    /// Reads `value` through `reader` levels of memory, like a `§§value` expression
+    pub fn read<'a>(&'a self, value: &'a Value, reader: usize) -> &'a Value {
+        value_reader(&self.variables, value, reader)
+    }
+
+    pub fn load(&mut self, value: &Value, reader: usize) {
+        self.pointer = self.read(value, reader).clone();
+        compress_value(&mut self.pointer);
+    }
+
+    /// `|> value`
+    pub fn push(&mut self, value: &Value, reader: usize) {
+        let mut pointer = self.read(value, reader).clone();
+        compress_value(&mut pointer);
+        self.saved_pointers.push(std::mem::replace(&mut self.pointer, pointer));
+    }
+
+    /// `<|`
+    pub fn pop(&mut self) {
+        if let Some(pointer) = self.saved_pointers.pop() {
+            self.pointer = pointer;
+        }
+    }
+
+    /// `<|>`
+    pub fn exchange(&mut self) {
+        if let Some(saved) = self.saved_pointers.last_mut() {
+            std::mem::swap(&mut self.pointer, saved);
+        }
+    }
+
+    /// The value of `@`, the pointer saved by the last `|>`
+    pub fn saved_pointer(&self) -> Value {
+        self.saved_pointers.last().cloned().unwrap_or(Value::Null)
+    }
+
+    /// `[<] label` at `statement_index`
+    pub fn define(&mut self, label: &Value, reader: usize, statement_index: usize) {
+        let label = self.read(label, reader).clone();
+        store(&mut self.variables, &mut self.memory_bytes, label, Value::SmallInt(statement_index as SmallInt + 1));
+    }
+
+    /// The statement index to continue from, `None` if the jump does nothing
+    pub fn jump(&self, label: &Value, reader: usize) -> Option<usize> {
+        jump_address(self.variables.get(self.read(label, reader)))
+    }
+
+    /// `[>:] label` at `statement_index`, the statement index to continue from
+    /// (`None` if the call does nothing)
+    pub fn call(&mut self, label: &Value, reader: usize, statement_index: usize, scoped: bool) -> Option<usize> {
+        let address = self.jump(label, reader)?;
+        self.calls.push(Frame::new(statement_index, scoped, &self.variables, self.memory_bytes));
+        Some(address)
+    }
+
+    /// `[<:]`, the statement index to continue from (`None` if there's no call to return from)
+    pub fn return_from_call(&mut self) -> Option<usize> {
+        let frame = self.calls.pop()?;
+        let next = frame.call + 1;
+        if frame.restore(&mut self.variables, &self.pointer) {
+            self.memory_bytes = memory_bytes(&self.variables);
+        }
+        Some(next)
+    }
+
+    /// Statement indexes of the calls that haven't returned yet, outermost first
+    pub fn call_stack(&self) -> impl Iterator<Item = usize> + '_ {
+        self.calls.iter().map(|frame| frame.call)
+    }
+
+    pub fn assign(&mut self, value: &Value, reader: usize) {
+        let value = timed(self.read(value, reader), || self.now());
+        store(&mut self.variables, &mut self.memory_bytes, self.pointer.clone(), value);
+    }
+
+    pub fn overwrite(&mut self, value: &Value, reader: usize) {
+        let key = timed(self.read(value, reader), || self.now());
+        store(&mut self.variables, &mut self.memory_bytes, key, self.pointer.clone());
+    }
+
+    pub fn swap(&mut self, value: &Value, reader: usize) {
+        let key = self.read(value, reader).clone();
+        swap(&mut self.variables, &mut self.memory_bytes, &self.pointer, key);
+    }
+
+    /// Math, comparisons, `~`, the logical and the bitwise operators, errors always stop the program
+    pub fn operate(&mut self, operator: Operator, value: &Value, reader: usize) -> Result<(), RuntimeErrorKind> {
+        let right = timed(self.read(value, reader), || self.now());
+        let left = self.variables.get(&self.pointer).unwrap_or(&Value::Null).clone();
+        let result = operate_with(operator, left, right, None, self.wall_clock, ErrorPolicy::Abort)?;
+        store(&mut self.variables, &mut self.memory_bytes, self.pointer.clone(), result);
+        Ok(())
+    }
+
+    /// `?`, whether the statements after it run
+    pub fn truthy(&self) -> bool {
+        value_is_truthy(self.pointed())
+    }
+
+    /// `!`
+    pub fn falsy(&self) -> bool {
+        !value_is_truthy(self.pointed())
+    }
+
+    /// `$`
+    pub fn exists(&self) -> bool {
+        value_exists(self.pointed())
+    }
+
+    /// `°`
+    pub fn empty(&self) -> bool {
+        !value_exists(self.pointed())
+    }
+
+    /// `{...}`, `statement` has to be a [`StatementKind::Log`]
+    pub fn log(&mut self, statement: &StatementKind) -> Result<(), RuntimeErrorKind> {
+        let now = self.now();
+        if let Some(string) = log(&mut self.variables, &mut self.memory_bytes, &self.pointer, statement, now) {
+            self.io.write_str(&string).map_err(|error| RuntimeErrorKind::Io(error.to_string()))?;
+            if self.last_flush.elapsed() > Duration::from_millis(50) {
+                self.flush();
+            }
+        }
+        Ok(())
+    }
+
+    /// `'`, there's no waiting: if the io has nothing to read the line is empty
+    pub fn input(&mut self) -> Result<(), RuntimeErrorKind> {
+        self.io.flush().map_err(|error| RuntimeErrorKind::Io(error.to_string()))?;
+        let line = self.io.read_line().unwrap_or_default();
+        let value = string_to_value(&line, self.now());
+        store(&mut self.variables, &mut self.memory_bytes, self.pointer.clone(), value);
+        Ok(())
+    }
+
+    pub fn flush(&mut self) {
+        let _ = self.io.flush();
+        self.last_flush = Instant::now();
+    }
+
+    pub fn memory(&self) -> &SpeckyDataContainer<Value> {
+        &self.variables
+    }
+
+    pub fn pointer(&self) -> &Value {
+        &self.pointer
+    }
+
+    pub fn io(&self) -> &IO {
+        &self.io
+    }
+
+    fn pointed(&self) -> &Value {
+        self.variables.get(&self.pointer).unwrap_or(&Value::Null)
+    }
+}
@@ -0,0 +1,167 @@
+use crate::ast::{SmallInt, StatementKind, Value};
+
+use super::{Interpreter, RuntimeErrorKind, SpeckyIo, Status, operation::*, value::*};
+
+impl<IO: SpeckyIo> Interpreter<IO> {
+    /// Executes the statement at the current statement index by walking the syntax tree,
+    /// returns whether the statement index should move to the next statement
+    pub(super) fn execute_statement(&mut self) -> Result<bool, RuntimeErrorKind> {
+        match &self.program[self.statement_index].kind {
+            StatementKind::Log { .. } => return self.log().map(|()| true),
+            StatementKind::Input => return self.input(),
+            StatementKind::Call { expression, scoped } => {
+                let (address, scoped) = (jump_address(self.variables.get(expression_value(&self.variables, &self.saved_pointers, expression))), *scoped);
+                return self.call(address, scoped)
+            },
+            StatementKind::Return => return Ok(self.return_from_call()),
+            _ => {},
+        }
+
+        let Self {
+            program,
+            variables,
+            current_pointer,
+            saved_pointers,
+            statement_index,
+            error_policy,
+            limits,
+            memory_bytes,
+            clock,
+            created,
+            wall_clock,
+            steps,
+            ..
+        } = self;
+
+        let mut next_statement = true;
+
+        macro_rules! store {
+            ($key:expr, $value:expr $(,)?) => {
+                store(variables, memory_bytes, $key, $value)
+            };
+        }
+
+        let kind = &program[*statement_index].kind;
+
+        if let Some((operator, expr)) = Operator::of(kind) {
+            let right = timed(expression_value(variables, saved_pointers, expr), || clock.now(*created, *steps));
+            let left = variables.get(current_pointer).unwrap_or(&Value::Null).clone();
+            let result = operate_with(operator, left, right, limits.max_memory_bytes, *wall_clock, *error_policy)?;
+            store!(current_pointer.clone(), result);
+            return Ok(next_statement)
+        }
+
+        macro_rules! match_statement {
+            { $($statement:ident $($expr:tt)? => $code:tt $(,)?)* } => {
+                match kind {
+                    $(
+                        match_statement!(@pat $statement $($expr)?) => {
+                            $(
+                                #[allow(unused_macros)]
+                                macro_rules! operand {
+                                    () => {
+                                        expression_value(&variables, &saved_pointers, $expr)
+                                    };
+                                }
+
+                                #[allow(unused_macros)]
+                                macro_rules! condition_jump {
+                                    ($condition:expr, $quantity:expr) => {
+                                        {
+                                            let value = variables.get(current_pointer).unwrap_or(&Value::Null);
+                                            #[allow(clippy::redundant_closure)]
+                                            #[allow(clippy::redundant_closure_call)]
+                                            if !$condition(value) { *statement_index += $quantity; }
+                                        }
+                                    }
+                                }
+                            )?
+
+                            $code
+                        },
+                    )*
+                    _ => unreachable!("executed above"),
+                }
+            };
+            (@pat $ident:ident $(())?) => { StatementKind::$ident };
+            (@pat $ident:ident $expr:tt) => { StatementKind::$ident $expr };
+        }
+
+        match_statement! {
+            Load(expr) => {
+                *current_pointer = operand!().clone();
+                compress_value(current_pointer);
+            },
+            Push(expr) => {
+                let mut pointer = operand!().clone();
+                compress_value(&mut pointer);
+                saved_pointers.push(std::mem::replace(current_pointer, pointer));
+            },
+            Pop() => {
+                if let Some(pointer) = saved_pointers.pop() {
+                    *current_pointer = pointer;
+                }
+            },
+            Exchange() => {
+                if let Some(saved) = saved_pointers.last_mut() {
+                    std::mem::swap(current_pointer, saved);
+                }
+            },
+            Define(expr) => {
+                store!(operand!().clone(), Value::SmallInt(*statement_index as SmallInt + 1));
+            },
+            Jump(expr) => {
+                if let Some(index) = jump_address(variables.get(operand!())) {
+                    *statement_index = index;
+                    next_statement = false;
+                }
+            },
+            Assign(expr) => {
+                store!(current_pointer.clone(), timed(operand!(), || clock.now(*created, *steps)));
+            },
+            Overwrite(expr) => {
+                store!(timed(operand!(), || clock.now(*created, *steps)), current_pointer.clone());
+            },
+            Swap(expr) => {
+                swap(variables, memory_bytes, current_pointer, operand!().clone());
+            },
+            Truthy(quantity) => { condition_jump!(|value| value_is_truthy(value), quantity); },
+            Falsy(quantity) => { condition_jump!(|value| !value_is_truthy(value), quantity); },
+            Exists(quantity) => { condition_jump!(|value| value_exists(value), quantity); },
+            Empty(quantity) => { condition_jump!(|value| !value_exists(value), quantity); },
+        }
+
+        Ok(next_statement)
+    }
+
+    /// Executes the log statement at the current statement index
+    pub(super) fn log(&mut self) -> Result<(), RuntimeErrorKind> {
+        let now = self.now();
+        let kind = &self.program[self.statement_index].kind;
+        if let Some(string) = log(&mut self.variables, &mut self.memory_bytes, &self.current_pointer, kind, now) {
+            self.io.write_str(&string).map_err(|error| RuntimeErrorKind::Io(error.to_string()))?;
+            if self.capture_output {
+                self.output.push_str(&string);
+            }
+            self.output_updated = true;
+        }
+        Ok(())
+    }
+
+    /// Executes the input statement at the current statement index,
+    /// returns whether a line was read
+    pub(super) fn input(&mut self) -> Result<bool, RuntimeErrorKind> {
+        self.io.flush().map_err(|error| RuntimeErrorKind::Io(error.to_string()))?;
+        match self.pending_input.take().or_else(|| self.io.read_line()) {
+            Some(line) => {
+                let value = string_to_value(&line, self.now());
+                store(&mut self.variables, &mut self.memory_bytes, self.current_pointer.clone(), value);
+                Ok(true)
+            },
+            None => {
+                self.status = Status::WaitingForInput;
+                Ok(false)
+            },
+        }
+    }
+}
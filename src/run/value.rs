@@ -0,0 +1,277 @@
+use std::time::Instant;
+
+use itertools::Itertools;
+
+use crate::ast::{Expression, Float, Integer, SmallInt, Symbol, Value};
+
+use super::{SpeckyDataContainer, NULL};
+
+#[inline(always)]
+pub fn compress_value(value: &mut Value) {
+    if let Value::Integer(int) = &value {
+        let si = int.try_into();
+        if let Ok(si) = si { 
+            *value = Value::SmallInt(si);
+        }
+    };
+}
+
+#[inline(always)]
+pub fn compress_integer(integer: Integer) -> Value {
+    (&integer).try_into()
+    .map(Value::SmallInt)
+    .unwrap_or(Value::Integer(integer))
+}
+
+/// Bytes counted by [`Limits::max_memory_bytes`](super::Limits::max_memory_bytes)
+#[inline(always)]
+pub fn value_bytes(value: &Value) -> usize {
+    match value {
+        Value::Symbol(symbol) => symbol.len(),
+        Value::Text(string) => string.len(),
+        Value::Integer(integer) => integer.bits().div_ceil(8) as usize,
+        Value::List(list) => list_bytes(list),
+        Value::Map(map) => map.iter().map(|(key, value)| 1 + value_bytes(key) + value_bytes(value)).sum(),
+        _ => 0,
+    }
+}
+
+/// Bytes of every key and value in memory, like [`store`](super::operation::store) keeps track of
+pub fn memory_bytes(variables: &SpeckyDataContainer<Value>) -> usize {
+    variables.iter().map(|(key, value)| value_bytes(key) + value_bytes(value)).sum()
+}
+
+/// A byte for every element and the bytes of the elements, so that lists of small values still count
+pub fn list_bytes(list: &[Value]) -> usize {
+    list.iter().map(|value| 1 + value_bytes(value)).sum()
+}
+
+/// Repeats `string`, unless the output would be bigger than `max_bytes`
+pub fn repeat_text(string: &str, count: usize, max_bytes: usize) -> Option<String> {
+    string.len()
+        .checked_mul(count)
+        .filter(|&bytes| bytes <= max_bytes)
+        .map(|_| string.repeat(count))
+}
+
+#[inline(always)]
+#[allow(clippy::needless_return)]
+pub fn string_to_value(string: &str, now: Instant) -> Value {
+    let string = string.trim();
+
+    if string.is_empty() {
+        return Value::Null
+    }
+
+    if string.chars().all(|c| char::is_ascii_digit(&c) || c == '.') {
+        match string.chars().filter(|&c| c == '.').count() {
+            0 => 
+                return string.parse::<SmallInt>()
+                .map(Value::SmallInt)
+                .unwrap_or(Value::Integer(string.parse::<Integer>().unwrap())),
+            1 => 
+                if let Ok(float) = string.parse::<Float>() {
+                    return Value::Float(float)
+                },
+            _ => {}
+        }
+    }
+
+    return match string {
+        "true"|"on"|"yes" => Value::Boolean(true),
+        "false"|"off"|"no" => Value::Boolean(false),
+        "null" => Value::Null,
+        "µ" => Value::Time(Some(now)),
+        string if string.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') =>
+            Value::Symbol(Symbol::new(string)),
+        string => Value::Text(string.to_string()),
+    };
+}
+
+/// What an expression reads: its value, or the pointer saved by the last `|>` for `@`,
+/// through as many levels of memory as it has `§`
+pub fn expression_value<'a>(memory: &'a SpeckyDataContainer<Value>, saved_pointers: &'a [Value], expression: &'a Expression) -> &'a Value {
+    let value = match expression.saved_pointer {
+        true => saved_pointers.last().unwrap_or(&NULL),
+        false => &expression.value,
+    };
+    value_reader(memory, value, expression.reader)
+}
+
+#[allow(clippy::unnecessary_unwrap)]
+pub fn value_reader<'a>(memory: &'a SpeckyDataContainer<Value>, value: &'a Value, reader: usize) -> &'a Value {
+    if reader == 0 {
+        return value
+    }
+
+    // the chain can't be longer than the reader or the memory
+    let mut chain: Vec<&Value> = Vec::with_capacity(reader.min(memory.len()) + 1);
+    chain.push(value);
+
+    let mut current_value = value;
+
+    for i in 0..reader {
+        let temp_value: Value;
+
+        let current_pointer = match current_value {
+            Value::SmallInt(int) => {
+                temp_value = Value::Integer(Integer::from(*int));
+                if memory.contains_key(current_value) {
+                    current_value
+                } else {
+                    &temp_value
+                }
+            },
+            Value::Integer(int) => {
+                let small_form = int.try_into()
+                .map(Value::SmallInt);
+
+                if small_form.is_ok() && memory.contains_key(small_form.as_ref().unwrap()) {
+                    temp_value = small_form.unwrap();
+                    &temp_value
+                } else {
+                    current_value
+                }
+            },
+            other => other,
+        };
+
+        current_value = memory.get(current_pointer).unwrap_or(&NULL);
+
+        let exists = chain.iter().enumerate().find_map(|(i, v)| if v == &current_value { Some(i) } else { None });
+
+        if let Some(index) = exists {
+            let base = reader + i + index + 1;
+            let modulo = chain.len() - index;
+            let chain_index = base % modulo + index;
+
+            return chain[chain_index];
+        }
+
+        chain.push(current_value);
+    }
+
+    current_value
+}
+
+pub fn memory_to_string(variables: &SpeckyDataContainer<Value>, sort: bool) -> String {
+    let mut variables_string = String::new();
+    variables_string += "{\n";
+
+    let mut push_str = |key, value| {
+        variables_string += &format!("\t{key:?} => {value:?}\n");
+    };
+
+    if sort {
+        let mut sorted_vars = variables.iter().collect::<Vec<_>>();
+
+        sorted_vars.sort_unstable_by(|(a,_), (b,_)| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        for (key, value) in sorted_vars {
+            push_str(key, value)
+        }
+    } else {
+        for (key, value) in variables {
+            push_str(key, value)
+        }
+    };
+
+    variables_string += "}";
+
+    variables_string
+}
+
+pub fn value_to_string(value: &Value, special: bool) -> String {
+    value_to_string_at(value, special, Instant::now())
+}
+
+/// Like [`value_to_string`], times are printed as the time passed until `now`
+pub fn value_to_string_at(value: &Value, special: bool, now: Instant) -> String {
+    match (value, special) {
+        (Value::Symbol(s), false) => s.to_string(),
+        (Value::Symbol(s), true) => Integer::from_bytes_be(num_bigint::Sign::Plus, s.as_bytes()).to_string(),
+
+        (Value::Boolean(b), false) => b.to_string(),
+        (Value::Boolean(b), true) => format!("{}", if *b { 1 } else { 0 }),
+
+        (Value::Integer(i), false) => i.to_string(),
+        (Value::Integer(i), true) =>
+            i.try_into()
+            .ok()
+            .and_then(char::from_u32)
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| char::REPLACEMENT_CHARACTER.to_string()),
+
+        (Value::SmallInt(i), false) => i.to_string(),
+        (Value::SmallInt(i), true) =>
+            (*i as u128).try_into()
+            .ok()
+            .and_then(char::from_u32)
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| char::REPLACEMENT_CHARACTER.to_string()),
+
+        (Value::Float(f), false) => f.to_string(),
+        (Value::Float(f), true) => f.to_f64().to_string(),
+
+        (Value::Text(s), false) => format!("/{}/", s.replace('/', r"\/")),
+        (Value::Text(s), true) => s.to_string(),
+
+        (Value::Time(d), false) => format!("{:?}", now.saturating_duration_since(d.unwrap_or(now))),
+        (Value::Time(d), true) => format!("{}", now.saturating_duration_since(d.unwrap_or(now)).as_secs_f64()),
+
+        (Value::Duration(d), false) => format!("{d:?}"),
+        (Value::Duration(d), true) => format!("{}", d.as_secs_f64()),
+
+        (Value::List(l), false) => format!("[{}]", l.iter().map(|value| value_to_string_at(value, false, now)).join(" ")),
+        (Value::List(l), true) => l.iter().map(|value| value_to_string_at(value, true, now)).collect(),
+
+        (Value::Map(m), false) => format!("{{{}}}", m.iter()
+            .map(|(key, value)| format!("{} => {}", value_to_string_at(key, false, now), value_to_string_at(value, false, now)))
+            .join(" ")),
+        (Value::Map(m), true) => m.values().map(|value| value_to_string_at(value, true, now)).collect(),
+
+        (Value::Null, false) => "null".to_string(),
+        (Value::Null, true) => "\0".to_string(),
+    }
+}
+
+/// The text of texts and symbols
+#[inline(always)]
+pub fn value_text(value: &Value) -> Option<&str> {
+    match value {
+        Value::Text(string) => Some(string),
+        Value::Symbol(symbol) => Some(symbol.as_str()),
+        _ => None,
+    }
+}
+
+/// Like [`value_text`], but keeps the string of texts
+pub fn into_text(value: Value) -> Option<String> {
+    match value {
+        Value::Text(string) => Some(string),
+        Value::Symbol(symbol) => Some(symbol.to_string()),
+        _ => None,
+    }
+}
+
+#[inline(always)]
+pub fn value_is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Symbol(_) => true,
+        Value::Boolean(b) => *b,
+        Value::Integer(n) => *n != Integer::from(0),
+        Value::SmallInt(i) => *i != 0,
+        Value::Float(f) => !f.is_nan() && !f.is_inf() && *f != Float::from(0.0),
+        Value::Text(s) => !s.is_empty(),
+        Value::Time(_) => true,
+        Value::Duration(d) => !d.is_zero(),
+        Value::List(l) => !l.is_empty(),
+        Value::Map(m) => !m.is_empty(),
+        Value::Null => false,
+    }
+}
+
+#[inline(always)]
+pub fn value_exists(value: &Value) -> bool {
+    !matches!(value, Value::Null)
+}
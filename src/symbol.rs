@@ -0,0 +1,120 @@
+use std::{cmp::Ordering, fmt, hash::{Hash, Hasher}, ops::Deref, sync::{Arc, LazyLock, RwLock}};
+
+use ahash::{AHashSet, RandomState};
+
+/// The symbols written in programs, the texts are never freed.
+/// Symbols made while a program runs aren't added, so it only grows with the code that gets parsed.
+static INTERNER: LazyLock<RwLock<AHashSet<&'static str>>> = LazyLock::new(Default::default);
+
+/// Fixed seeds, so that symbols with the same text get the same hash however they were made
+static HASHER: RandomState = RandomState::with_seeds(0x5bec_4a11, 0x73b0_1d5e, 0x9e37_79b9, 0x85eb_ca6b);
+
+#[derive(Clone)]
+enum SymbolText {
+    Interned(&'static str),
+    Owned(Arc<str>),
+}
+
+/// A symbol (`abc_abc`), its hash is computed once, so hashing and comparing it rarely touches its text.
+/// The ones written in programs are interned and shared, the ones made while running own their text,
+/// which counts towards the memory of the program like texts do.
+///
+/// Symbols aren't `Copy` ids: a global table would have to keep every symbol a program ever makes
+/// (`a + b` in a loop makes new ones forever), and a table owned by the interpreter would have to be passed
+/// to everything that prints, compares or sorts values. Cloning an interned symbol copies a pointer and
+/// its hash, an owned one bumps a reference count.
+#[derive(Clone)]
+pub struct Symbol {
+    hash: u64,
+    text: SymbolText,
+}
+
+impl Symbol {
+    /// A symbol made while running, like the result of `a + b` or an input
+    pub fn new(text: &str) -> Symbol {
+        Symbol { hash: HASHER.hash_one(text), text: SymbolText::Owned(text.into()) }
+    }
+
+    /// A symbol written in the code, shared with every other one with the same text
+    pub fn intern(text: &str) -> Symbol {
+        let hash = HASHER.hash_one(text);
+        if let Some(&text) = INTERNER.read().unwrap().get(text) {
+            return Symbol { hash, text: SymbolText::Interned(text) }
+        }
+
+        let mut interner = INTERNER.write().unwrap();
+        // another thread could have added it in the meantime
+        let text = match interner.get(text) {
+            Some(&text) => text,
+            None => {
+                let text: &'static str = Box::leak(text.into());
+                interner.insert(text);
+                text
+            },
+        };
+        Symbol { hash, text: SymbolText::Interned(text) }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match &self.text {
+            SymbolText::Interned(text) => text,
+            SymbolText::Owned(text) => text,
+        }
+    }
+}
+
+impl Deref for Symbol {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl PartialEq for Symbol {
+    fn eq(&self, other: &Self) -> bool {
+        self.hash == other.hash && (std::ptr::eq(self.as_str(), other.as_str()) || self.as_str() == other.as_str())
+    }
+}
+
+impl Eq for Symbol {}
+
+impl Hash for Symbol {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.hash.hash(state)
+    }
+}
+
+/// Symbols are sorted by their text, like the strings they replace
+impl PartialOrd for Symbol {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Symbol {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match self == other {
+            true => Ordering::Equal,
+            false => self.as_str().cmp(other.as_str()),
+        }
+    }
+}
+
+impl fmt::Debug for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl From<&str> for Symbol {
+    fn from(text: &str) -> Self {
+        Symbol::new(text)
+    }
+}
@@ -0,0 +1,64 @@
+use crate::{ast::Value, golden::Case, run::{BufferIo, Engine, Limits}, Interpreter};
+
+use super::both_engines;
+
+#[test]
+fn engines_agree_on_examples() {
+    for path in crate::golden::discover(&["examples".into()]).unwrap() {
+        // like the golden test, whose clock makes `µ` read the same in both engines
+        // (its input doesn't print memory with times, which show the instant the interpreter got created)
+        let case = Case::load(&path).unwrap();
+        let input = case.input.iter().map(String::as_str).collect::<Vec<_>>();
+        both_engines(&case.code, &input, |interpreter| interpreter.set_clock(case.clock));
+    }
+}
+
+#[test]
+fn resolved_labels() {
+    let counter = "|< n <= 0 [<] loop |< n + 1 |< c <= §n < 3 ? [>] loop |< n {%}";
+    assert_eq!(both_engines(counter, &[], |_| {}), "3\n");
+
+    // jumping before the label is stored does nothing
+    assert_eq!(both_engines("[>] f |< a {@} [<] f [>] f", &[], |_| {}), "a\n");
+
+    // labels that are also changed by the program are read from memory
+    assert_eq!(both_engines("[<] a |< a {%} + 2 [>] a", &[], |_| {}), "1\n");
+    assert_eq!(both_engines("|< x <= a [<] a |< §x {%} + 2 [>] a", &[], |_| {}), "3\n");
+
+    // the pointer gets moved to the label, so `<= 4` changes it
+    let moved = "|< n <= 0 [<] top <= 4 |< n + 1 |< c <= §n < 4 ? [>] top |< n {%}";
+    let setup = |interpreter: &mut Interpreter<BufferIo>| {
+        interpreter.run_until(2).unwrap();
+        interpreter.set_pointer(Value::Symbol("top".into()));
+    };
+    assert_eq!(both_engines(moved, &[], setup), "4\n");
+
+    // and so does changing the memory from outside
+    let setup = |interpreter: &mut Interpreter<BufferIo>| {
+        interpreter.run_until(4).unwrap();
+        interpreter.memory_mut().insert(Value::Symbol("loop".into()), Value::SmallInt(0));
+    };
+    both_engines(counter, &[], setup);
+}
+
+#[test]
+fn fused_statements() {
+    // the step limit stops between a load and the statement after it
+    for max_steps in 1..6 {
+        let run = |engine| {
+            let mut interpreter = Interpreter::with_io(crate::parse("|< a <= 1 |< b <= 2 {%}").unwrap(), BufferIo::default());
+            interpreter.set_engine(engine);
+            interpreter.set_limits(Limits { max_steps: Some(max_steps), ..Default::default() });
+            let error = interpreter.run_to_end().err();
+            (error, interpreter.steps(), interpreter.memory().len())
+        };
+        assert_eq!(run(Engine::Ast), run(Engine::Bytecode), "{max_steps}");
+    }
+
+    // errors point at the statement after the load
+    let error = both_engines("|< a <= 1 |< a \\ 0", &[], |_| {});
+    assert_eq!(error, "");
+    let program = crate::parse("|< a <= 1 |< a \\ 0").unwrap();
+    let error = Interpreter::with_io(program, BufferIo::default()).run_to_end().unwrap_err();
+    assert_eq!(error.statement_index, 3);
+}
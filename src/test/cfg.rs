@@ -0,0 +1,57 @@
+use crate::cfg::{Block, Edge, EdgeKind, Graph, Target};
+
+fn graph(code: &str) -> Graph {
+    Graph::build(&crate::parse(code).unwrap())
+}
+
+#[test]
+fn cfg_blocks() {
+    let jump = |label: &str, target| Edge { kind: EdgeKind::Jump(label.to_string()), target };
+    let next = |target| Edge { kind: EdgeKind::Next, target };
+
+    assert_eq!(graph("|< a <= 3 [<] loop - 1 ? [>] loop {%}").blocks, [
+        Block { start: 0, end: 2, edges: vec![next(Target::Block(1))] },
+        Block { start: 2, end: 5, edges: vec![
+            Edge { kind: EdgeKind::Holds('?'), target: Target::Block(2) },
+            Edge { kind: EdgeKind::Skips(1), target: Target::Block(3) },
+        ] },
+        Block { start: 5, end: 6, edges: vec![jump("loop", Target::Block(1))] },
+        Block { start: 6, end: 7, edges: vec![next(Target::End)] },
+    ]);
+
+    // dynamic jumps, labels changed with math and labels that aren't defined yet
+    assert_eq!(graph("[>] §a").blocks[0].edges, [jump("§a", Target::Unknown), next(Target::End)]);
+    assert_eq!(graph("[<] a |< a + 1 [>] a").blocks[0].edges, [jump("a", Target::Block(0)), jump("a", Target::Unknown)]);
+    assert_eq!(graph("[>] a [<] a").blocks[0].edges, [jump("a", Target::Block(1)), next(Target::Block(1))]);
+    assert_eq!(graph("!!! {%}").blocks[0].edges[1], Edge { kind: EdgeKind::Skips(3), target: Target::End });
+
+    // calls come back after themselves, returns go after every call or to the next statement
+    assert_eq!(graph("[<] f {%} [<:] [>:] f {@}").blocks, [
+        Block { start: 0, end: 3, edges: vec![
+            Edge { kind: EdgeKind::Return, target: Target::Block(2) },
+            next(Target::Block(1)),
+        ] },
+        Block { start: 3, end: 4, edges: vec![
+            Edge { kind: EdgeKind::Call("f".to_string()), target: Target::Block(0) },
+            next(Target::Block(2)),
+        ] },
+        Block { start: 4, end: 5, edges: vec![next(Target::End)] },
+    ]);
+}
+
+#[test]
+fn cfg_output() {
+    let code = "[<] loop\n|< a <= /\"x\"/ ?\n[>] loop";
+    let parsed = crate::parse(code).unwrap();
+    let graph = Graph::build(&parsed);
+
+    let dot = graph.to_dot(code, &parsed);
+    assert!(dot.starts_with("digraph program {\n"), "{dot}");
+    assert!(dot.contains("b0 [label=\"line 1\\l[<] loop\\l|< a <= /\\\"x\\\"/ ?\\l\"];"), "{dot}");
+    assert!(dot.contains("b0 -> end [label=\"skip 1\"];"), "{dot}");
+    assert!(dot.contains("b1 -> b0 [label=\"[>] loop\"];"), "{dot}");
+
+    let mermaid = graph.to_mermaid(code, &parsed);
+    assert!(mermaid.contains("b1[\"line 3<br/>[#gt;] loop\"]"), "{mermaid}");
+    assert!(mermaid.contains("b0 -->|\"?\"| b1"), "{mermaid}");
+}
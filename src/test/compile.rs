@@ -0,0 +1,296 @@
+use std::{path::{Path, PathBuf}, process::{Command, Output, Stdio}, io::Write};
+
+use crate::{ast::Value, compile, golden::{self, Case}, run::{BufferIo, Clock, Limits, Operator, Runtime}, Interpreter, Status};
+
+#[test]
+fn rust_runtime() {
+    // |< a <= 3 [<] loop {%} - 1 ? [>] loop, by hand
+    let mut rt = Runtime::with_io(BufferIo::default());
+    let (a, one, three, label) = (Value::Symbol("a".into()), Value::SmallInt(1), Value::SmallInt(3), Value::Symbol("loop".into()));
+    let print = crate::parse("{%}").unwrap().remove(0).kind;
+
+    rt.load(&a, 0);
+    rt.assign(&three, 0);
+    rt.define(&label, 0, 3);
+    let mut index = 4;
+    while index != 8 {
+        index = match index {
+            4 => { rt.log(&print).unwrap(); 5 },
+            5 => { rt.operate(Operator::Minus, &one, 0).unwrap(); 6 },
+            6 => if rt.truthy() { 7 } else { 8 },
+            _ => rt.jump(&label, 0).unwrap(),
+        };
+    }
+    assert_eq!(rt.io().output, "3\n2\n1\n");
+    assert_eq!(rt.memory().get(&a), Some(&Value::SmallInt(0)));
+
+    assert!(rt.operate(Operator::Divide, &Value::SmallInt(0), 0).is_err());
+    rt.input().unwrap();
+    assert_eq!(rt.memory().get(&a), Some(&Value::Null));
+
+    // the options of the generated programs
+    let mut rt = Runtime::from_args(["--max-steps", "1", "--clock", "fixed"].map(String::from)).unwrap();
+    assert!(rt.step().is_ok());
+    assert_eq!(rt.step().unwrap_err().to_string(), "Limit exceeded: more than 1 statements executed");
+    assert_eq!(Runtime::from_args(["--clock".to_string()]).unwrap_err(), "missing value for '--clock'");
+    assert_eq!(Runtime::from_args(["--engine", "ast"].map(String::from)).unwrap_err(), "unknown option '--engine' (expected '--clock' or '--max-steps')");
+}
+
+#[test]
+fn rust_output() {
+    let code = "|< a <= -1.50 |< b <= -1.50 |< a + §b ? {%} [>] /end/";
+    let rust = compile::rust::transpile(code, &crate::parse(code).unwrap(), "test.specky");
+
+    assert!(rust.starts_with("// Generated by `speckylang compile --target rust` from test.specky\n"));
+    assert!(rust.contains("        Value::Float(-\"1.50\".parse::<Float>().unwrap()),\n"));
+    // literals are created once
+    assert_eq!(rust.matches("parse::<Float>").count(), 1);
+    assert!(rust.contains("    let mut rt = Runtime::from_args(std::env::args().skip(1))"));
+    assert!(rust.contains("    while index < 9 {\n        let next: Result<usize, RuntimeErrorKind> = rt.step().and_then(|()| match index {\n"));
+    assert!(rust.contains("            5 => rt.operate(Operator::Plus, &constants[2], 1).map(|()| 6),\n"));
+    assert!(rust.contains("            6 => Ok(if rt.truthy() { 7 } else { 8 }),\n"));
+    assert!(rust.contains("            8 => Ok(rt.jump(&constants[3], 0).unwrap_or(9)),\n"));
+
+    // maps sort their entries, floats keep their own digits
+    let code = "|< m <= {2.50 => -1.0 1.5 => [0.10]}";
+    let rust = compile::rust::transpile(code, &crate::parse(code).unwrap(), "test.specky");
+    assert!(rust.contains("Value::Map(Map::from_iter([(Value::Float(\"1.5\".parse::<Float>().unwrap()), Value::List(vec![Value::Float(\"0.10\".parse::<Float>().unwrap())])), \
+        (Value::Float(\"2.50\".parse::<Float>().unwrap()), Value::Float(-\"1.0\".parse::<Float>().unwrap()))]))"));
+}
+
+/// Corners of the operations and log statements that the examples don't reach
+const OPERATIONS: &str = r"
+|< a <= /héllo/ {@} {$} ~ 1 {%}
+|< b <= /ab/ * -3 {%} |< c <= /abcd/ * 2 {%}
+|< d <= -7 %% 3 {%} |< e <= 7 %% -3 {%} |< f <= -7 % 3 {%} |< g <= -7 \ 2 {%}
+|< h <= abc - ab {%} |< i <= abc + def {%} {;} |< j <= /x/ + 5 {%} |< k <= 3 > 2 {%} |< l <= ab < abc {%}
+|< m <= 65 {$} |< n <= true {$} |< o <= hi {$} {%$}
+|< p <= 2 ^ 100 {%} |< q <= null {%} {%$\}
+|< r <= a {%} => zz |< zz {%} <=> r {%} |< r {%}
+|< s <= /line1\nl2/ {%^}
+|< t <= /abc/ {%~} |< u <= 3 {%} & 0 {%} >-< 1 {%} | 0 {%}
+|< v <= /12/ {%<$} {;} {%}
+|< w <= /ab/ >< /ab/ {%} |< x <= null = null {%} |< y <= 1 ~ 0 {%} |< z <= /abc/ ~ 9 {%} {°°%}
+|< aa <= 12 .& 10 {%} .| 5 {%} .>-< -1 {%} .<< 100 {%} .>> 98 {%} |< ab <= -7 .>> 1 {%} .~ §ab {%}
+|< ba <= 3 ^ 90 {%} * -7 {%} \ 12345678901234567 {%} % 98765 {%} |< bb <= 2 ^ 127 {*%} - 1 {*%} * -1 - 2 {*%}
+|< bc <= -3 ^ 81 .& 2 ^ 140 - 1 {%} .| -5 {%} .>-< 2 ^ 130 {%} .>> 70 {%} .<< 33 {%} .~ §bc {%} |< bd <= 2 ^ 200 - 1 %% -1000000007 {%}
+{+$}
+";
+
+/// The examples that end by themselves and [`OPERATIONS`], with their code and what they print
+fn halting_examples(input: &[&str]) -> Vec<(PathBuf, String, String)> {
+    let mut examples = vec![];
+    let mut interpreter = Interpreter::with_io(crate::parse(OPERATIONS).unwrap(), BufferIo::default());
+    interpreter.run_to_end().unwrap();
+    examples.push((PathBuf::from("operations"), OPERATIONS.to_string(), interpreter.stdout().to_string()));
+
+    for path in crate::golden::discover(&[PathBuf::from("examples")]).unwrap() {
+        let code = std::fs::read_to_string(&path).unwrap();
+        // the time is different on every run
+        if code.contains('µ') {
+            continue
+        }
+
+        let mut interpreter = Interpreter::with_io(crate::parse(&code).unwrap(), BufferIo::with_input(input.iter().copied()));
+        interpreter.set_limits(Limits { max_steps: Some(1_000_000), ..Default::default() });
+        if interpreter.run_to_end() == Ok(Status::Halted) {
+            let stdout = interpreter.stdout().to_string();
+            examples.push((path, code, stdout));
+        }
+    }
+    examples
+}
+
+fn run_with_input(mut command: Command, input: &[&str]) -> Output {
+    let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+    // programs that end without reading all of it close stdin early
+    let _ = child.stdin.take().unwrap().write_all(input.iter().map(|line| format!("{line}\n")).collect::<String>().as_bytes());
+    child.wait_with_output().unwrap()
+}
+
+const INPUT: [&str; 5] = ["5", "3", "+++[->++<]>.", "n", "80"];
+
+/// Float digits and rounding that only the Rust output shares with the interpreter
+const FLOATS: &str = r"
+|< a <= 1.0 \ 3.0 {%} * 3 {%} - 1 {%} |< b <= 2.0 ^ 0.5 {%} ^ 2 {%} |< c <= 0.1 + 0.2 {%} = 0.3 {%}
+|< d <= 10.0 ^ 30 {%} + 1 {%} |< e <= 7.5 % 2 {%} |< f <= -7.5 %% 2 {%} |< g <= 1 \ 7.0 {%} * 7 {%} {%$}
+|< h <= 2 ^ 100 * 1.5 {%} |< i <= 3.14159 ~ 2 {%} |< j <= /2.50/ + 0.25 {%}
+";
+
+/// Builds [`OPERATIONS`], [`FLOATS`] and every example with `rustc` and compares what they print with the interpreter.
+/// The examples run like in the golden tests: with their input, clock and steps.
+#[test]
+fn rust_matches_interpreter() {
+    let target = Path::new("target/transpiled");
+    let built = Command::new(env!("CARGO")).args(["build", "--lib", "--target-dir"]).arg(target).status().unwrap();
+    assert!(built.success());
+    let debug = target.join("debug");
+
+    let mut cases = [("operations", OPERATIONS), ("floats", FLOATS)].map(|(name, code)| Case {
+        path: PathBuf::from(name),
+        code: code.to_string(),
+        input: vec![],
+        expected: None,
+        unchecked: None,
+        limits: Limits::default(),
+        clock: Clock::Fixed,
+    }).to_vec();
+    for path in golden::discover(&[PathBuf::from("examples")]).unwrap() {
+        cases.push(Case::load(&path).unwrap());
+    }
+
+    // rustc takes a while, so every program gets built at once
+    let programs = std::thread::scope(|scope| {
+        let builds = cases.iter().map(|case| scope.spawn(|| {
+            let name = case.path.with_extension("").to_str().unwrap().replace(['/', '\\'], "_");
+            let source = debug.join(format!("{name}.rs"));
+            std::fs::write(&source, compile::rust::transpile(&case.code, &crate::parse(&case.code).unwrap(), &name)).unwrap();
+            let compiled = Command::new("rustc")
+                .args(["--edition", "2021", "-o"]).arg(debug.join(&name)).arg(&source)
+                .arg("--extern").arg(format!("speckylang={}", debug.join("libspeckylang.rlib").display()))
+                .arg("-L").arg(debug.join("deps"))
+                .status().unwrap();
+            assert!(compiled.success(), "{}", case.path.display());
+            debug.join(name)
+        })).collect::<Vec<_>>();
+        builds.into_iter().map(|build| build.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    for (case, program) in cases.iter().zip(programs) {
+        let max_steps = case.limits.max_steps.unwrap_or(golden::DEFAULT_MAX_STEPS);
+        let mut interpreter = Interpreter::with_io(crate::parse(&case.code).unwrap(), BufferIo::with_input(case.input.iter().cloned()));
+        interpreter.set_limits(Limits { max_steps: Some(max_steps), ..Default::default() });
+        interpreter.set_clock(case.clock);
+        // running out of input reads empty lines, like stdin at its end
+        let halted = loop {
+            match interpreter.run_to_end() {
+                Ok(Status::WaitingForInput) => interpreter.provide_input(""),
+                result => break result == Ok(Status::Halted),
+            }
+        };
+
+        let clock = match case.clock {
+            Clock::Real => "real".to_string(),
+            Clock::Fixed => "fixed".to_string(),
+            Clock::Step(step) => format!("step:{}ns", step.as_nanos()),
+        };
+        let mut command = Command::new(program);
+        command.args(["--clock", &clock, "--max-steps", &max_steps.to_string()]);
+        let output = run_with_input(command, &case.input.iter().map(String::as_str).collect::<Vec<_>>());
+        assert_eq!(output.status.success(), halted, "{}: {}", case.path.display(), String::from_utf8_lossy(&output.stderr));
+        // the error report follows the output, like with the interpreter
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        let report = stdout.strip_prefix(interpreter.stdout());
+        assert!(report.is_some_and(|report| report.is_empty() == halted), "{}:\n{stdout}", case.path.display());
+    }
+}
+
+#[test]
+fn c_output() {
+    let code = "|< a <= /a\\\\b\"?\\t/ |< b <= 1.50 {%}";
+    let statements = crate::parse(code).unwrap();
+
+    let unsupported = compile::c::transpile(code, &statements, "test.specky", false).unwrap_err();
+    assert_eq!(unsupported.message, "Floats need `--fixed-width` in C");
+    assert_eq!(&code[unsupported.area.0..unsupported.area.1], "1.50");
+
+    let c = compile::c::transpile(code, &statements, "test.specky", true).unwrap();
+    assert!(c.contains("#define SP_FIXED_WIDTH 1\n"));
+    assert!(c.contains("    constants[1] = sp_text(\"a\\\\b\\\"\\?\\011\", 6);\n"));
+    assert!(c.contains("    constants[3] = sp_float(1.50);\n"));
+    assert!(c.contains("            case 3: sp_assign(&rt, &constants[3], 0); index = 4; break;\n"));
+
+    let code = "|< a <= {b => [1]}";
+    let unsupported = compile::c::transpile(code, &crate::parse(code).unwrap(), "test.specky", true).unwrap_err();
+    assert_eq!(unsupported.message, "Maps aren't supported in C");
+
+    // C has no 128-bit literals
+    let code = "|< a <= 170141183460469231731687303715884105727 - 9223372036854775808 ^ 2";
+    let c = compile::c::transpile(code, &crate::parse(code).unwrap(), "test.specky", false).unwrap();
+    assert!(c.contains("sp_small((sp_int)((sp_uint)0x7fffffffffffffffULL << 64 | 0xffffffffffffffffULL))"));
+    assert!(c.contains("sp_small((sp_int)((sp_uint)0x0ULL << 64 | 0x8000000000000000ULL))"));
+    assert!(c.contains("sp_small((sp_int)2LL)"));
+}
+
+/// Builds every example with `cc` and compares what it prints with the interpreter,
+/// the ones that go past 128 bits have to stop.
+/// Programs with floats are only built, wrapping integers can change what they do.
+#[test]
+#[ignore]
+fn c_matches_interpreter() {
+    let target = Path::new("target/transpiled/c");
+    std::fs::create_dir_all(target).unwrap();
+
+    for (path, code, stdout) in halting_examples(&INPUT) {
+        let statements = crate::parse(&code).unwrap();
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = target.join(format!("{name}.c"));
+        let exact = compile::c::transpile(&code, &statements, name, false);
+        let c = exact.clone().or_else(|_| compile::c::transpile(&code, &statements, name, true)).unwrap();
+        std::fs::write(&source, c).unwrap();
+        let compiled = Command::new("cc").args(["-O2", "-Wall", "-Wextra", "-Werror", "-o"]).arg(target.join(name)).arg(&source).arg("-lm").status().unwrap();
+        assert!(compiled.success(), "{}", path.display());
+        if exact.is_err() {
+            continue
+        }
+
+        let output = run_with_input(Command::new(target.join(name)), &INPUT);
+        let printed = String::from_utf8(output.stdout).unwrap();
+        if output.status.success() {
+            assert_eq!(printed, stdout, "{}", path.display());
+        } else {
+            assert!(String::from_utf8(output.stderr).unwrap().contains("Integer overflow"), "{}", path.display());
+            assert!(stdout.starts_with(&printed), "{}", path.display());
+        }
+    }
+}
+
+#[test]
+fn js_output() {
+    let code = "|< a <= /é\\n/ |< b <= -1.50 |< a + §b {%} ' [>] 12345678901234567890123456789012345678901234567890";
+    let js = compile::js::transpile(code, &crate::parse(code).unwrap(), "test.specky");
+
+    assert!(js.starts_with("// Generated by `speckylang compile --target js` from test.specky\n"));
+    assert!(js.contains("        text(\"\\u{e9}\\u{a}\"),\n"));
+    assert!(js.contains("        float(d.neg(d.parse(\"1.50\"))),\n"));
+    assert!(js.contains("        integer(12345678901234567890123456789012345678901234567890n),\n"));
+    assert!(js.contains("                case 5: rt.operate(Operator.Plus, constants[2], 1); index = 6; break;\n"));
+    assert!(js.contains("                case 7: await rt.input(); index = 8; break;\n"));
+}
+
+/// The generated code without the runtime, `UPDATE_SNAPSHOTS=1 cargo test` rewrites it
+#[test]
+fn js_snapshot() {
+    let path = Path::new("src/test/snapshots/factorial.js");
+    let code = std::fs::read_to_string("examples/factorial.specky").unwrap();
+    let js = compile::js::transpile(&code, &crate::parse(&code).unwrap(), "examples/factorial.specky")
+        .replacen(compile::js::RUNTIME, "", 1);
+
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(path, &js).unwrap();
+    }
+    assert_eq!(js, std::fs::read_to_string(path).unwrap());
+}
+
+/// Runs every example with `node` and compares what it prints with the interpreter,
+/// does nothing if node isn't installed
+#[test]
+fn js_matches_interpreter() {
+    if Command::new("node").arg("--version").output().is_err() {
+        return
+    }
+    let target = Path::new("target/transpiled/js");
+    std::fs::create_dir_all(target).unwrap();
+
+    for (path, code, stdout) in halting_examples(&INPUT) {
+        let name = path.file_stem().unwrap().to_str().unwrap();
+        let source = target.join(format!("{name}.mjs"));
+        std::fs::write(&source, compile::js::transpile(&code, &crate::parse(&code).unwrap(), name)).unwrap();
+
+        let mut node = Command::new("node");
+        node.arg(&source);
+        let output = run_with_input(node, &INPUT);
+        assert!(output.status.success(), "{}: {}", path.display(), String::from_utf8_lossy(&output.stderr));
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), stdout, "{}", path.display());
+    }
+}
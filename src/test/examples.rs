@@ -1,6 +1,5 @@
 use itertools::Itertools;
 
-use crate::ast;
 use crate::test_read;
 use crate::test_run;
 
@@ -36,6 +35,7 @@ fn bottles_of_beer() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_fold)]
 fn factorial() {
     let output = test_run!(test_read!("examples/factorial.specky"));
 
@@ -84,9 +84,7 @@ fn bubblesort() {
         assert_eq!(
             test_run!(
                 test_read!("examples/bubblesort.specky"),
-                [
-                    ast::Statement::Assign(ast::Expression { value: ast::Value::Text(array.iter().join(" ")), reader: 0 }),
-                ]
+                [array.iter().join(" ")]
             ).stdout.lines().rev().find_map(|s| (!s.is_empty()).then_some(s.trim())),
             Some(sorted.iter().join(" ").as_str())
         )
@@ -94,16 +92,14 @@ fn bubblesort() {
 }
 
 #[test]
+#[allow(clippy::redundant_closure, clippy::useless_conversion)]
 fn brainfuck() {
     let string = test_read!("examples/brainfuck.specky");
 
     let run = |instructions: &str, debug: bool| {
         test_run!(
             string,
-            [
-                ast::Statement::Assign(ast::Expression { value: ast::Value::Text(instructions.to_string()), reader: 0 }),
-                ast::Statement::Assign(ast::Expression { value: ast::Value::Boolean(debug), reader: 0 }),
-            ]
+            [instructions, debug]
         ).stdout
     };
 
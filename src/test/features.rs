@@ -1,6 +1,10 @@
+use std::time::Duration;
+
 use itertools::Itertools;
 
-use crate::{test_run, test_run_assert};
+use crate::{ast::{Integer, StatementKind, Symbol, Value}, run::{BufferIo, Clock, Engine, ErrorPolicy, Limit, Limits, RuntimeErrorKind}, CodeArea, Interpreter, Parser, Status, test_engines_assert, test_run, test_run_assert};
+
+use super::{both_engines, run_engines};
 
 #[test]
 fn print_test() {
@@ -15,6 +19,10 @@ fn print_test() {
 
     let output = test_run!(r"|< ab {~@\}");
     assert_eq!(output.stdout, "ba");
+
+    // `~~` reverses twice
+    let output = test_run!(r"|< ab {~~@}");
+    assert_eq!(output.stdout, "ab\n");
 }
 
 #[test]
@@ -107,3 +115,392 @@ fn some_math() {
         "<= -5 %% 3 {%}" => "1\n",
     );
 }
+
+#[test]
+fn text_times_float() {
+    // fractions count characters, not bytes
+    test_run_assert!(
+        "<= /é/ * 1.5 {%}" => "/éé/\n",
+        "<= /aéb/ * 1.5 {%}" => "/aébaé/\n",
+        "<= /aéb/ * -1.5 {%}" => "/éabéa/\n",
+        "<= /日本/ * 0.4 {%}" => "/日/\n",
+    );
+}
+
+#[test]
+fn persistent_state() {
+    let mut interpreter = Interpreter::new(vec![]);
+
+    let mut run = |code: &str| {
+        let start = interpreter.stdout().len();
+        interpreter.extend_program(Parser::new(code).parse_statements().unwrap());
+        interpreter.run_to_end().unwrap();
+        interpreter.stdout()[start..].to_string()
+    };
+
+    assert_eq!(run("|< a <= 5"), "");
+    assert_eq!(run("+ 2 {%}"), "7\n");
+    assert_eq!(run("|< §a {@}"), "7\n");
+}
+
+#[test]
+fn step_api() {
+    let program = Parser::new("|< a <= 1 [<] loop |< a + 1 |< b <= §a < 5 ? [>] loop").parse_statements().unwrap();
+    let mut interpreter = Interpreter::new(program);
+
+    assert_eq!(interpreter.status(), Status::Running);
+    assert_eq!(interpreter.step().unwrap(), Status::Running);
+    assert_eq!(interpreter.pointer(), &Value::Symbol("a".into()));
+    assert_eq!(interpreter.program_counter(), 1);
+
+    assert_eq!(interpreter.run_until(2).unwrap(), Status::Running);
+    assert_eq!(interpreter.program_counter(), 3);
+    assert_eq!(interpreter.memory().get(&Value::Symbol("loop".into())), Some(&Value::SmallInt(3)));
+
+    assert_eq!(interpreter.run_to_end().unwrap(), Status::Halted);
+    assert_eq!(interpreter.memory().get(&Value::Symbol("a".into())), Some(&Value::SmallInt(5)));
+    assert_eq!(interpreter.memory().get(&Value::Symbol("b".into())), Some(&Value::Boolean(false)));
+    assert_eq!(interpreter.read(&Value::Symbol("loop".into()), 1), &Value::SmallInt(3));
+    assert_eq!(interpreter.read(&Value::Symbol("loop".into()), 2), &Value::Null);
+    assert_eq!(interpreter.step().unwrap(), Status::Halted);
+}
+
+#[test]
+fn waiting_for_input() {
+    let program = Parser::new("|< a ' {%}").parse_statements().unwrap();
+    let mut interpreter = Interpreter::with_io(program, BufferIo::default());
+
+    assert_eq!(interpreter.run_to_end().unwrap(), Status::WaitingForInput);
+    assert_eq!(interpreter.program_counter(), 1);
+    assert_eq!(interpreter.step().unwrap(), Status::WaitingForInput);
+
+    interpreter.provide_input("42");
+    assert_eq!(interpreter.run_to_end().unwrap(), Status::Halted);
+    assert_eq!(interpreter.stdout(), "42\n");
+}
+
+#[test]
+fn scripted_input() {
+    let ran = test_run!("|< a ' |< b ' |< c ' |< a {%} |< b {%} |< c {%}", ["12", "hello world", "true"]);
+    assert_eq!(ran.stdout, "12\n/hello world/\ntrue\n");
+
+    let program = Parser::new("|< a ' {%}").parse_statements().unwrap();
+    let mut interpreter = Interpreter::with_io(program, BufferIo::with_input(["5"]));
+    interpreter.set_capture_output(false);
+    interpreter.run_to_end().unwrap();
+    assert_eq!(interpreter.stdout(), "");
+    assert_eq!(interpreter.io().output, "5\n");
+}
+
+#[test]
+fn runtime_errors() {
+    for (code, kind) in [
+        ("<= 1 \\ 0", RuntimeErrorKind::DivisionByZero),
+        ("<= 1 % 0", RuntimeErrorKind::DivisionByZero),
+        ("<= 1 %% 0", RuntimeErrorKind::DivisionByZero),
+        ("<= 100000000000000000000000000000000000000000 \\ 0", RuntimeErrorKind::DivisionByZero),
+        ("<= 2 ^ -1", RuntimeErrorKind::NegativeExponent),
+        ("<= 2 ^ 100000000000", RuntimeErrorKind::ExponentTooLarge),
+        ("<= 1 .<< 100000000000", RuntimeErrorKind::ShiftTooLarge),
+        ("<= 1 .>> -100000000000", RuntimeErrorKind::ShiftTooLarge),
+        // without a memory limit to go over
+        ("<= /ab/ * 10000000000000000000", RuntimeErrorKind::ValueTooLarge),
+        ("<= [1] * -10000000000000000000", RuntimeErrorKind::ValueTooLarge),
+    ] {
+        let error = test_run!(format!("{{@}} {code} {{%}}")).error.expect(code);
+        assert_eq!(error.kind, kind, "{code}");
+        assert_eq!(error.statement_index, 2, "{code}");
+
+        let mut interpreter = Interpreter::with_io(crate::parse(code).unwrap(), BufferIo::default());
+        interpreter.set_error_policy(ErrorPolicy::Null);
+        assert_eq!(interpreter.run_to_end(), Ok(Status::Halted), "{code}");
+        assert_eq!(interpreter.memory().get(&Value::Null), Some(&Value::Null), "{code}");
+    }
+}
+
+#[test]
+fn small_int_overflow() {
+    test_run_assert!(
+        "<= 2 ^ 127 {%}" => "170141183460469231731687303715884105728\n",
+        "<= 3 ^ 3 {%}" => "27\n",
+        "<= 0 - 170141183460469231731687303715884105728 \\ -1 {%}" => "170141183460469231731687303715884105728\n",
+        "<= 0 - 170141183460469231731687303715884105728 % -1 {%}" => "0\n",
+        "<= 170141183460469231731687303715884105727 %% 170141183460469231731687303715884105727 {%}" => "0\n",
+        "<= 5 %% 170141183460469231731687303715884105727 {%}" => "5\n",
+    );
+}
+
+#[test]
+fn source_areas() {
+    let code = "|< a  <= §§b\n{%} ??? [>] - 5";
+    let parsed = crate::parse(code).unwrap();
+
+    let areas = parsed.iter().map(|statement| &code[statement.area.0..statement.area.1]).collect::<Vec<_>>();
+    assert_eq!(areas, ["|< a", "<= §§b", "{%}", "???", "[>] - 5"]);
+
+    let StatementKind::Assign(expression) = &parsed[1].kind else { panic!() };
+    assert_eq!(&code[expression.area.0..expression.area.1], "§§b");
+    let StatementKind::Jump(expression) = &parsed[4].kind else { panic!() };
+    assert_eq!(&code[expression.area.0..expression.area.1], "- 5");
+
+    let error = test_run!("|< a <= 1\n\\ 0").error.unwrap();
+    assert_eq!(error.area, CodeArea(10, 13));
+}
+
+#[test]
+fn profiling() {
+    // the bytecode runs loads together with the statement after them, they still get counted
+    for engine in [Engine::Ast, Engine::Bytecode] {
+        let program = crate::parse("|< a <= 3 [<] loop - 1 ? [>] loop {%}").unwrap();
+        let mut interpreter = Interpreter::with_io(program, BufferIo::default());
+        interpreter.set_engine(engine);
+        interpreter.set_profiling(true);
+        interpreter.run_to_end().unwrap();
+
+        let profile = interpreter.profile().unwrap();
+        let counts = profile.statements.iter().map(|statement| statement.count).collect::<Vec<_>>();
+        assert_eq!(counts, [1, 1, 1, 3, 3, 2, 1]);
+        assert_eq!(profile.jumps.get(&Value::Symbol("loop".into())), Some(&2));
+    }
+}
+
+#[test]
+fn execution_limits() {
+    let limited = |code: &str, limits: Limits| {
+        let mut interpreter = Interpreter::with_io(crate::parse(code).unwrap(), BufferIo::default());
+        interpreter.set_error_policy(ErrorPolicy::Null);
+        interpreter.set_limits(limits);
+        interpreter.run_to_end().map_err(|error| error.kind)
+    };
+    let exceeded = |limit| Err(RuntimeErrorKind::LimitExceeded(limit));
+
+    let steps = Limits { max_steps: Some(100), ..Default::default() };
+    assert_eq!(limited("[<] loop [>] loop", steps), exceeded(Limit::Steps(100)));
+    assert_eq!(limited("|< a <= 1 + 1", steps), Ok(Status::Halted));
+
+    let timeout = Limits { timeout: Some(Duration::from_millis(20)), ..Default::default() };
+    assert_eq!(limited("[<] loop [>] loop", timeout), exceeded(Limit::Timeout(Duration::from_millis(20))));
+    assert_eq!(limited("[<] loop |< a <= 1 [>] loop", timeout), exceeded(Limit::Timeout(Duration::from_millis(20))));
+    // the timeout counts from the first statement, until it gets restarted (like for every input of the repl)
+    let mut interpreter = Interpreter::with_io(crate::parse("|< a <= 1").unwrap(), BufferIo::default());
+    interpreter.set_limits(timeout);
+    interpreter.run_to_end().unwrap();
+    std::thread::sleep(Duration::from_millis(30));
+    interpreter.extend_program(crate::parse("|< b <= 2").unwrap());
+    assert_eq!(interpreter.run_to_end().map_err(|error| error.kind), exceeded(Limit::Timeout(Duration::from_millis(20))));
+    interpreter.restart_timeout();
+    assert_eq!(interpreter.run_to_end(), Ok(Status::Halted));
+
+    let entries = Limits { max_memory_entries: Some(3), ..Default::default() };
+    assert_eq!(limited("|< a <= 1 |< b <= 2 |< a <= 3", entries), Ok(Status::Halted));
+    assert_eq!(limited("|< a <= 0 [<] loop + 1 => §a [>] loop", entries), exceeded(Limit::MemoryEntries(3)));
+
+    let bytes = Limits { max_memory_bytes: Some(1000), ..Default::default() };
+    assert_eq!(limited("|< a <= /ab/ * 400", bytes), Ok(Status::Halted));
+    assert_eq!(limited("|< a <= /ab/ * 600", bytes), exceeded(Limit::MemoryBytes(1000)));
+    assert_eq!(limited("|< a <= /ab/ * 10000000000000000000000", bytes), exceeded(Limit::MemoryBytes(1000)));
+    assert_eq!(limited("|< a <= 2 ^ 100000", bytes), exceeded(Limit::MemoryBytes(1000)));
+    assert_eq!(limited("|< a <= 1 .<< 100000", bytes), exceeded(Limit::MemoryBytes(1000)));
+    assert_eq!(limited("|< a <= /ab/ * 400 |< b <= §a", bytes), exceeded(Limit::MemoryBytes(1000)));
+    assert_eq!(limited("|< a <= /ab/ * 400 |< a <= §a", bytes), Ok(Status::Halted));
+
+    // the memory that scoped calls keep counts too
+    assert_eq!(limited("|< a <= /ab/ * 300 [<] f [~>:] f", bytes), exceeded(Limit::MemoryBytes(1000)));
+    assert_eq!(limited("|< a <= 1 |< b <= 2 [<] f [~>:] f", entries), exceeded(Limit::MemoryEntries(3)));
+    assert_eq!(limited("|< a <= /ab/ * 200 [>] s [<] g [<:] [<] s [~>:] g [~>:] g", bytes), Ok(Status::Halted));
+}
+
+#[test]
+fn interned_symbols() {
+    let output = test_run!("|< bb <= b |< a <= /a/ |< ab <= ab + c {+$} |< §ab {@$}");
+    assert_eq!(
+        output.stdout,
+        "{\n\tSymbol(\"a\") => Text(\"a\")\n\tSymbol(\"ab\") => Symbol(\"abc\")\n\tSymbol(\"bb\") => Symbol(\"b\")\n}\n6382179\n",
+    );
+
+    // symbols made while running aren't interned, but still find the ones written in the code
+    let output = test_run!("|< ab <= 1 |< x <= a + b |< §x {%} |< y ' |< §y {%}", ["ab"]);
+    assert_eq!(output.stdout, "1\n1\n");
+    assert_eq!(Symbol::new("abc"), Symbol::intern("abc"));
+    assert_eq!(Symbol::new("abc"), Symbol::new(&format!("ab{}", 'c')));
+    assert!(Symbol::new("b") > Symbol::new("abc"));
+    assert_eq!(format!("{:?}", Value::Symbol("x_1".into())), "Symbol(\"x_1\")");
+}
+
+#[test]
+fn clocks() {
+    let timed = |code: &str, clock: Clock| both_engines(code, &["µ"], |interpreter| interpreter.set_clock(clock));
+
+    let code = "|< start <= µ |< start {%} {%$} |< input ' {%}";
+    assert_eq!(timed(code, Clock::Fixed), "0ns\n0\n0ns\n");
+    // `<= µ` is the second statement and the logs the fourth and fifth
+    assert_eq!(timed(code, Clock::Step(Duration::from_millis(1))), "2ms\n0.003\n1ms\n");
+
+    assert_eq!("real".parse(), Ok(Clock::Real));
+    assert_eq!("step:1ms".parse(), Ok(Clock::Step(Duration::from_millis(1))));
+    assert_eq!("step:2.5us".parse(), Ok(Clock::Step(Duration::from_nanos(2500))));
+    assert!("step:1h".parse::<Clock>().is_err());
+    assert!("step:10000000000000000000s".parse::<Clock>().is_err());
+    // virtual time stops instead of going past what the platform can hold
+    assert_eq!(timed("|< a <= µ ~ unix {%}", Clock::Step(Duration::MAX)), "3153600000\n");
+    assert!("virtual".parse::<Clock>().is_err());
+}
+
+#[test]
+fn time_arithmetic() {
+    let timed = |code: &str| both_engines(code, &[], |interpreter| interpreter.set_clock(Clock::Step(Duration::from_millis(1))));
+
+    // laps, `µ` reads 1ms more for every statement
+    assert_eq!(timed("|< a <= µ |< lap <= µ - §a {%} {%$} * 3 {%} \\ 2 {%} ~ us {%} |< lap <= §a - µ {%}"), "2ms\n0.002\n6ms\n3ms\n3000\nnull\n");
+    assert_eq!(timed("|< a <= µ |< b <= µ |< c <= §a < §b {%} |< c <= §a = §a {%} |< s <= 1 ~ s |< b <= §a + §s - §a {%}"), "true\ntrue\n1s\n");
+    // durations from numbers, compared and divided
+    assert_eq!(timed("|< m <= 1 ~ ms |< a <= 1500 ~ us {%} |< b <= §a > §m {%} |< c <= 2 ~ ms ~ ms {%} |< d <= 7 ~ ms % §a {%} ~ ns {%}"), "1.5ms\ntrue\n2\n1ms\n1000000\n");
+    assert_eq!(timed("|< a <= 3 ~ ms |< b <= 2 ~ ms - §a {%} |< c <= 0 - 1 |< d <= 1 ~ s * §c {%} |< e <= 0 ~ s & true {%} |< f <= 1 ~ ns & true {%}"), "null\nnull\nfalse\ntrue\n");
+    // virtual clocks start at the Unix epoch
+    let dates = "|< s <= 1709214300 ~ s |< a <= µ + §s |< b <= §a ~ unix {%} |< c <= §a ~ date {%} |< d <= §a ~ datetime {%} |< e <= §a ~ week {%}";
+    assert_eq!(timed(dates), "1709214300\n/2024-02-29/\n/2024-02-29T13:45:00Z/\nnull\n");
+
+    let runtime = crate::parse("|< a <= 2 ~ s \\ 0").unwrap();
+    let output = crate::run::run_with_io(&runtime, BufferIo::default());
+    assert_eq!(output.error.map(|error| error.kind), Some(RuntimeErrorKind::DivisionByZero));
+    assert_eq!(output.variables.get(&Value::Symbol(Symbol::new("a"))), Some(&Value::Duration(Duration::from_secs(2))));
+}
+
+#[test]
+fn lists() {
+    test_engines_assert!(
+        // indexes and length
+        "|< a <= [1 /x/ [2 3]] {%} |< b <= §a ~ 2 {%} |< c <= §a ~~ len {%} |< d <= §a ~ 3 {%} |< e <= §a ~ 0.5 {%}" => "[1 /x/ [2 3]]\n[2 3]\n3\nnull\n1\n",
+        // appending, concatenating and the `$` form
+        "|< a <= [104 105] {%$} + 33 {%$} + [1] {%} {*}" => "hi\nhi!\n[104 105 33 1]\nList\n",
+        // assigning an index
+        "|< a <= [1 2 3] |< p <= [1] + 9 |< a ~= §p {%} |< b <= [1 2] ~= [5 0] {%}" => "[1 9 3]\nnull\n",
+        // repeating, backwards with negative counts
+        "|< a <= [1 2] * 2 {%} * -1 {%} |< b <= [] * 3 {%}" => "[1 2 1 2]\n[2 1 2 1]\n[]\n",
+        // comparisons and truthiness
+        "|< a <= [1 2] = [1 2] {%} |< b <= [1 2] < [1 3] {%} |< c <= [1 2] < [1 2 0] {%} |< d <= [1 a] < [1 2] {%} |< e <= [1.5] > [1] {%}" => "true\ntrue\ntrue\nnull\ntrue\n",
+        "|< a <= [] & true {%} |< b <= [0] & true {%}" => "false\ntrue\n",
+    );
+
+    // lists count towards the memory limit, repeating one can't go past it
+    let mut interpreter = Interpreter::with_io(crate::parse("|< a <= [1 2 3] * 1000").unwrap(), BufferIo::default());
+    interpreter.set_limits(Limits { max_memory_bytes: Some(1000), ..Default::default() });
+    let error = interpreter.run_to_end().unwrap_err();
+    assert_eq!(error.kind, RuntimeErrorKind::LimitExceeded(Limit::MemoryBytes(1000)));
+    // lists can be memory keys
+    let output = test_run!("|< [1 2] <= 5 |< a <= [1 2] {%} |< §a {%}");
+    assert_eq!(output.stdout, "[1 2]\n5\n");
+}
+
+#[test]
+fn maps() {
+    test_engines_assert!(
+        // entries are sorted by key, later ones replace earlier ones
+        "|< m <= {b => 2 a => /x/ b => 3} {%} {*} |< a <= §m ~ a {%} |< c <= §m ~ c {%}" => "{a => /x/ b => 3}\nMap\n/x/\nnull\n",
+        "|< m <= {b => 2 a => 1} |< k <= §m ~~ keys {%} |< n <= §m ~~ len {%} |< e <= {} & true {%}" => "[a b]\n2\nfalse\n",
+        // any key can be read, properties have their own operator
+        "|< m <= {len => 7 keys => 8} |< a <= §m ~ len {%} |< b <= §m ~ keys {%} |< c <= §m ~~ len {%} |< d <= 5 ~~ len {%} |< e <= [1] ~~ keys {%}" => "7\n8\n2\nnull\nnull\n",
+        // inserting, removing with null and merging
+        "|< m <= {a => 1} ~= [[0] 5] {%} ~= [a null] {%} + {z => 104 y => 105} {%} {%$}" => "{a => 1 [0] => 5}\n{[0] => 5}\n{y => 105 z => 104 [0] => 5}\nih\u{5}\n",
+        "|< a <= {x => 1 y => 2} = {y => 2 x => 1} {%} |< b <= {x => 1} >< {x => 1.5} {%} |< c <= {x => 1} < {x => 2} {%}" => "true\ntrue\nnull\n",
+    );
+
+    // maps are memory keys, however their entries got inserted
+    let output = test_run!("|< {a => 1 b => 2} <= found |< m <= {b => 2} ~= [a 1] |< §m {%}");
+    assert_eq!(output.stdout, "found\n");
+}
+
+#[test]
+fn map_float_keys() {
+    use crate::ast::{Float, Map};
+
+    let nan = || Value::Float(num_bigfloat::NAN);
+    let float = |value: f64| Value::Float(Float::from(value));
+
+    // NaN goes after the other floats and is the same key every time, duplicates replace earlier entries
+    let map: Map = [(nan(), Value::SmallInt(1)), (float(2.5), Value::SmallInt(2)), (nan(), Value::SmallInt(3)), (float(-1.0), Value::SmallInt(4)), (float(2.5), Value::SmallInt(5))]
+        .into_iter().collect();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map.get(&nan()), Some(&Value::SmallInt(3)));
+    assert_eq!(map.get(&float(2.5)), Some(&Value::SmallInt(5)));
+    assert!(matches!(map.keys().last(), Some(Value::Float(key)) if key.is_nan()));
+    // also inside of lists, and maps with NaN are equal to themselves
+    let nested: Map = [(Value::List(vec![nan()]), Value::Map(map.clone())), (Value::List(vec![float(0.0)]), Value::SmallInt(6))].into_iter().collect();
+    assert_eq!(nested.get(&Value::List(vec![nan()])), Some(&Value::Map(map.clone())));
+    assert_eq!(nested, nested.clone());
+
+    assert_eq!(test_run!("|< m <= {1.5 => a 0.5 => b 1.5 => c} {%}").stdout, "{0.5 => b 1.5 => c}\n");
+}
+
+#[test]
+fn calls() {
+    test_engines_assert!(
+        // returns go back to after the last call, without a call they do nothing
+        "|< x <= 3 [<] double |< x * 2 [<:] [>:] double [>:] double {%} [<:] {%}" => "24\n24\n",
+        "|< n <= 3 [<] down |< n - 1 ? [>:] down {%} [<:] |< done {@}" => "0\n0\n0\ndone\n",
+        // scoped calls only keep the value at the pointer
+        "[<] f |< a <= 5 |< b <= 7 [<:] |< a <= 1 |< b <= 0 [~>:] f {%} |< a {%}" => "7\n1\n",
+        // calls to labels that aren't stored yet do nothing
+        "[>:] later {@} [<] later" => "null\n",
+    );
+
+    let depth = Limits { max_call_depth: Some(50), ..Default::default() };
+    let error = run_engines("[<] forever [>:] forever", &[], |interpreter| interpreter.set_limits(depth)).0.unwrap_err();
+    assert_eq!(error.kind, RuntimeErrorKind::LimitExceeded(Limit::CallDepth(50)));
+    assert_eq!(error.stack.len(), 50);
+
+    // the stack trace lists the calls that haven't returned
+    let error = run_engines("|< skip <= 1 [<] f |< skip !! <= 1 \\ 0 [<:] |< skip <= 0 [>:] f", &[], |_| {}).0.unwrap_err();
+    assert_eq!(error.kind, RuntimeErrorKind::DivisionByZero);
+    assert_eq!(error.statement_index, 6);
+    assert_eq!(error.stack.iter().map(|call| call.statement_index).collect::<Vec<_>>(), [10]);
+
+    assert!(crate::parse("[~>] f").is_err());
+}
+
+#[test]
+fn repeated_options() {
+    // options written twice still lex one by one: `{<<}` toggles the assignment off again, `[<<]` defines and `[>>]` jumps
+    test_run_assert!(
+        "|< a <= 5 {<<%} {<%} {%}" => "5\n5\n",
+        "|< n <= 2 [<<] loop |< n - 1 {%} ? [>>] loop" => "1\n0\n",
+        "|< a <= 1 |> b <= 2 <| {%} .<< 1 {%}" => "1\n2\n",
+    );
+}
+
+#[test]
+fn pointer_stack() {
+    test_engines_assert!(
+        // `|>` saves the pointer, `@` is the saved one and `<|` goes back to it
+        "|< a <= 1 |> b <= 2 {@} |< c <= §@ {%} <| {@} <| {@}" => "b\n1\na\na\n",
+        "|< a |> b <= @ {%} |> @ {@} |< c <=> @ {%} |< §b {%}" => "a\na\na\nnull\n",
+        // `<|>` swaps the pointer with the saved one, so both can be used without going through memory
+        "|< sum <= 0 |> i <= 10 [<] loop <|> + §@ <|> - 1 ? [>] loop <| {%}" => "55\n",
+        "|< a <|> <| {@}" => "a\n",
+    );
+}
+
+#[test]
+fn bitwise() {
+    test_engines_assert!(
+        "<= 12 .& 10 {%} .| 5 {%} .>-< 3 {%} .~ 7 {%}" => "8\n13\n14\n-8\n",
+        // integers act like two's complement numbers, right shifts round down and negative counts shift the other way
+        "<= -12 .& 255 {%} .| -256 {%} .>> 3 {%} |< a <= -7 .>> 1 {%} .<< -1 {%}" => "244\n-12\n-2\n-4\n-2\n",
+        // the logical operators still only look at truthiness
+        "<= 12 & 10 {%} | 0 {%} >-< 3 {%}" => "true\ntrue\nfalse\n",
+        "<= 5 .<< 126 {%}" => "425352958651173079329218259289710264320\n",
+        // big integers mixed with small ones
+        "<= 2 ^ 130 .| 1 .>-< 1 {%}" => "1361129467683753853853498429727072845824\n",
+        "|< a <= 2 ^ 128 .~ §a {%} .~ §a {%}" => "-340282366920938463463374607431768211457\n340282366920938463463374607431768211456\n",
+        "<= -5 .>> 1000000000000 {%} <= 2 ^ 200 .>> 1000000000000 {%}" => "-1\n0\n",
+        // only integers have bits
+        "<= 1.5 .<< 1 {%} <= 1 .& true {%} .~ /a/ {%}" => "null\nnull\nnull\n",
+    );
+
+    // shifts past 128 bits become big integers instead of overflowing, and come back when they fit again
+    let result = |code: &str| run_engines(code, &[], |_| {}).1.memory().get(&Value::Null).cloned();
+    assert_eq!(result("<= 1 .<< 127"), Some(Value::Integer(Integer::from(1) << 127)));
+    assert_eq!(result("<= -1 .<< 127"), Some(Value::SmallInt(i128::MIN)));
+    assert_eq!(result("<= 3 .<< 200 .>> 199"), Some(Value::SmallInt(6)));
+    assert_eq!(result("<= 2 ^ 130 - 1 .& 255"), Some(Value::SmallInt(255)));
+}
@@ -0,0 +1,75 @@
+use logos::Logos;
+
+use crate::{fmt::format, token::{self, Piece, Token}};
+
+fn examples() -> Vec<(String, String)> {
+    crate::golden::discover(&["examples".into()]).unwrap()
+        .into_iter()
+        .map(|path| (path.display().to_string(), std::fs::read_to_string(path).unwrap()))
+        .collect()
+}
+
+#[test]
+fn lossless_pieces() {
+    for (name, code) in examples() {
+        let pieces = token::lossless(&code);
+        let joined = pieces.iter().map(|(_, span)| &code[span.clone()]).collect::<String>();
+        assert_eq!(joined, code, "{name}");
+    }
+
+    let code = "|< a # comment\n{%} #";
+    let pieces = token::lossless(code).into_iter().map(|(piece, _)| piece).collect::<Vec<_>>();
+    assert_eq!(pieces, [
+        Piece::Token(Token::Load), Piece::Whitespace, Piece::Token(Token::Symbol), Piece::Whitespace, Piece::Comment,
+        Piece::Whitespace, Piece::Token(Token::CurlyBracketOpen), Piece::Token(Token::Percent), Piece::Token(Token::CurlyBracketClose),
+        Piece::Whitespace, Piece::Comment,
+    ]);
+    assert_eq!(crate::parse(code).unwrap().len(), 2);
+}
+
+#[test]
+fn spacing() {
+    assert_eq!(format("|<a<=  §§ b\n\n\n\n{ %\\ }  ' [ < ]x").unwrap(), "|< a <= §§b\n\n{%\\} ' [<] x\n");
+    assert_eq!(format("|< a\n<= 50\n- 1").unwrap(), "|< a <= 50\n- 1\n");
+    assert_eq!(format("<= - 5 |< a ??  ???").unwrap(), "<= -5 |< a ?? ???\n");
+    assert_eq!(format("|< a <=[1/a//b/[ 2 -3]]~= [0 µ]").unwrap(), "|< a <= [1 /a/ /b/ [2 -3]] ~= [0 µ]\n");
+    assert_eq!(format("|< m <= {a=>1 [b]=>{ }}+{c=>-2}").unwrap(), "|< m <= {a => 1 [b] => {}} + {c => -2}\n");
+    assert_eq!(format("[ ~ >: ]f [<:] [> >] g {< <%}").unwrap(), "[~>:] f [<:] [>>] g {<<%}\n");
+    // `~ ~` would be `~~`
+    assert_eq!(format("|< m ~~len {~ ~%}").unwrap(), "|< m ~~ len {~ ~%}\n");
+    assert_eq!(format("|>a<=§ @ <|><|").unwrap(), "|> a <= §@ <|> <|\n");
+    assert_eq!(format("<= 1 .<<2 .& 3.5.>>1").unwrap(), "<= 1 .<< 2 .& 3.5 .>> 1\n");
+    // `%%` would be a different token
+    assert_eq!(format("{% %}").unwrap(), "{% %}\n");
+}
+
+#[test]
+fn indentation() {
+    assert_eq!(
+        format("|< a ?? \n |< b {%}\n |< c ???\n{%}\n{@} {%}\n|< d").unwrap(),
+        "|< a ??\n    |< b {%}\n|< c ???\n    {%}\n    {@} {%}\n|< d\n",
+    );
+    assert_eq!(
+        format("[<] loop\n|< i + 1 ? [>] loop\n|< temp <= §i < 10 ?\n[>] loop\n|< done").unwrap(),
+        "[<] loop\n    |< i + 1 ? [>] loop\n    |< temp <= §i < 10 ?\n[>] loop\n|< done\n",
+    );
+}
+
+#[test]
+fn comments() {
+    assert_eq!(
+        format("# start\n\n|< a   # the pointer\n<= 1 # one  \n    # own line\n{%}\n# end").unwrap(),
+        "# start\n\n|< a # the pointer\n<= 1 # one\n# own line\n{%}\n# end\n",
+    );
+}
+
+#[test]
+fn format_examples() {
+    let tokens = |code: &str| Token::lexer(code).collect::<Vec<_>>();
+
+    for (name, code) in examples() {
+        let formatted = format(&code).unwrap();
+        assert_eq!(tokens(&formatted), tokens(&code), "{name}");
+        assert_eq!(format(&formatted).unwrap(), formatted, "{name}");
+    }
+}
@@ -0,0 +1,80 @@
+use std::path::{Path, PathBuf};
+
+use crate::golden::{self, Case, Outcome};
+
+#[test]
+fn header_comments() {
+    let directory = Path::new("target/golden");
+    std::fs::create_dir_all(directory).unwrap();
+    let path = directory.join("header.specky");
+    std::fs::write(&path, "# adds two numbers\n# stdin: 2\n# stdin:  3\n\n# stdout: 5\n# max-steps: 100\n|< a ' |< b ' |< a + §b {%}\n# stdout: 6\n").unwrap();
+    let _ = std::fs::remove_file(path.with_extension("stdout"));
+
+    let case = Case::load(&path).unwrap();
+    assert_eq!(case.input, ["2", " 3"]);
+    assert_eq!(case.expected.as_deref(), Some("5\n"));
+    assert_eq!(case.limits.max_steps, Some(100));
+    assert_eq!(case.check(), Outcome::Passed);
+
+    // the companion file wins, blessing writes it
+    case.bless("4\n").unwrap();
+    let case = Case::load(&path).unwrap();
+    assert_eq!(case.check(), Outcome::Failed { expected: Some("4\n".to_string()), actual: "5\n".to_string() });
+
+    // without an expected output the program fails, unless it says why
+    std::fs::write(&path, "{}\n").unwrap();
+    let _ = std::fs::remove_file(path.with_extension("stdout"));
+    assert_eq!(Case::load(&path).unwrap().check(), Outcome::Failed { expected: None, actual: "\n".to_string() });
+    std::fs::write(&path, "# unchecked: prints the time\n{}\n").unwrap();
+    assert_eq!(Case::load(&path).unwrap().check(), Outcome::Unchecked("prints the time".to_string()));
+
+    std::fs::write(&path, "# max-steps: many\n").unwrap();
+    assert_eq!(Case::load(&path).unwrap_err(), "invalid max-steps: many");
+}
+
+#[test]
+fn limits_and_errors() {
+    let case = |code: &str| Case {
+        path: PathBuf::from("test.specky"),
+        code: code.to_string(),
+        input: vec![],
+        expected: None,
+        unchecked: None,
+        limits: crate::run::Limits { max_steps: Some(10), ..Default::default() },
+        clock: crate::run::Clock::Fixed,
+    };
+
+    // programs that don't end are compared until their `# max-steps:`, missing input is an empty line
+    assert_eq!(case("[<] a |< b ' {%} [>] a").run().unwrap(), "null\nnull\n");
+    // without one, or with any other limit, not ending is an error
+    let endless = Case { limits: crate::run::Limits::default(), ..case("[<] a |< b ' {%} [>] a") };
+    assert_eq!(endless.run().unwrap_err(), format!("Limit exceeded: more than {} statements executed (statement 4)", golden::DEFAULT_MAX_STEPS));
+    let deep = Case {
+        limits: crate::run::Limits { max_steps: Some(10), max_call_depth: Some(2), ..Default::default() },
+        ..case("[<] f [>:] f")
+    };
+    assert_eq!(deep.run().unwrap_err(), "Limit exceeded: more than 2 nested calls (statement 1)");
+    assert_eq!(case("|< a <= 1 \\ 0").run().unwrap_err(), "Division by zero (statement 2)");
+    // µ doesn't move
+    assert_eq!(case("|< a <= µ {%} {%$}").run().unwrap(), "0ns\n0\n");
+}
+
+#[test]
+fn first_difference() {
+    assert_eq!(golden::first_difference("a\nb\n", "a\nb\n"), None);
+    assert_eq!(golden::first_difference("a\nb\n", "a\nc\n"), Some((2, Some("b\n"), Some("c\n"))));
+    assert_eq!(golden::first_difference("a\n", "a\nb"), Some((2, None, Some("b"))));
+}
+
+/// Every example still prints its expected output, the ones without it say why
+#[test]
+fn examples() {
+    for path in golden::discover(&[PathBuf::from("examples")]).unwrap() {
+        let case = Case::load(&path).unwrap();
+        match case.check() {
+            Outcome::Passed => {},
+            Outcome::Unchecked(reason) => assert!(!reason.is_empty(), "{}", path.display()),
+            outcome => panic!("{}: {outcome:?}", path.display()),
+        }
+    }
+}
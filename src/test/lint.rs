@@ -0,0 +1,45 @@
+use crate::lint::{lint, Rule};
+
+fn rules(code: &str) -> Vec<Rule> {
+    lint(code, &crate::parse(code).unwrap()).into_iter().map(|warning| warning.rule).collect()
+}
+
+#[test]
+fn lint_rules() {
+    assert_eq!(rules("[<] loop |< a + 1 ? [>] loop"), []);
+    assert_eq!(rules("[>] nowhere"), [Rule::UndefinedLabel]);
+    assert_eq!(rules("|< address <= 3 [>] address"), []);
+    assert_eq!(rules("|< a ???"), [Rule::ConditionPastEnd]);
+    assert_eq!(rules("|< a ??? {%} {%} {%}"), []);
+    assert_eq!(rules("[<] a [<] b [<] a"), [Rule::DuplicateLabel]);
+    assert_eq!(rules("{@%} {%} {% %} {+*}"), [Rule::ConflictingLogOptions, Rule::ConflictingLogOptions]);
+    assert_eq!(rules("[<] loop {%} [>] loop {@} {@} [<] end {%}"), [Rule::Unreachable]);
+    assert_eq!(rules("[<] loop {%} ? [>] loop {@}"), []);
+    assert_eq!(rules("[>:] nowhere"), [Rule::UndefinedLabel]);
+    assert_eq!(rules("[<] f {%} [<:] [>:] f [~>:] f {@}"), []);
+}
+
+#[test]
+fn lint_functions() {
+    let code = "
+        [<] function
+            |< return + 1
+        [>] return
+        [<] return [>] function {%}
+        [<] return [>] function {%}
+    ";
+    assert_eq!(rules(code), []);
+}
+
+#[test]
+fn lint_allow_comments() {
+    assert_eq!(rules("[>] nowhere # lint: allow(undefined-label)"), []);
+    assert_eq!(rules("# lint: allow(condition-past-end, undefined-label)\n[>] nowhere ??"), []);
+    assert_eq!(rules("# lint: allow(unreachable)\n[>] nowhere"), [Rule::UndefinedLabel]);
+    assert_eq!(rules("# lint: allow(undefined-label)\n\n[>] nowhere"), [Rule::UndefinedLabel]);
+
+    let code = "[<] a\n[<] a";
+    let warning = &lint(code, &crate::parse(code).unwrap())[0];
+    assert_eq!(warning.area.line(code), 2);
+    assert_eq!(warning.related.as_ref().unwrap().1.line(code), 1);
+}
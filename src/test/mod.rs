@@ -1,5 +1,15 @@
 mod features;
 mod examples;
+mod fmt;
+mod lint;
+mod cfg;
+mod bytecode;
+mod compile;
+mod golden;
+
+use std::collections::HashMap;
+
+use crate::{ast::Value, run::{BufferIo, Engine, Limits, RunResult}, Interpreter, Status};
 
 #[macro_export]
 macro_rules! test_read {
@@ -13,18 +23,12 @@ macro_rules! test_run {
     ($string:expr $(, [$($input:expr),* $(,)?])?) => {
         {
             let temp = &$string;
-            let mut parser = crate::parser::Parser::new(temp);
-            #[allow(unused_mut)]
-            let mut parsed = parser.parse_statements().unwrap();
+            let parsed = $crate::parse(temp).unwrap();
 
-            $($(
-                if let Some(index) = parsed.iter().position(|v| matches!(v, crate::ast::Statement::Input)) {
-                    parsed[index] = $input;
-                }
-            )*)?
+            let input: Vec<String> = vec![$($($input.to_string()),*)?];
 
             // println!("{:#?}", parsed);
-            let mut ran = crate::run::run(&parsed);
+            let mut ran = $crate::run_with_io(&parsed, $crate::run::BufferIo::with_input(input));
 
             ran.stdout = ran.stdout
                 .trim_start_matches("input your brainfuck program: do you want debug mode? ").to_string();
@@ -43,3 +47,55 @@ macro_rules! test_run_assert {
         )*
     };
 }
+
+#[macro_export]
+macro_rules! test_engines_assert {
+    ($($input:expr => $expected:expr),*$(,)?) => {
+        $(
+            assert_eq!($crate::test::both_engines($input, &[], |_| {}), $expected, "{}", $input);
+        )*
+    };
+}
+
+/// The memory with the times written relative to what `µ` reads now,
+/// which is the same for both engines with virtual clocks even though they got created at different times
+fn relative_memory(interpreter: &Interpreter<BufferIo>) -> HashMap<Value, Value> {
+    let now = interpreter.now();
+    interpreter.memory().iter()
+        .map(|(key, value)| match value {
+            Value::Time(Some(time)) if *time >= now => (key.clone(), Value::Text(format!("µ + {:?}", *time - now))),
+            Value::Time(Some(time)) => (key.clone(), Value::Text(format!("µ - {:?}", now - *time))),
+            value => (key.clone(), value.clone()),
+        })
+        .collect()
+}
+
+/// Runs `code` with both engines after `setup`, checking that they end up in the same state.
+/// Returns the result and the interpreter of the bytecode.
+pub fn run_engines(code: &str, input: &[&str], setup: impl Fn(&mut Interpreter<BufferIo>)) -> (RunResult<Status>, Interpreter<BufferIo>) {
+    let run = |engine| {
+        let mut interpreter = Interpreter::with_io(crate::parse(code).unwrap(), BufferIo::with_input(input.iter().copied()));
+        interpreter.set_engine(engine);
+        interpreter.set_limits(Limits { max_steps: Some(300_000), ..Default::default() });
+        setup(&mut interpreter);
+        let result: RunResult<Status> = interpreter.run_to_end();
+        (interpreter, result)
+    };
+
+    let (ast, ast_result) = run(Engine::Ast);
+    let (bytecode, bytecode_result) = run(Engine::Bytecode);
+
+    assert_eq!(ast_result, bytecode_result, "{code}");
+    assert_eq!(ast.stdout(), bytecode.stdout(), "{code}");
+    assert_eq!(relative_memory(&ast), relative_memory(&bytecode), "{code}");
+    assert_eq!(ast.pointer(), bytecode.pointer(), "{code}");
+    assert_eq!(ast.program_counter(), bytecode.program_counter(), "{code}");
+    assert_eq!(ast.steps(), bytecode.steps(), "{code}");
+
+    (bytecode_result, bytecode)
+}
+
+/// Like [`run_engines`], with what got printed
+pub fn both_engines(code: &str, input: &[&str], setup: impl Fn(&mut Interpreter<BufferIo>)) -> String {
+    run_engines(code, input, setup).1.stdout().to_string()
+}
@@ -0,0 +1,52 @@
+// Generated by `speckylang compile --target js` from examples/factorial.specky
+// Run with `node <this file>.mjs`, or import it and `await run({ write, input, decimal })`
+
+
+const LINES = [1, 2, 4, 5, 7, 8, 9, 10, 11, 13, 14, 16, 17];
+
+export async function run(host = {}) {
+    const rt = new Runtime(host);
+    const d = rt.decimal;
+    const constants = [
+        symbol("number"),
+        integer(10n),
+        symbol("product"),
+        integer(1n),
+        symbol("repeat"),
+    ];
+    let index = 0;
+
+    for (;;) {
+        const current = index;
+        try {
+            switch (index) {
+                case 0: rt.load(constants[0], 0); index = 1; break;
+                case 1: rt.assign(constants[1], 0); index = 2; break;
+                case 2: rt.load(constants[2], 0); index = 3; break;
+                case 3: rt.assign(constants[3], 0); index = 4; break;
+                case 4: rt.define(constants[4], 0, 4); index = 5; break;
+                case 5: rt.load(constants[2], 0); index = 6; break;
+                case 6: rt.operate(Operator.Times, constants[0], 1); index = 7; break;
+                case 7: rt.load(constants[0], 0); index = 8; break;
+                case 8: rt.operate(Operator.Minus, constants[3], 0); index = 9; break;
+                case 9: index = rt.truthy() ? 10 : 11; break;
+                case 10: index = rt.jump(constants[4], 0, 11); break;
+                case 11: rt.load(constants[2], 0); index = 12; break;
+                case 12: rt.log({ kind: "value", reader: 1, pretty: false, sort: false, reverse: false, newline: true, space: 0, vertical: false, assign: false }); index = 13; break;
+                default: return;
+            }
+        } catch (error) {
+            if (error instanceof SpeckyError) {
+                error.statement = current;
+                error.line = LINES[current];
+                error.calls = rt.calls.map(({ call }) => ({ statement: call, line: LINES[call] }));
+            }
+            throw error;
+        }
+    }
+}
+
+// `node <this file>.mjs` runs the program with stdin and stdout
+if (globalThis.process?.argv?.[1] && import.meta.url === (await import("node:url")).pathToFileURL(process.argv[1]).href) {
+    await runMain(run);
+}
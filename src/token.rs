@@ -1,3 +1,5 @@
+use std::ops::Range;
+
 use logos::Logos;
 
 #[derive(Logos, Debug, PartialEq, Clone, Copy)]
@@ -12,6 +14,20 @@ pub enum Token {
     #[token("<=>")]
     Swap,
 
+    // pointer stack
+    #[token("|>")]
+    Push,
+    #[token("<|")]
+    Pop,
+    #[token("<|>")]
+    Exchange,
+
+    // calls, not `>>` and `<<`: `[>>]` jumps and `{<<}` toggles twice like they always did
+    #[token(">:")]
+    Call,
+    #[token("<:")]
+    Return,
+
     // <operator> <value>
     #[token("&")]
     And,
@@ -19,6 +35,18 @@ pub enum Token {
     Or,
     #[token(">-<")]
     Xor,
+    #[token(".&")]
+    BitAnd,
+    #[token(".|")]
+    BitOr,
+    #[token(".>-<")]
+    BitXor,
+    #[token(".~")]
+    BitNot,
+    #[token(".<<")]
+    ShiftLeft,
+    #[token(".>>")]
+    ShiftRight,
     #[token("+")]
     Plus,
     #[token("-")]
@@ -71,6 +99,10 @@ pub enum Token {
     Backslash,
     #[token("~")]
     Tilde,
+    #[token("~=")]
+    TildeEqual,
+    #[token("~~")]
+    TTilde,
 
     // <operator> <unaryoperator> <value>
     #[token("'")]
@@ -99,6 +131,47 @@ pub enum Token {
     #[regex(r"[0-9]+\.[0-9]+")]
     FloatLiteral,
 
-    #[regex(r"[ \r\t\n]+|(#[^\n]+\n)", logos::skip, allow_greedy = true)]
+    #[regex(r"[ \r\t\n]+|(#[^\n]*)", logos::skip, allow_greedy = true)]
     Error,
 }
+
+/// A token, or the text between tokens that the parser skips
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Piece {
+    Token(Token),
+    Whitespace,
+    /// From `#` to the end of the line (without the newline)
+    Comment,
+    /// Characters that aren't part of any token
+    Invalid,
+}
+
+/// Splits the code into pieces that cover all of it, so that it can be re-emitted without losing anything
+pub fn lossless(code: &str) -> Vec<(Piece, Range<usize>)> {
+    let mut pieces = vec![];
+    let mut end = 0;
+
+    let push_skipped = |pieces: &mut Vec<_>, start: usize, end: usize| {
+        let mut index = start;
+        while index < end {
+            let rest = &code[index..end];
+            let (piece, length) = match rest.strip_prefix('#') {
+                Some(comment) => (Piece::Comment, 1 + comment.find('\n').unwrap_or(comment.len())),
+                None => (Piece::Whitespace, rest.find('#').unwrap_or(rest.len())),
+            };
+            pieces.push((piece, index..index + length));
+            index += length;
+        }
+    };
+
+    let mut lexer = Token::lexer(code);
+    while let Some(token) = lexer.next() {
+        let span = lexer.span();
+        push_skipped(&mut pieces, end, span.start);
+        pieces.push((token.map(Piece::Token).unwrap_or(Piece::Invalid), span.clone()));
+        end = span.end;
+    }
+    push_skipped(&mut pieces, end, code.len());
+
+    pieces
+}